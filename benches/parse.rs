@@ -0,0 +1,173 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use tinyserde::parser::{JsonParser, JsonValue};
+use tinyserde::JsonBorrowedParser;
+
+const TARGET_SIZE: usize = 10 * 1024 * 1024;
+const STRING_COUNT: usize = 10_000;
+const STRING_RUNS: u32 = 100;
+const FRAGMENT_COUNT: usize = 1_000_000;
+const CLONE_RUNS: u32 = 10;
+#[cfg(feature = "bumpalo")]
+const ARENA_DOCUMENT_COUNT: usize = 100_000;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn generate_document(size: usize) -> String {
+    let mut builder = String::from("[");
+    let mut i = 0;
+    while builder.len() < size {
+        if i > 0 {
+            builder.push(',');
+        }
+        builder.push_str(&format!(
+            "\n  {{ \"id\": {}, \"name\": \"item number {}\", \"active\": {}, \"parent\": null }}",
+            i,
+            i,
+            i % 2 == 0
+        ));
+        i += 1;
+    }
+    builder.push_str("\n]");
+    builder
+}
+
+fn generate_strings(count: usize) -> String {
+    let strings: Vec<String> = (0..count).map(|i| format!("\"string value number {}\"", i)).collect();
+    format!("[{}]", strings.join(", "))
+}
+
+// Compares the owned parser, which copies the input and allocates every
+// string, with the borrowed parser, which slices strings out of the input.
+fn compare_string_parsing() {
+    let input = generate_strings(STRING_COUNT);
+    let start = Instant::now();
+    for _ in 0..STRING_RUNS {
+        JsonParser::new(input.as_str()).parse().expect("Could not parse benchmark input.");
+    }
+    let owned = start.elapsed() / STRING_RUNS;
+    let start = Instant::now();
+    for _ in 0..STRING_RUNS {
+        JsonBorrowedParser::new(&input).parse().expect("Could not parse benchmark input.");
+    }
+    let borrowed = start.elapsed() / STRING_RUNS;
+    println!(
+        "parsed {} strings in {:.3?} owned, {:.3?} borrowed ({:.1}x)",
+        STRING_COUNT,
+        owned,
+        borrowed,
+        owned.as_secs_f64() / borrowed.as_secs_f64()
+    );
+}
+
+// Compares creating a parser for every small document with resetting
+// one parser, which copies each document into the same input buffer.
+fn compare_reset() {
+    let fragments = ["[1, 2, 3]", "true", "-12.5", "[[], [null]]"];
+    let measure = |parse: &mut dyn FnMut(&str)| {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for i in 0..FRAGMENT_COUNT {
+            parse(fragments[i % fragments.len()]);
+        }
+        (start.elapsed(), ALLOCATIONS.load(Ordering::Relaxed) - allocations)
+    };
+    let (new_time, new_allocations) = measure(&mut |fragment| {
+        JsonParser::new(fragment).parse().expect("Could not parse benchmark input.");
+    });
+    let mut parser = JsonParser::new("");
+    let (reset_time, reset_allocations) = measure(&mut |fragment| {
+        parser.reset_from_str(fragment);
+        parser.parse().expect("Could not parse benchmark input.");
+    });
+    println!(
+        "parsed {} documents in {:.3?} with {} allocations using new, {:.3?} with {} allocations using reset",
+        FRAGMENT_COUNT, new_time, new_allocations, reset_time, reset_allocations
+    );
+}
+
+// Compares the borrowed parser, which allocates a Vec or HashMap for every
+// container, with parsing into an arena that is reset between documents.
+#[cfg(feature = "bumpalo")]
+fn compare_arena() {
+    let fragments = [r#"{"id": 1, "tags": ["a", "b"]}"#, r#"[{"x": 1.5}, {"y": null}]"#, r#"{"name": "esc\taped", "ok": true}"#];
+    let measure = |parse: &mut dyn FnMut(&str)| {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for i in 0..ARENA_DOCUMENT_COUNT {
+            parse(fragments[i % fragments.len()]);
+        }
+        (start.elapsed(), ALLOCATIONS.load(Ordering::Relaxed) - allocations)
+    };
+    let (borrowed_time, borrowed_allocations) = measure(&mut |fragment| {
+        JsonBorrowedParser::new(fragment).parse().expect("Could not parse benchmark input.");
+    });
+    let mut bump = bumpalo::Bump::new();
+    let (arena_time, arena_allocations) = measure(&mut |fragment| {
+        bump.reset();
+        JsonParser::new_in_arena(fragment, &bump).parse().expect("Could not parse benchmark input.");
+    });
+    println!(
+        "parsed {} documents in {:.3?} with {} allocations borrowed, {:.3?} with {} allocations in an arena",
+        ARENA_DOCUMENT_COUNT, borrowed_time, borrowed_allocations, arena_time, arena_allocations
+    );
+}
+
+// Compares the recursive `clone` with `deep_clone`, which keeps its own
+// stack, on a document that is wide rather than deep.
+fn compare_clone(value: &JsonValue) {
+    let start = Instant::now();
+    for _ in 0..CLONE_RUNS {
+        drop(value.clone());
+    }
+    let recursive = start.elapsed() / CLONE_RUNS;
+    let start = Instant::now();
+    for _ in 0..CLONE_RUNS {
+        drop(value.deep_clone());
+    }
+    let iterative = start.elapsed() / CLONE_RUNS;
+    println!(
+        "cloned the document in {:.3?} with clone, {:.3?} with deep_clone ({:.2}x)",
+        recursive,
+        iterative,
+        iterative.as_secs_f64() / recursive.as_secs_f64()
+    );
+}
+
+fn main() {
+    let input = generate_document(TARGET_SIZE);
+    let size = input.len();
+    let mut parser = JsonParser::new(input);
+    let start = Instant::now();
+    let value = parser.parse().expect("Could not parse benchmark input.");
+    let elapsed = start.elapsed();
+    let megabytes = size as f64 / (1024.0 * 1024.0);
+    println!(
+        "parsed {:.1} MB in {:.3?} ({:.1} MB/s)",
+        megabytes,
+        elapsed,
+        megabytes / elapsed.as_secs_f64()
+    );
+    compare_string_parsing();
+    compare_reset();
+    compare_clone(&value);
+    #[cfg(feature = "bumpalo")]
+    compare_arena();
+}
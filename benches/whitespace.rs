@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use tinyserde::whitespace::{count_whitespace, count_whitespace_scalar};
+use tinyserde::JsonParser;
+
+const TARGET_SIZE: usize = 1024 * 1024;
+
+// Pretty-printed with deep indentation, so that most of the input is
+// whitespace.
+fn generate_document(size: usize) -> String {
+    let indent = " ".repeat(64);
+    let mut builder = String::from("[");
+    let mut i = 0;
+    while builder.len() < size {
+        if i > 0 {
+            builder.push(',');
+        }
+        builder.push_str(&format!("\n{indent}{{\n{indent}{indent}\"id\": {i},\n{indent}{indent}\"ok\": true\n{indent}}}"));
+        i += 1;
+    }
+    builder.push_str("\n]");
+    builder
+}
+
+// Skips every run of whitespace in `input` with `count`, stepping over the
+// tokens in between.
+fn skip_all(input: &[u8], count: fn(&[u8]) -> usize) -> usize {
+    let mut cursor = 0;
+    let mut runs = 0;
+    while cursor < input.len() {
+        cursor += count(&input[cursor..]);
+        cursor += input[cursor..].iter().position(|b| b.is_ascii_whitespace()).unwrap_or(input.len() - cursor);
+        runs += 1;
+    }
+    runs
+}
+
+fn whitespace(c: &mut Criterion) {
+    let input = generate_document(TARGET_SIZE);
+    let mut group = c.benchmark_group("whitespace");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("scalar", |b| b.iter(|| skip_all(black_box(input.as_bytes()), count_whitespace_scalar)));
+    group.bench_function("simd", |b| b.iter(|| skip_all(black_box(input.as_bytes()), count_whitespace)));
+    group.bench_function("parse", |b| b.iter(|| JsonParser::new(black_box(input.as_str())).parse().unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, whitespace);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Parsing arbitrary input must return an error rather than panic, and
+// anything that parses must print as JSON that parses back to the same
+// value.
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    if let Ok(value) = tinyserde::parse_str(&input) {
+        let output = value.to_string();
+        assert_eq!(tinyserde::parse_str(&output), Ok(value), "{:?} printed as {:?}", input, output);
+    }
+});
@@ -0,0 +1,223 @@
+use crate::borrowed::{number, BorrowedJsonValue, JsonBorrowedParser};
+use crate::config::JsonParserConfig;
+use crate::object::JsonObject;
+use crate::parser::{JsonParser, JsonValue, ParserError};
+use crate::prelude::*;
+use crate::tokenizer::Token;
+use bumpalo::collections::Vec as BumpVec;
+use bumpalo::Bump;
+
+/// A value whose arrays, objects and decoded strings live in a `Bump`
+/// arena. Strings without escape sequences are slices of the input. Nothing
+/// is freed until the arena is dropped or reset.
+///
+/// Objects keep their members in input order, including duplicate keys.
+/// `to_owned` keeps the last of any duplicates, like the owned parser.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaJsonValue<'a> {
+    Null,
+    Bool(bool),
+    Number(i64),
+    Number64(u64),
+    Float(f64),
+    String(&'a str),
+    Array(&'a [ArenaJsonValue<'a>]),
+    Object(&'a [(&'a str, ArenaJsonValue<'a>)]),
+}
+
+impl ArenaJsonValue<'_> {
+    pub fn to_owned(&self) -> JsonValue {
+        match self {
+            ArenaJsonValue::Null => JsonValue::Null,
+            ArenaJsonValue::Bool(value) => JsonValue::Bool(*value),
+            ArenaJsonValue::Number(value) => JsonValue::Number(*value),
+            ArenaJsonValue::Number64(value) => JsonValue::Number64(*value),
+            ArenaJsonValue::Float(value) => JsonValue::Float(*value),
+            ArenaJsonValue::String(value) => JsonValue::String(value.to_string().into()),
+            ArenaJsonValue::Array(array) => JsonValue::Array(array.iter().map(ArenaJsonValue::to_owned).collect()),
+            ArenaJsonValue::Object(members) => JsonValue::Object(
+                members.iter().map(|(key, value)| (key.to_string(), value.to_owned())).collect::<JsonObject>(),
+            ),
+        }
+    }
+}
+
+enum Frame<'a> {
+    Array(BumpVec<'a, ArenaJsonValue<'a>>),
+    Object(BumpVec<'a, (&'a str, ArenaJsonValue<'a>)>, Option<&'a str>),
+}
+
+impl<'a> Frame<'a> {
+    fn into_value(self) -> ArenaJsonValue<'a> {
+        match self {
+            Frame::Array(array) => ArenaJsonValue::Array(array.into_bump_slice()),
+            Frame::Object(members, _) => ArenaJsonValue::Object(members.into_bump_slice()),
+        }
+    }
+}
+
+/// Parses strict JSON into an `ArenaJsonValue` allocated from `bump`.
+pub struct JsonParserArena<'a> {
+    parser: JsonBorrowedParser<'a>,
+    bump: &'a Bump,
+}
+
+impl JsonParser {
+    pub fn new_in_arena<'a>(input: &'a str, bump: &'a Bump) -> JsonParserArena<'a> {
+        Self::with_config_in_arena(input, JsonParserConfig::default(), bump)
+    }
+
+    /// Applies the limits of `config`. The input is read as strict JSON
+    /// whatever the mode, and the other options are ignored.
+    pub fn with_config_in_arena<'a>(input: &'a str, config: JsonParserConfig, bump: &'a Bump) -> JsonParserArena<'a> {
+        JsonParserArena { parser: JsonBorrowedParser::with_config(input, config), bump }
+    }
+}
+
+impl<'a> JsonParserArena<'a> {
+    pub fn parse(&mut self) -> Result<ArenaJsonValue<'a>, ParserError> {
+        let value = self.parse_value()?;
+        if let Some((at_byte, _)) = self.parser.next_token()? {
+            return Err(self.parser.syntax_error(at_byte, "Expected the end of input"));
+        }
+        Ok(value)
+    }
+
+    // Moves a decoded string into the arena.
+    fn alloc_str(&self, string: Cow<'a, str>) -> &'a str {
+        match string {
+            Cow::Borrowed(string) => string,
+            Cow::Owned(string) => self.bump.alloc_str(&string),
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<&'a str, ParserError> {
+        let key = self.parser.parse_key()?;
+        Ok(self.alloc_str(key))
+    }
+
+    fn parse_value(&mut self) -> Result<ArenaJsonValue<'a>, ParserError> {
+        let mut stack: BumpVec<'a, Frame<'a>> = BumpVec::new_in(self.bump);
+        'value: loop {
+            let token = self.parser.expect_token("value")?;
+            self.parser.count_value(token.1, stack.len())?;
+            let mut value = match token {
+                (_, Token::Null) => ArenaJsonValue::Null,
+                (_, Token::Bool(value)) => ArenaJsonValue::Bool(value),
+                (_, Token::Number(raw)) => match number(raw)? {
+                    BorrowedJsonValue::Float(value) => ArenaJsonValue::Float(value),
+                    BorrowedJsonValue::Number(value) => ArenaJsonValue::Number(value),
+                    BorrowedJsonValue::Number64(value) => ArenaJsonValue::Number64(value),
+                    _ => unreachable!(),
+                },
+                (_, Token::String(raw)) => ArenaJsonValue::String(self.alloc_str(self.parser.string(raw)?)),
+                (_, Token::LBracket) if self.parser.peek_token()? == Some(Token::RBracket) => {
+                    self.parser.next_token()?;
+                    ArenaJsonValue::Array(&[])
+                }
+                (_, Token::LBracket) => {
+                    stack.push(Frame::Array(BumpVec::new_in(self.bump)));
+                    continue;
+                }
+                (_, Token::LBrace) if self.parser.peek_token()? == Some(Token::RBrace) => {
+                    self.parser.next_token()?;
+                    ArenaJsonValue::Object(&[])
+                }
+                (_, Token::LBrace) => {
+                    let key = self.parse_key()?;
+                    stack.push(Frame::Object(BumpVec::new_in(self.bump), Some(key)));
+                    continue;
+                }
+                (at_byte, _) => return Err(self.parser.syntax_error(at_byte, "Expected a value")),
+            };
+            loop {
+                let closed = match stack.last_mut() {
+                    None => return Ok(value),
+                    Some(Frame::Array(array)) => {
+                        array.push(value);
+                        self.parser.check_array_length(array.len())?;
+                        match self.parser.expect_token("',' or ']'")? {
+                            (_, Token::Comma) => false,
+                            (_, Token::RBracket) => true,
+                            (at_byte, _) => return Err(self.parser.syntax_error(at_byte, "Expected ',' or ']'")),
+                        }
+                    }
+                    Some(Frame::Object(members, key)) => {
+                        members.push((key.take().unwrap(), value));
+                        self.parser.check_object_size(members.len())?;
+                        match self.parser.expect_token("',' or '}'")? {
+                            (_, Token::Comma) => {
+                                *key = Some(self.parse_key()?);
+                                false
+                            }
+                            (_, Token::RBrace) => true,
+                            (at_byte, _) => return Err(self.parser.syntax_error(at_byte, "Expected ',' or '}'")),
+                        }
+                    }
+                };
+                if !closed {
+                    continue 'value;
+                }
+                value = stack.pop().unwrap().into_value();
+            }
+        }
+    }
+}
+
+#[test]
+fn test_arena_parse_matches_owned_parse() {
+    let inputs = [
+        "null",
+        "[]",
+        "{}",
+        r#"{"a": [1, -2, 2.5e3, true, false, null], "b": {"c": "d", "e": {}}}"#,
+        r#"["plain", "esc\n\"aped\"", "\u00e9\ud83d\ude00", "\"nested\" \"quoted\" words\""]"#,
+        r#"{"dup": 1, "k\u00e9y": 2, "dup": 3}"#,
+        "-9223372036854775808",
+        "[9223372036854775808, 18446744073709551615]",
+    ];
+    let bump = Bump::new();
+    for input in inputs {
+        let value = JsonParser::new_in_arena(input, &bump).parse().unwrap();
+        assert_eq!(value.to_owned(), crate::parse_str(input).unwrap(), "input: {:?}", input);
+    }
+    for input in ["", "[1, 2", "{\"a\" 1}", "[1] 2", "\"\\ud800\"", "18446744073709551616"] {
+        assert_eq!(
+            JsonParser::new_in_arena(input, &bump).parse(),
+            JsonBorrowedParser::new(input).parse().map(|_| ArenaJsonValue::Null),
+            "input: {:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_arena_strings_borrow_unless_escaped() {
+    let input = String::from(r#"{"key": ["plain", "tab\tbed"]}"#);
+    let bump = Bump::new();
+    let value = JsonParser::new_in_arena(&input, &bump).parse().unwrap();
+    let ArenaJsonValue::Object([("key", ArenaJsonValue::Array([plain, escaped]))]) = value else { panic!("{:?}", value) };
+    let ArenaJsonValue::String(plain) = plain else { panic!("{:?}", plain) };
+    assert!(input.as_bytes().as_ptr_range().contains(&plain.as_ptr()));
+    assert_eq!(*escaped, ArenaJsonValue::String("tab\tbed"));
+    assert!(bump.allocated_bytes() > 0);
+}
+
+#[test]
+fn test_arena_parse_applies_limits() {
+    let bump = Bump::new();
+    let deep = "[".repeat(200_000);
+    let max_depth = JsonParserConfig::default().max_depth;
+    assert_eq!(JsonParser::new_in_arena(&deep, &bump).parse(), Err(ParserError::DepthLimitExceeded { limit: max_depth }));
+    let config = JsonParserConfig { max_string_length: 3, max_array_length: 2, max_object_size: 1, ..Default::default() };
+    for input in ["\"abcd\"", "[1, 2, 3]", "{\"a\": 1, \"b\": 2}", "[{\"abcd\": 1}]"] {
+        assert_eq!(
+            JsonParser::with_config_in_arena(input, config.clone(), &bump).parse(),
+            JsonBorrowedParser::with_config(input, config.clone()).parse().map(|_| ArenaJsonValue::Null),
+            "input: {:?}",
+            input
+        );
+    }
+    // Members with the same key are all kept, so they all count.
+    assert_eq!(JsonParser::with_config_in_arena("{\"a\": 1, \"a\": 2}", config, &bump).parse(), Err(ParserError::ObjectSizeExceeded { limit: 1 }));
+}
@@ -0,0 +1,134 @@
+use crate::parser::JsonValue;
+use crate::prelude::*;
+
+/// The elements of a `JsonValue::Array`, borrowed from a value with
+/// `as_json_array` or built from a `Vec`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[repr(transparent)]
+pub struct JsonArray(Vec<JsonValue>);
+
+impl JsonArray {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn from_vec_ref(array: &Vec<JsonValue>) -> &JsonArray {
+        // SAFETY: JsonArray is a transparent wrapper around the Vec.
+        unsafe { &*(array as *const Vec<JsonValue> as *const JsonArray) }
+    }
+
+    pub(crate) fn from_vec_mut(array: &mut Vec<JsonValue>) -> &mut JsonArray {
+        // SAFETY: JsonArray is a transparent wrapper around the Vec.
+        unsafe { &mut *(array as *mut Vec<JsonValue> as *mut JsonArray) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&JsonValue> {
+        self.0.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut JsonValue> {
+        self.0.get_mut(index)
+    }
+
+    pub fn push(&mut self, value: JsonValue) {
+        self.0.push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<JsonValue> {
+        self.0.pop()
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, JsonValue> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, JsonValue> {
+        self.0.iter_mut()
+    }
+
+    pub fn into_vec(self) -> Vec<JsonValue> {
+        self.0
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonArray {
+    fn from(array: Vec<JsonValue>) -> Self {
+        JsonArray(array)
+    }
+}
+
+impl From<JsonArray> for JsonValue {
+    fn from(array: JsonArray) -> Self {
+        JsonValue::Array(array.0)
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonArray {
+    type Item = &'a JsonValue;
+    type IntoIter = core::slice::Iter<'a, JsonValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut JsonArray {
+    type Item = &'a mut JsonValue;
+    type IntoIter = core::slice::IterMut<'a, JsonValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl IntoIterator for JsonArray {
+    type Item = JsonValue;
+    type IntoIter = alloc::vec::IntoIter<JsonValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[test]
+fn test_json_array_borrowed_from_value() {
+    let mut value = crate::parse_str(r#"[1, "two", [3]]"#).unwrap();
+    let array = value.as_json_array().unwrap();
+    assert_eq!(array.len(), 3);
+    assert!(!array.is_empty());
+    assert_eq!(array.get(1), Some(&JsonValue::from("two")));
+    assert_eq!(array.get(3), None);
+    assert_eq!(array.iter().filter(|value| value.is_array()).count(), 1);
+
+    let array = value.as_json_array_mut().unwrap();
+    assert_eq!(array.pop(), Some(crate::parse_str("[3]").unwrap()));
+    array.push(JsonValue::Null);
+    for element in array.iter_mut() {
+        if let JsonValue::Number(n) = element {
+            *n *= 10;
+        }
+    }
+    *array.get_mut(1).unwrap() = JsonValue::Bool(true);
+    assert_eq!(value, crate::parse_str("[10, true, null]").unwrap());
+    assert!(JsonValue::Null.as_json_array().is_none());
+    assert!(crate::parse_str("{}").unwrap().as_json_array().is_none());
+}
+
+#[test]
+fn test_json_array_owned() {
+    let mut array = JsonArray::new();
+    assert!(array.is_empty());
+    array.push(JsonValue::Number(1));
+    array.push(JsonValue::Number(2));
+    assert_eq!(array, JsonArray::from(vec![JsonValue::Number(1), JsonValue::Number(2)]));
+    assert_eq!(JsonValue::from(array.clone()), crate::parse_str("[1, 2]").unwrap());
+    assert_eq!(array.into_iter().collect::<Vec<_>>(), vec![JsonValue::Number(1), JsonValue::Number(2)]);
+}
@@ -0,0 +1,74 @@
+use crate::parser::{JsonParser, JsonValue, ParserError};
+use crate::reader::ChunkedInput;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+impl JsonParser {
+    /// Parses a single value from an async reader, the counterpart of
+    /// `parse_from_reader` for code running on tokio.
+    pub async fn parse_from_async_reader(reader: impl AsyncRead + Unpin) -> Result<JsonValue, ParserError> {
+        let mut reader = BufReader::new(reader);
+        let mut input = ChunkedInput::new();
+        loop {
+            let chunk = match reader.fill_buf().await {
+                Ok(chunk) => chunk,
+                Err(err) => return Err(ParserError::Io(err)),
+            };
+            if chunk.is_empty() {
+                return input.finish();
+            }
+            let length = chunk.len();
+            match input.feed(chunk) {
+                Some((result, used)) => {
+                    reader.consume(used);
+                    return result;
+                }
+                None => reader.consume(length),
+            }
+        }
+    }
+}
+
+// Parses `input` from a mock reader that hands it out one byte per read.
+// The mock panics if it is dropped with input left, so the rest is read
+// after parsing.
+#[cfg(test)]
+async fn parse_one_byte_at_a_time(input: &[u8]) -> Result<JsonValue, ParserError> {
+    use tokio::io::AsyncReadExt;
+    let mut builder = tokio_test::io::Builder::new();
+    for byte in input {
+        builder.read(std::slice::from_ref(byte));
+    }
+    let mut reader = builder.build();
+    let value = JsonParser::parse_from_async_reader(&mut reader).await;
+    reader.read_to_end(&mut vec![]).await.unwrap();
+    value
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_parse_from_async_reader_one_byte_at_a_time() {
+    let cases = [
+        "{\n  \"name\": \"tinyserde 𝄞\",\n  \"values\": [1, -2.5, true, null, \"é\"],\n  \"nested\": {\"a\": [[], {}]}\n}\n",
+        "12345",
+        "\"𝄞\"",
+        "  ",
+        "[1, 2",
+        "{\"a\" 1}",
+        "nul",
+    ];
+    for input in cases {
+        assert_eq!(parse_one_byte_at_a_time(input.as_bytes()).await, crate::parse_str(input), "input: {:?}", input);
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_parse_from_async_reader_errors() {
+    assert_eq!(parse_one_byte_at_a_time(b"[\"\xff\"]").await, Err(ParserError::InvalidUtf8 { byte_offset: 2 }));
+    assert_eq!(parse_one_byte_at_a_time(b"\"\xf0\x9d").await, Err(ParserError::InvalidUtf8 { byte_offset: 1 }));
+    let reader = tokio_test::io::Builder::new()
+        .read(b"[1, ")
+        .read_error(std::io::Error::other("connection reset"))
+        .build();
+    assert_eq!(JsonParser::parse_from_async_reader(reader).await, Err(ParserError::Io(std::io::Error::other("connection reset"))));
+}
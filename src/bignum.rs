@@ -0,0 +1,100 @@
+use crate::parser::{float_value, JsonValue, ParserError};
+use crate::prelude::*;
+use bigdecimal::BigDecimal;
+use core::fmt;
+
+// Keeps an f64 when its shortest representation has the same value as the
+// literal, so that only numbers an f64 would round or overflow become
+// `BigNum`.
+pub(crate) fn float_or_bignum(raw: &str) -> Result<JsonValue, ParserError> {
+    // JSON5 literals such as `5.` are not decimals that BigDecimal reads.
+    let Ok(exact) = raw.parse::<BigDecimal>() else {
+        return float_value(raw).map(JsonValue::Float);
+    };
+    let float: f64 = raw.parse().unwrap();
+    if float.is_finite() && format!("{:e}", float).parse::<BigDecimal>().unwrap() == exact {
+        return Ok(JsonValue::Float(float));
+    }
+    Ok(JsonValue::BigNum(exact))
+}
+
+// A negative scale means trailing zeros that only an exponent in the
+// literal produces, so those values keep the exponent.
+pub(crate) fn write_bignum(f: &mut impl fmt::Write, value: &BigDecimal) -> fmt::Result {
+    if value.as_bigint_and_exponent().1 < 0 {
+        value.write_scientific_notation(f)
+    } else {
+        value.write_plain_string(f)
+    }
+}
+
+#[cfg(test)]
+fn parse_bignum(input: &str) -> Result<JsonValue, ParserError> {
+    let config = crate::JsonParserConfig { bignum: true, ..Default::default() };
+    crate::JsonParser::with_config(input, config).parse()
+}
+
+#[test]
+fn test_parse_bignum() {
+    let cases = [
+        ("0.1", JsonValue::Float(0.1)),
+        ("1e308", JsonValue::Float(1e308)),
+        ("-2.5E-3", JsonValue::Float(-2.5e-3)),
+        ("12", JsonValue::Number(12)),
+        ("18446744073709551615", JsonValue::Number64(u64::MAX)),
+        ("999999999999999999999999.9999999999", JsonValue::BigNum("999999999999999999999999.9999999999".parse().unwrap())),
+        ("18446744073709551616", JsonValue::BigNum("18446744073709551616".parse().unwrap())),
+        ("-9223372036854775809", JsonValue::BigNum("-9223372036854775809".parse().unwrap())),
+        ("1e400", JsonValue::BigNum("1e400".parse().unwrap())),
+        ("0.30000000000000000001", JsonValue::BigNum("0.30000000000000000001".parse().unwrap())),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(parse_bignum(input), Ok(expected), "input: {}", input);
+    }
+    // Without the option, numbers are parsed as before.
+    assert_eq!(crate::parse_str("1e400"), Err(ParserError::NumberOverflow("1e400".to_string())));
+}
+
+#[test]
+fn test_bignum_round_trip() {
+    let cases = [
+        "0.1",
+        "1e308",
+        "999999999999999999999999.9999999999",
+        "-123456789012345678901234567890",
+        "0.000000000000000000000000000000000000001",
+        "1e400",
+        "1.5e400",
+        "[1, 2.5, 1e-400, {\"price\": 19.999999999999999999}]",
+    ];
+    for input in cases {
+        let value = parse_bignum(input).unwrap();
+        assert_eq!(parse_bignum(&value.to_string()), Ok(value.clone()), "input: {}", input);
+        if let JsonValue::BigNum(_) = value {
+            assert_eq!(value.to_string(), input, "input: {}", input);
+        }
+    }
+}
+
+#[test]
+fn test_bignum_compares_by_value() {
+    let big = |literal: &str| JsonValue::BigNum(literal.parse().unwrap());
+    let ordered = [
+        JsonValue::Float(f64::NEG_INFINITY),
+        big("-1e400"),
+        JsonValue::Number(1),
+        big("1"),
+        big("1.00000000000000000001"),
+        JsonValue::Float(18_446_744_073_709_551_616.0),
+        big("18446744073709551616"),
+        JsonValue::Float(f64::INFINITY),
+        JsonValue::Float(f64::NAN),
+    ];
+    for (i, a) in ordered.iter().enumerate() {
+        for (j, b) in ordered.iter().enumerate() {
+            assert_eq!(a.cmp(b), i.cmp(&j), "{:?} and {:?}", a, b);
+        }
+    }
+    assert_eq!(big("1.50"), big("1.5"));
+    assert_eq!(JsonValue::Float(1.0).cmp(&big("1")), core::cmp::Ordering::Less);
+}
@@ -0,0 +1,379 @@
+use crate::config::JsonParserConfig;
+use crate::object::{HashMap, JsonObject};
+use crate::parser::{float_value, JsonValue, ParserError};
+use crate::prelude::*;
+use crate::tokenizer::{is_whitespace, JsonTokenizer, Token, TokenError};
+
+/// A `JsonValue` whose strings and keys borrow from the input. Strings
+/// without escape sequences are slices of the input. Strings with escapes
+/// have to be decoded and so are owned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedJsonValue<'a> {
+    Null,
+    Bool(bool),
+    Number(i64),
+    Number64(u64),
+    Float(f64),
+    String(Cow<'a, str>),
+    Array(Vec<BorrowedJsonValue<'a>>),
+    Object(HashMap<Cow<'a, str>, BorrowedJsonValue<'a>>),
+}
+
+impl BorrowedJsonValue<'_> {
+    pub fn to_owned(&self) -> JsonValue {
+        match self {
+            BorrowedJsonValue::Null => JsonValue::Null,
+            BorrowedJsonValue::Bool(value) => JsonValue::Bool(*value),
+            BorrowedJsonValue::Number(value) => JsonValue::Number(*value),
+            BorrowedJsonValue::Number64(value) => JsonValue::Number64(*value),
+            BorrowedJsonValue::Float(value) => JsonValue::Float(*value),
+            BorrowedJsonValue::String(value) => JsonValue::String(value.to_string().into()),
+            BorrowedJsonValue::Array(array) => JsonValue::Array(array.iter().map(BorrowedJsonValue::to_owned).collect()),
+            BorrowedJsonValue::Object(map) => JsonValue::Object(
+                map.iter().map(|(key, value)| (key.to_string(), value.to_owned())).collect::<JsonObject>(),
+            ),
+        }
+    }
+}
+
+enum Frame<'a> {
+    Array(Vec<BorrowedJsonValue<'a>>),
+    Object(HashMap<Cow<'a, str>, BorrowedJsonValue<'a>>, Option<Cow<'a, str>>),
+}
+
+impl<'a> Frame<'a> {
+    fn into_value(self) -> BorrowedJsonValue<'a> {
+        match self {
+            Frame::Array(array) => BorrowedJsonValue::Array(array),
+            Frame::Object(members, _) => BorrowedJsonValue::Object(members),
+        }
+    }
+}
+
+/// Parses strict JSON into a `BorrowedJsonValue` that borrows from
+/// `input`.
+pub struct JsonBorrowedParser<'a> {
+    pub input: &'a str,
+    tokens: JsonTokenizer<'a>,
+    peeked: Option<(usize, Token<'a>)>,
+    pub(crate) config: JsonParserConfig,
+    total_values: usize,
+}
+
+impl<'a> JsonBorrowedParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_config(input, JsonParserConfig::default())
+    }
+
+    /// Applies the limits of `config`. The input is read as strict JSON
+    /// whatever the mode, and the other options are ignored.
+    pub fn with_config(input: &'a str, config: JsonParserConfig) -> Self {
+        JsonBorrowedParser { input, tokens: JsonTokenizer::new(input), peeked: None, config, total_values: 0 }
+    }
+
+    pub fn parse(&mut self) -> Result<BorrowedJsonValue<'a>, ParserError> {
+        let value = self.parse_value()?;
+        if let Some((at_byte, _)) = self.next_token()? {
+            return Err(self.syntax_error(at_byte, "Expected the end of input"));
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn syntax_error(&self, at_byte: usize, message: &'static str) -> ParserError {
+        let (line, column) = self.line_and_column(at_byte);
+        ParserError::SyntaxError { line, column, message }
+    }
+
+    fn line_and_column(&self, at_byte: usize) -> (usize, usize) {
+        let before = &self.input[..at_byte];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap().chars().count() + 1;
+        (line, column)
+    }
+
+    // Returns the next token along with the byte offset it starts at.
+    pub(crate) fn next_token(&mut self) -> Result<Option<(usize, Token<'a>)>, ParserError> {
+        if let Some(token) = self.peeked.take() {
+            return Ok(Some(token));
+        }
+        let rest = &self.input[self.tokens.offset()..];
+        let at_byte = self.tokens.offset() + rest.len() - rest.trim_start_matches(is_whitespace).len();
+        match self.tokens.next() {
+            None => Ok(None),
+            Some(Ok(token)) => Ok(Some((at_byte, token))),
+            Some(Err(err)) => Err(match err {
+                TokenError::UnexpectedCharacter { at_byte, .. } => self.syntax_error(at_byte, "Unexpected character"),
+                TokenError::UnterminatedString { at_byte } => {
+                    let (line, column) = self.line_and_column(at_byte);
+                    ParserError::UnterminatedString { line, column, at_byte }
+                }
+                TokenError::InvalidEscapeSequence { char, at_byte } => {
+                    let (line, column) = self.line_and_column(at_byte);
+                    ParserError::InvalidEscapeSequence { char, line, column, at_byte }
+                }
+                TokenError::InvalidNumberLiteral { at_byte } => {
+                    let raw = self.input[at_byte..]
+                        .chars()
+                        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+                        .collect();
+                    ParserError::InvalidNumberLiteral { raw }
+                }
+            }),
+        }
+    }
+
+    pub(crate) fn peek_token(&mut self) -> Result<Option<Token<'a>>, ParserError> {
+        if self.peeked.is_none() {
+            self.peeked = self.next_token()?;
+        }
+        Ok(self.peeked.map(|(_, token)| token))
+    }
+
+    // Like next_token, but reports the end of input as UnexpectedEof.
+    pub(crate) fn expect_token(&mut self, expected: &'static str) -> Result<(usize, Token<'a>), ParserError> {
+        self.next_token()?.ok_or(ParserError::UnexpectedEof { expected })
+    }
+
+    // Counts the value that `token` starts, `depth` levels deep.
+    pub(crate) fn count_value(&mut self, token: Token<'a>, depth: usize) -> Result<(), ParserError> {
+        if self.total_values >= self.config.max_total_values {
+            return Err(ParserError::TotalValuesExceeded { limit: self.config.max_total_values });
+        }
+        self.total_values += 1;
+        if matches!(token, Token::LBracket | Token::LBrace) && depth >= self.config.max_depth {
+            return Err(ParserError::DepthLimitExceeded { limit: self.config.max_depth });
+        }
+        Ok(())
+    }
+
+    // Decodes a string or key token.
+    pub(crate) fn string(&self, raw: &'a str) -> Result<Cow<'a, str>, ParserError> {
+        let string = unescape(raw)?;
+        if string.len() > self.config.max_string_length {
+            return Err(ParserError::StringLengthExceeded { limit: self.config.max_string_length });
+        }
+        Ok(string)
+    }
+
+    pub(crate) fn check_array_length(&self, length: usize) -> Result<(), ParserError> {
+        match length > self.config.max_array_length {
+            true => Err(ParserError::ArrayLengthExceeded { limit: self.config.max_array_length }),
+            false => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_object_size(&self, size: usize) -> Result<(), ParserError> {
+        match size > self.config.max_object_size {
+            true => Err(ParserError::ObjectSizeExceeded { limit: self.config.max_object_size }),
+            false => Ok(()),
+        }
+    }
+
+    pub(crate) fn parse_key(&mut self) -> Result<Cow<'a, str>, ParserError> {
+        let key = match self.expect_token("string")? {
+            (_, Token::String(raw)) => self.string(raw)?,
+            (at_byte, _) => return Err(self.syntax_error(at_byte, "Expected '\"'")),
+        };
+        match self.expect_token("':'")? {
+            (_, Token::Colon) => Ok(key),
+            (at_byte, _) => Err(self.syntax_error(at_byte, "Expected ':'")),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<BorrowedJsonValue<'a>, ParserError> {
+        let mut stack: Vec<Frame<'a>> = vec![];
+        'value: loop {
+            let token = self.expect_token("value")?;
+            self.count_value(token.1, stack.len())?;
+            let mut value = match token {
+                (_, Token::Null) => BorrowedJsonValue::Null,
+                (_, Token::Bool(value)) => BorrowedJsonValue::Bool(value),
+                (_, Token::Number(raw)) => number(raw)?,
+                (_, Token::String(raw)) => BorrowedJsonValue::String(self.string(raw)?),
+                (_, Token::LBracket) if self.peek_token()? == Some(Token::RBracket) => {
+                    self.next_token()?;
+                    BorrowedJsonValue::Array(vec![])
+                }
+                (_, Token::LBracket) => {
+                    stack.push(Frame::Array(vec![]));
+                    continue;
+                }
+                (_, Token::LBrace) if self.peek_token()? == Some(Token::RBrace) => {
+                    self.next_token()?;
+                    BorrowedJsonValue::Object(HashMap::new())
+                }
+                (_, Token::LBrace) => {
+                    let key = self.parse_key()?;
+                    stack.push(Frame::Object(HashMap::new(), Some(key)));
+                    continue;
+                }
+                (at_byte, _) => return Err(self.syntax_error(at_byte, "Expected a value")),
+            };
+            loop {
+                let closed = match stack.last_mut() {
+                    None => return Ok(value),
+                    Some(Frame::Array(array)) => {
+                        array.push(value);
+                        self.check_array_length(array.len())?;
+                        match self.expect_token("',' or ']'")? {
+                            (_, Token::Comma) => false,
+                            (_, Token::RBracket) => true,
+                            (at_byte, _) => return Err(self.syntax_error(at_byte, "Expected ',' or ']'")),
+                        }
+                    }
+                    Some(Frame::Object(members, key)) => {
+                        members.insert(key.take().unwrap(), value);
+                        self.check_object_size(members.len())?;
+                        match self.expect_token("',' or '}'")? {
+                            (_, Token::Comma) => {
+                                *key = Some(self.parse_key()?);
+                                false
+                            }
+                            (_, Token::RBrace) => true,
+                            (at_byte, _) => return Err(self.syntax_error(at_byte, "Expected ',' or '}'")),
+                        }
+                    }
+                };
+                if !closed {
+                    continue 'value;
+                }
+                value = stack.pop().unwrap().into_value();
+            }
+        }
+    }
+}
+
+pub(crate) fn number(raw: &str) -> Result<BorrowedJsonValue<'_>, ParserError> {
+    if raw.contains(['.', 'e', 'E']) {
+        return float_value(raw).map(BorrowedJsonValue::Float);
+    }
+    match (raw.parse(), raw.parse()) {
+        (Ok(value), _) => Ok(BorrowedJsonValue::Number(value)),
+        (_, Ok(value)) => Ok(BorrowedJsonValue::Number64(value)),
+        _ => Err(ParserError::NumberOverflow(raw.to_string())),
+    }
+}
+
+// The tokenizer has already checked the escape sequences, apart from
+// unpaired surrogates.
+pub(crate) fn unescape(raw: &str) -> Result<Cow<'_, str>, ParserError> {
+    if !raw.contains('\\') {
+        return Ok(Cow::Borrowed(raw));
+    }
+    let mut builder = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    let code_unit = |chars: &mut core::str::Chars| {
+        let digits: String = chars.take(4).collect();
+        u32::from_str_radix(&digits, 16).unwrap()
+    };
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            builder.push(ch);
+            continue;
+        }
+        let unescaped = match chars.next().unwrap() {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let high = code_unit(&mut chars);
+                if (0xDC00..=0xDFFF).contains(&high) {
+                    return Err(ParserError::InvalidSurrogatePair);
+                }
+                if (0xD800..=0xDBFF).contains(&high) {
+                    if !chars.as_str().starts_with("\\u") {
+                        return Err(ParserError::InvalidSurrogatePair);
+                    }
+                    chars.nth(1);
+                    let low = code_unit(&mut chars);
+                    if !(0xDC00..=0xDFFF).contains(&low) {
+                        return Err(ParserError::InvalidSurrogatePair);
+                    }
+                    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap()
+                } else {
+                    char::from_u32(high).unwrap()
+                }
+            }
+            escaped => escaped,
+        };
+        builder.push(unescaped);
+    }
+    Ok(Cow::Owned(builder))
+}
+
+#[test]
+fn test_borrowed_parse_matches_owned_parse() {
+    let inputs = [
+        "null",
+        "[]",
+        "{}",
+        r#"{"a": [1, -2, 2.5e3, true, false, null], "b": {"c": "d", "e": {}}}"#,
+        r#"["plain", "esc\n\"aped\"", "\u00e9\ud83d\ude00", "\"nested\" \"quoted\" words\""]"#,
+        "-9223372036854775808",
+    ];
+    for input in inputs {
+        let borrowed = JsonBorrowedParser::new(input).parse().unwrap();
+        assert_eq!(borrowed.to_owned(), crate::parse_str(input).unwrap(), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_borrowed_strings_borrow_unless_escaped() {
+    let input = String::from(r#"{"key": ["plain", "tab\tbed"]}"#);
+    let value = JsonBorrowedParser::new(&input).parse().unwrap();
+    let BorrowedJsonValue::Object(map) = &value else { panic!("{:?}", value) };
+    let (key, elements) = map.iter().next().unwrap();
+    assert!(matches!(key, Cow::Borrowed("key")));
+    let BorrowedJsonValue::Array(elements) = elements else { panic!("{:?}", elements) };
+    assert!(matches!(&elements[0], BorrowedJsonValue::String(Cow::Borrowed("plain"))));
+    assert_eq!(elements[1], BorrowedJsonValue::String(Cow::Owned("tab\tbed".to_string())));
+    assert!(matches!(&elements[1], BorrowedJsonValue::String(Cow::Owned(_))));
+}
+
+#[test]
+fn test_borrowed_parse_errors_match_owned_parse() {
+    let inputs = [
+        "", "[1, 2", "{\"a\" 1}", "[1] 2", "{\"a\": }", "[1,]", "\"abc", "\"\\x\"", "012", "1.e5",
+        "18446744073709551616", "\"\\ud800\"", "@", "{\"a\": 1 \"b\": 2}", "[\n  \"abc", "[\"é\",\n \"\\q\"]",
+    ];
+    for input in inputs {
+        let borrowed = JsonBorrowedParser::new(input).parse().unwrap_err();
+        let owned = crate::parse_str(input).unwrap_err();
+        // Syntax errors are reported at the same position, but the
+        // messages are worded differently.
+        match (&borrowed, &owned) {
+            (
+                ParserError::SyntaxError { line, column, .. },
+                ParserError::SyntaxError { line: owned_line, column: owned_column, .. },
+            ) => assert_eq!((line, column), (owned_line, owned_column), "input: {:?}", input),
+            _ => assert_eq!(borrowed, owned, "input: {:?}", input),
+        }
+    }
+}
+
+#[test]
+fn test_borrowed_parse_applies_limits() {
+    let deep = "[".repeat(200_000);
+    let config = JsonParserConfig { max_depth: 2, max_string_length: 3, max_array_length: 2, max_object_size: 1, max_total_values: 4, ..Default::default() };
+    let cases = [
+        (deep.as_str(), ParserError::DepthLimitExceeded { limit: 2 }),
+        ("[[[]]]", ParserError::DepthLimitExceeded { limit: 2 }),
+        ("\"abcd\"", ParserError::StringLengthExceeded { limit: 3 }),
+        ("{\"abcd\": 1}", ParserError::StringLengthExceeded { limit: 3 }),
+        ("[1, 2, 3]", ParserError::ArrayLengthExceeded { limit: 2 }),
+        ("{\"a\": 1, \"b\": 2}", ParserError::ObjectSizeExceeded { limit: 1 }),
+        ("[[1], [2]]", ParserError::TotalValuesExceeded { limit: 4 }),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(JsonBorrowedParser::with_config(input, config.clone()).parse().unwrap_err(), expected, "input: {:.10}", input);
+        assert_eq!(crate::JsonParser::with_config(input, config.clone()).parse().unwrap_err(), expected, "input: {:.10}", input);
+    }
+    assert!(JsonBorrowedParser::with_config("[[\"abc\"], {\"k\": 1}]", config.clone()).parse().is_err());
+    assert!(JsonBorrowedParser::with_config("[[\"abc\", 1]]", config).parse().is_ok());
+    let max_depth = JsonParserConfig::default().max_depth;
+    assert_eq!(JsonBorrowedParser::new(&deep).parse(), Err(ParserError::DepthLimitExceeded { limit: max_depth }));
+    let nested = "[".repeat(max_depth) + &"]".repeat(max_depth);
+    assert!(JsonBorrowedParser::new(&nested).parse().is_ok());
+}
@@ -0,0 +1,69 @@
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+use crate::prelude::*;
+
+/// Collects members for a `JsonValue::Object`.
+#[derive(Debug, Default)]
+pub struct JsonObjectBuilder {
+    object: JsonObject,
+}
+
+impl JsonObjectBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a member, replacing any earlier member with the same key.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> &mut Self {
+        self.object.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns the object built so far, leaving the builder empty.
+    pub fn build(&mut self) -> JsonValue {
+        JsonValue::Object(core::mem::take(&mut self.object))
+    }
+}
+
+/// Collects elements for a `JsonValue::Array`.
+#[derive(Debug, Default)]
+pub struct JsonArrayBuilder {
+    array: Vec<JsonValue>,
+}
+
+impl JsonArrayBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, value: impl Into<JsonValue>) -> &mut Self {
+        self.array.push(value.into());
+        self
+    }
+
+    /// Returns the array built so far, leaving the builder empty.
+    pub fn build(&mut self) -> JsonValue {
+        JsonValue::Array(core::mem::take(&mut self.array))
+    }
+}
+
+#[test]
+fn test_builders_match_parsed_document() {
+    let array = JsonArrayBuilder::new().push(1).push(2.5).push("bar").build();
+    let value = JsonObjectBuilder::new().insert("foo", array).build();
+    assert_eq!(value, crate::parse_str(r#"{ "foo": [1, 2.5, "bar"] }"#).unwrap());
+}
+
+#[test]
+fn test_builders_with_dynamic_data() {
+    let mut tags = JsonArrayBuilder::default();
+    for tag in ["json", "parser"] {
+        tags.push(tag);
+    }
+    let mut object = JsonObjectBuilder::default();
+    object.insert("name", "tinyserde").insert("tags", tags.build()).insert("name", String::from("renamed"));
+    object.insert("missing", None::<i64>);
+    assert_eq!(object.build(), crate::parse_str(r#"{"name": "renamed", "tags": ["json", "parser"], "missing": null}"#).unwrap());
+    assert_eq!(object.build(), JsonValue::Object(JsonObject::new()));
+    assert_eq!(tags.build(), JsonValue::Array(vec![]));
+}
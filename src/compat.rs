@@ -0,0 +1,4 @@
+//! Conversions to and from the value types of other JSON libraries.
+
+#[cfg(feature = "serde_json")]
+pub mod serde_json;
@@ -0,0 +1,92 @@
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+
+/// Integers that fit in an i64 become `Number` and larger ones that fit in
+/// a u64 become `Number64`. Other numbers become `Float`.
+impl From<::serde_json::Value> for JsonValue {
+    fn from(value: ::serde_json::Value) -> Self {
+        match value {
+            ::serde_json::Value::Null => JsonValue::Null,
+            ::serde_json::Value::Bool(value) => JsonValue::Bool(value),
+            ::serde_json::Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+                (Some(value), _) => JsonValue::Number(value),
+                (_, Some(value)) => JsonValue::Number64(value),
+                _ => JsonValue::Float(number.as_f64().unwrap()),
+            },
+            ::serde_json::Value::String(value) => JsonValue::from(value),
+            ::serde_json::Value::Array(array) => JsonValue::Array(array.into_iter().map(JsonValue::from).collect()),
+            ::serde_json::Value::Object(map) => JsonValue::Object(
+                map.into_iter().map(|(key, value)| (key, JsonValue::from(value))).collect::<JsonObject>(),
+            ),
+        }
+    }
+}
+
+/// Non-finite floats have no JSON representation and become `Null`, as
+/// they do when serialized.
+impl From<JsonValue> for ::serde_json::Value {
+    fn from(mut value: JsonValue) -> Self {
+        match &mut value {
+            JsonValue::Null => ::serde_json::Value::Null,
+            JsonValue::Bool(value) => ::serde_json::Value::Bool(*value),
+            JsonValue::Number(value) => ::serde_json::Value::Number((*value).into()),
+            JsonValue::Number64(value) => ::serde_json::Value::Number((*value).into()),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(value) => JsonValue::Float(bigdecimal::ToPrimitive::to_f64(value).unwrap()).into(),
+            JsonValue::Float(value) => match ::serde_json::Number::from_f64(*value) {
+                Some(number) => ::serde_json::Value::Number(number),
+                None => ::serde_json::Value::Null,
+            },
+            JsonValue::String(value) => ::serde_json::Value::String(core::mem::take(value).into_owned()),
+            JsonValue::Array(array) => {
+                ::serde_json::Value::Array(core::mem::take(array).into_iter().map(::serde_json::Value::from).collect())
+            }
+            JsonValue::Object(map) => ::serde_json::Value::Object(
+                core::mem::take(map).into_iter().map(|(key, value)| (key, ::serde_json::Value::from(value))).collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+const CORPUS: &[&str] = &[
+    "null",
+    "true",
+    "false",
+    "0",
+    "-9223372036854775808",
+    "9223372036854775807",
+    "1.5",
+    "-2.5e-3",
+    "1E22",
+    r#""""#,
+    r#""\u0000\u001f""#,
+    r#""𝄞 é \"\\\/\b\f\n\r\t""#,
+    "[]",
+    "[[[]]]",
+    r#"[1, "two", 3.5, null, true, [false]]"#,
+    "{}",
+    r#"{"": 0, "a": {"b": {"c": []}}, "d": [{}, {"e": null}]}"#,
+];
+
+#[test]
+fn test_convert_corpus_both_ways() {
+    for input in CORPUS {
+        let value = crate::parse_str(input).unwrap();
+        let serde_value: ::serde_json::Value = ::serde_json::from_str(input).unwrap();
+        assert_eq!(::serde_json::Value::from(value.clone()), serde_value, "input: {}", input);
+        assert_eq!(JsonValue::from(serde_value.clone()), value, "input: {}", input);
+        assert_eq!(JsonValue::from(::serde_json::Value::from(value.clone())), value, "input: {}", input);
+    }
+}
+
+#[test]
+fn test_convert_numbers() {
+    assert_eq!(JsonValue::from(::serde_json::json!(3)), JsonValue::Number(3));
+    assert_eq!(JsonValue::from(::serde_json::json!(3.0)), JsonValue::Float(3.0));
+    assert_eq!(JsonValue::from(::serde_json::json!(u64::MAX)), JsonValue::Number64(u64::MAX));
+    assert_eq!(::serde_json::Value::from(JsonValue::Float(f64::NAN)), ::serde_json::Value::Null);
+    // serde_json reads -0 as a float to keep the sign.
+    let negative_zero: ::serde_json::Value = ::serde_json::from_str("-0").unwrap();
+    assert_eq!(JsonValue::from(negative_zero), JsonValue::Float(-0.0));
+}
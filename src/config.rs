@@ -0,0 +1,84 @@
+use crate::object::JsonObjectKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Only accept JSON as specified by RFC 8259.
+    #[default]
+    Strict,
+    /// Also accept trailing commas, `//` line comments, `NaN`, `Infinity`
+    /// and `-Infinity`. When `JsonParser::parse` reads a single document,
+    /// an unescaped quote that is not followed by `,`, `:`, `]`, `}` or the
+    /// end of input is also kept as part of the string.
+    Lenient,
+    /// Accept everything `Lenient` does except unescaped quotes inside
+    /// strings, plus the rest of JSON5: unquoted
+    /// and single-quoted keys, single-quoted strings, line continuations,
+    /// hexadecimal numbers, signs such as `+Infinity` and `/* */` comments.
+    Json5,
+}
+
+/// What `JsonParser` does when an object has the same key more than once,
+/// which RFC 8259 allows but leaves the meaning of to the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the value of the last member with the key.
+    #[default]
+    LastWins,
+    /// Keep the value of the first member with the key.
+    FirstWins,
+    /// Fail with `ParserError::DuplicateKey`.
+    Error,
+    /// Keep the last value like `LastWins`, and record every value of the
+    /// key, which `JsonParser::duplicate_keys` returns after the parse.
+    CollectAll,
+}
+
+/// Limits applied while parsing, so that untrusted input cannot exhaust
+/// the stack or memory. Exceeding a limit aborts the parse with the
+/// matching `ParserError` variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonParserConfig {
+    /// Maximum number of nested arrays and objects.
+    pub max_depth: usize,
+    /// Maximum length in bytes of a decoded string or object key.
+    pub max_string_length: usize,
+    /// Maximum number of elements in a single array.
+    pub max_array_length: usize,
+    /// Maximum number of members in a single object.
+    pub max_object_size: usize,
+    /// Maximum number of values in the whole document, containers included.
+    pub max_total_values: usize,
+    pub mode: ParseMode,
+    /// The map used for parsed objects. `BTreeMap` keeps keys sorted and
+    /// `IndexMap` keeps them in input order, so that serializing the same
+    /// document always produces the same output.
+    pub preserve_order: JsonObjectKind,
+    /// Parse numbers that fit in neither an i64 nor a u64, and numbers
+    /// that an f64 would round, as `JsonValue::BigNum`.
+    #[cfg(feature = "bignum")]
+    pub bignum: bool,
+    /// Applies to the values `parse` builds. Event handlers see every
+    /// member whatever the policy.
+    pub duplicate_key_policy: DuplicateKeyPolicy,
+    /// Treat a byte order mark at the start of the input as a syntax
+    /// error rather than skipping it.
+    pub reject_bom: bool,
+}
+
+impl Default for JsonParserConfig {
+    fn default() -> Self {
+        JsonParserConfig {
+            max_depth: 512,
+            max_string_length: 64 * 1024 * 1024,
+            max_array_length: 16 * 1024 * 1024,
+            max_object_size: 1024 * 1024,
+            max_total_values: 64 * 1024 * 1024,
+            mode: ParseMode::Strict,
+            preserve_order: JsonObjectKind::default(),
+            #[cfg(feature = "bignum")]
+            bignum: false,
+            duplicate_key_policy: DuplicateKeyPolicy::LastWins,
+            reject_bom: false,
+        }
+    }
+}
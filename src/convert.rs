@@ -0,0 +1,478 @@
+use crate::parser::JsonValue;
+use crate::object::JsonObject;
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+// Conversions out of a JsonValue hand the original value back as the
+// error so that nothing is lost when the variant does not match.
+
+impl TryFrom<JsonValue> for i64 {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        value.as_i64().ok_or(value)
+    }
+}
+
+impl TryFrom<JsonValue> for f64 {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or(value)
+    }
+}
+
+impl TryFrom<JsonValue> for bool {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Bool(value) => Ok(value),
+            value => Err(value),
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for String {
+    type Error = JsonValue;
+
+    fn try_from(mut value: JsonValue) -> Result<Self, Self::Error> {
+        match &mut value {
+            JsonValue::String(string) => Ok(core::mem::take(string).into_owned()),
+            _ => Err(value),
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for Vec<JsonValue> {
+    type Error = JsonValue;
+
+    fn try_from(mut value: JsonValue) -> Result<Self, Self::Error> {
+        match &mut value {
+            JsonValue::Array(array) => Ok(core::mem::take(array)),
+            _ => Err(value),
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for JsonObject {
+    type Error = JsonValue;
+
+    fn try_from(mut value: JsonValue) -> Result<Self, Self::Error> {
+        match &mut value {
+            JsonValue::Object(map) => Ok(core::mem::take(map)),
+            _ => Err(value),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<JsonValue> for HashMap<String, JsonValue> {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        JsonObject::try_from(value).map(HashMap::from)
+    }
+}
+
+impl JsonValue {
+    /// Moves the string out of the value, or returns the value itself when
+    /// it is not a string. Strings that borrow static data are copied.
+    pub fn into_string(self) -> Result<String, JsonValue> {
+        String::try_from(self)
+    }
+
+    pub fn into_array(self) -> Result<Vec<JsonValue>, JsonValue> {
+        Vec::try_from(self)
+    }
+
+    /// A `JsonObject` converts into a `HashMap` with `From` when the `std`
+    /// feature is enabled.
+    pub fn into_object(self) -> Result<JsonObject, JsonValue> {
+        JsonObject::try_from(self)
+    }
+}
+
+impl<'a> TryFrom<&'a JsonValue> for i64 {
+    type Error = &'a JsonValue;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        value.as_i64().ok_or(value)
+    }
+}
+
+impl<'a> TryFrom<&'a JsonValue> for f64 {
+    type Error = &'a JsonValue;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or(value)
+    }
+}
+
+impl<'a> TryFrom<&'a JsonValue> for bool {
+    type Error = &'a JsonValue;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or(value)
+    }
+}
+
+impl<'a> TryFrom<&'a JsonValue> for &'a str {
+    type Error = &'a JsonValue;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        value.as_str().ok_or(value)
+    }
+}
+
+impl<'a> TryFrom<&'a JsonValue> for &'a Vec<JsonValue> {
+    type Error = &'a JsonValue;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        value.as_array().ok_or(value)
+    }
+}
+
+impl<'a> TryFrom<&'a JsonValue> for &'a JsonObject {
+    type Error = &'a JsonValue;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        value.as_object().ok_or(value)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(value: i64) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+impl From<i32> for JsonValue {
+    fn from(value: i32) -> Self {
+        JsonValue::Number(value as i64)
+    }
+}
+
+impl From<u32> for JsonValue {
+    fn from(value: u32) -> Self {
+        JsonValue::Number(value as i64)
+    }
+}
+
+/// Values up to `i64::MAX` become `Number` and larger ones `Number64`.
+impl From<u64> for JsonValue {
+    fn from(value: u64) -> Self {
+        match i64::try_from(value) {
+            Ok(value) => JsonValue::Number(value),
+            Err(_) => JsonValue::Number64(value),
+        }
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Float(value)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value.into())
+    }
+}
+
+// Copies the string, since its lifetime is unknown. Use
+// `JsonValue::String("literal".into())` to borrow a static string instead.
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string().into())
+    }
+}
+
+impl From<Cow<'static, str>> for JsonValue {
+    fn from(value: Cow<'static, str>) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(array: Vec<JsonValue>) -> Self {
+        JsonValue::Array(array)
+    }
+}
+
+impl From<JsonObject> for JsonValue {
+    fn from(map: JsonObject) -> Self {
+        JsonValue::Object(map)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<HashMap<String, JsonValue>> for JsonValue {
+    fn from(map: HashMap<String, JsonValue>) -> Self {
+        JsonValue::Object(map.into())
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => JsonValue::Null,
+        }
+    }
+}
+
+impl FromIterator<JsonValue> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = JsonValue>>(iter: I) -> Self {
+        JsonValue::Array(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<(String, JsonValue)> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = (String, JsonValue)>>(iter: I) -> Self {
+        JsonValue::Object(iter.into_iter().collect())
+    }
+}
+
+/// Same as `extend_array`, so it panics unless the value is an array or
+/// null.
+impl Extend<JsonValue> for JsonValue {
+    fn extend<I: IntoIterator<Item = JsonValue>>(&mut self, iter: I) {
+        self.extend_array(iter)
+    }
+}
+
+/// Same as `extend_object`, so it panics unless the value is an object or
+/// null.
+impl Extend<(String, JsonValue)> for JsonValue {
+    fn extend<I: IntoIterator<Item = (String, JsonValue)>>(&mut self, iter: I) {
+        self.extend_object(iter)
+    }
+}
+
+#[cfg(test)]
+use crate::value::sample_values;
+
+#[test]
+fn test_try_from_owned() {
+    for value in sample_values() {
+        assert_eq!(i64::try_from(value.clone()), i64::try_from(&value).map_err(JsonValue::clone));
+        assert_eq!(f64::try_from(value.clone()), f64::try_from(&value).map_err(JsonValue::clone));
+        let result = bool::try_from(value.clone());
+        match value {
+            JsonValue::Bool(b) => assert_eq!(result, Ok(b)),
+            _ => assert_eq!(result, Err(value.clone())),
+        }
+        let result = String::try_from(value.clone());
+        match &value {
+            JsonValue::String(s) => assert_eq!(result, Ok(s.to_string())),
+            _ => assert_eq!(result, Err(value.clone())),
+        }
+        let result = Vec::<JsonValue>::try_from(value.clone());
+        match &value {
+            JsonValue::Array(array) => assert_eq!(result, Ok(array.clone())),
+            _ => assert_eq!(result, Err(value.clone())),
+        }
+        #[cfg(feature = "std")]
+        {
+            let result = HashMap::<String, JsonValue>::try_from(value.clone());
+            match &value {
+                JsonValue::Object(map) => assert_eq!(result, Ok(map.clone().into())),
+                _ => assert_eq!(result, Err(value.clone())),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_try_from_owned_numbers() {
+    assert_eq!(i64::try_from(JsonValue::Number64(5)), Ok(5));
+    assert_eq!(f64::try_from(JsonValue::Number64(5)), Ok(5.0));
+    let big = JsonValue::Number64(i64::MAX as u64 + 1);
+    assert_eq!(i64::try_from(big.clone()), Err(big.clone()));
+    assert_eq!(f64::try_from(big), Ok(9_223_372_036_854_775_808.0));
+    assert_eq!(i64::try_from(JsonValue::Float(5.0)), Err(JsonValue::Float(5.0)));
+    #[cfg(feature = "bignum")]
+    assert_eq!(f64::try_from(JsonValue::BigNum("2.5".parse().unwrap())), Ok(2.5));
+}
+
+#[test]
+fn test_try_from_borrowed() {
+    for value in sample_values() {
+        let value = &value;
+        match value {
+            JsonValue::Number(n) => assert_eq!(i64::try_from(value), Ok(*n)),
+            _ => assert_eq!(i64::try_from(value), Err(value)),
+        }
+        match value {
+            JsonValue::Number(_) | JsonValue::Float(_) => assert!(f64::try_from(value).is_ok()),
+            _ => assert_eq!(f64::try_from(value), Err(value)),
+        }
+        match value {
+            JsonValue::Bool(b) => assert_eq!(bool::try_from(value), Ok(*b)),
+            _ => assert_eq!(bool::try_from(value), Err(value)),
+        }
+        match value {
+            JsonValue::String(s) => assert_eq!(<&str>::try_from(value), Ok(s.as_ref())),
+            _ => assert_eq!(<&str>::try_from(value), Err(value)),
+        }
+        match value {
+            JsonValue::Array(array) => assert_eq!(<&Vec<JsonValue>>::try_from(value), Ok(array)),
+            _ => assert_eq!(<&Vec<JsonValue>>::try_from(value), Err(value)),
+        }
+        match value {
+            JsonValue::Object(map) => assert_eq!(<&JsonObject>::try_from(value), Ok(map)),
+            _ => assert_eq!(<&JsonObject>::try_from(value), Err(value)),
+        }
+    }
+}
+
+#[test]
+fn test_try_from_with_question_mark() {
+    fn read_count(value: JsonValue) -> Result<i64, JsonValue> {
+        let count: i64 = value.try_into()?;
+        Ok(count * 2)
+    }
+    assert_eq!(read_count(JsonValue::Number(21)), Ok(42));
+    assert_eq!(read_count(JsonValue::Null), Err(JsonValue::Null));
+}
+
+#[cfg(test)]
+struct CountingAllocator;
+
+#[cfg(test)]
+thread_local! {
+    static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+// Counts allocations per thread, so that tests running in parallel do not
+// see each other's allocations.
+#[cfg(test)]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[cfg(test)]
+pub(crate) fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(|count| count.get());
+    f();
+    ALLOCATIONS.with(|count| count.get()) - before
+}
+
+#[test]
+fn test_static_strings_do_not_allocate() {
+    let allocations = count_allocations(|| {
+        let value = JsonValue::String("literal".into());
+        let copy = std::hint::black_box(value.clone());
+        assert!(value == copy);
+        assert!(JsonValue::from(Cow::Borrowed("x")).is_string());
+    });
+    assert_eq!(allocations, 0);
+    assert!(count_allocations(|| drop(std::hint::black_box(JsonValue::from("literal")))) > 0);
+}
+
+#[test]
+fn test_into_conversions_move_without_copying() {
+    let string = JsonValue::from("owned".to_string());
+    let array = crate::parse_str(r#"[1, "two", [3]]"#).unwrap();
+    let object = crate::parse_str(r#"{"a": {"b": [1]}}"#).unwrap();
+    let (expected_array, expected_object) = (array.clone(), object.clone());
+    let mut results = None;
+    let allocations = count_allocations(|| {
+        results = Some((string.into_string(), array.into_array(), object.into_object()));
+    });
+    assert_eq!(allocations, 0);
+    let (string, array, object) = results.unwrap();
+    assert_eq!(string.unwrap(), "owned");
+    assert_eq!(JsonValue::Array(array.unwrap()), expected_array);
+    assert_eq!(JsonValue::Object(object.unwrap()), expected_object);
+
+    for value in sample_values() {
+        if !value.is_string() {
+            assert_eq!(value.clone().into_string(), Err(value.clone()));
+        }
+        if !value.is_array() {
+            assert_eq!(value.clone().into_array(), Err(value.clone()));
+        }
+        if !value.is_object() {
+            assert_eq!(value.clone().into_object(), Err(value.clone()));
+        }
+    }
+}
+
+#[test]
+fn test_owned_and_borrowed_strings_are_equal() {
+    let borrowed = JsonValue::String("text".into());
+    let owned = JsonValue::String("text".to_string().into());
+    assert_eq!(borrowed, owned);
+    assert_eq!(crate::parse_str("\"text\""), Ok(borrowed));
+}
+
+#[test]
+fn test_from_scalars() {
+    assert_eq!(JsonValue::from(-3i64), JsonValue::Number(-3));
+    assert_eq!(JsonValue::from(i32::MIN), JsonValue::Number(i32::MIN as i64));
+    assert_eq!(JsonValue::from(u32::MAX), JsonValue::Number(u32::MAX as i64));
+    assert_eq!(JsonValue::from(0.5), JsonValue::Float(0.5));
+    assert_eq!(JsonValue::from(true), JsonValue::Bool(true));
+    assert_eq!(JsonValue::from("abc".to_string()), JsonValue::String("abc".into()));
+    assert_eq!(JsonValue::from("abc"), JsonValue::String("abc".into()));
+}
+
+#[test]
+fn test_from_option() {
+    assert_eq!(JsonValue::from(None::<i64>), JsonValue::Null);
+    assert_eq!(JsonValue::from(Some("x")), JsonValue::String("x".into()));
+    assert_eq!(JsonValue::from(Some(Some(1i64))), JsonValue::Number(1));
+}
+
+#[test]
+fn test_collect_and_extend() {
+    let elements = vec![JsonValue::Number(1), JsonValue::from("two"), JsonValue::Null];
+    let array: JsonValue = elements.into_iter().collect();
+    assert_eq!(array, crate::parse_str(r#"[1, "two", null]"#).unwrap());
+    let squares: JsonValue = (1..4i64).map(|n| JsonValue::from(n * n)).collect();
+    assert_eq!(squares, crate::parse_str("[1, 4, 9]").unwrap());
+
+    let members = vec![("a".to_string(), JsonValue::Number(1)), ("b".to_string(), JsonValue::Bool(true))];
+    let mut object: JsonValue = members.into_iter().collect();
+    assert_eq!(object, crate::parse_str(r#"{"a": 1, "b": true}"#).unwrap());
+    object.extend([("a".to_string(), JsonValue::Null), ("c".to_string(), JsonValue::from("x"))]);
+    assert_eq!(object, crate::parse_str(r#"{"a": null, "b": true, "c": "x"}"#).unwrap());
+
+    let mut array = JsonValue::Null;
+    array.extend(vec![JsonValue::Number(1)]);
+    array.extend([squares]);
+    assert_eq!(array, crate::parse_str("[1, [1, 4, 9]]").unwrap());
+    assert_eq!(std::iter::empty::<JsonValue>().collect::<JsonValue>(), JsonValue::Array(vec![]));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_from_nested() {
+    let value = JsonValue::from(HashMap::from([
+        ("name".to_string(), "tinyserde".into()),
+        ("tags".to_string(), vec![JsonValue::from("json"), JsonValue::from(None::<bool>)].into()),
+        ("version".to_string(), JsonValue::from(1u32)),
+    ]));
+    assert_eq!(value, crate::parse_str(r#"{"name": "tinyserde", "tags": ["json", null], "version": 1}"#).unwrap());
+}
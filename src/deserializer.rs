@@ -1,5 +1,6 @@
 use crate::parser::JsonValue;
-use std::collections::HashMap;
+use crate::object::JsonObject;
+use crate::prelude::*;
 
 #[derive(Debug)]
 pub struct Deserializer {
@@ -11,16 +12,16 @@ impl Deserializer {
         self.deserialize_helper(self.input.clone())
     }
 
-    fn deserialize_helper(&self, value: JsonValue) -> String {
-        return match value {
-            JsonValue::String(value) => value,
-            JsonValue::Object(map) => self.deserialize_object(map),
-            JsonValue::Array(array) => self.deserialize_array(array),
+    fn deserialize_helper(&self, mut value: JsonValue) -> String {
+        match &mut value {
+            JsonValue::String(value) => core::mem::take(value).into_owned(),
+            JsonValue::Object(map) => self.deserialize_object(core::mem::take(map)),
+            JsonValue::Array(array) => self.deserialize_array(core::mem::take(array)),
             _ => "".to_string(),
         }
     }
 
-    fn deserialize_object(&self, map: HashMap<String, JsonValue>) -> String {
+    fn deserialize_object(&self, map: JsonObject) -> String {
         let mut builder = String::new();
         builder.push('{');
         for (k, v) in map.into_iter() {
@@ -0,0 +1,172 @@
+use crate::parser::{JsonParser, ParserError};
+
+/// Receives the values of a document in order as it is parsed. Every
+/// method does nothing by default.
+pub trait JsonEventHandler {
+    fn on_null(&mut self) {}
+    fn on_bool(&mut self, _value: bool) {}
+    fn on_number(&mut self, _value: i64) {}
+    /// Called for integers above `i64::MAX`.
+    fn on_number64(&mut self, _value: u64) {}
+    /// Called for numbers parsed with `JsonParserConfig::bignum`.
+    #[cfg(feature = "bignum")]
+    fn on_bignum(&mut self, _value: &bigdecimal::BigDecimal) {}
+    /// Called for numbers with a fraction or an exponent.
+    fn on_float(&mut self, _value: f64) {}
+    fn on_string(&mut self, _value: &str) {}
+    fn on_start_object(&mut self) {}
+    fn on_object_key(&mut self, _key: &str) {}
+    fn on_end_object(&mut self) {}
+    fn on_start_array(&mut self) {}
+    fn on_end_array(&mut self) {}
+}
+
+/// Parses a document without building a `JsonValue`, passing each value to
+/// a `JsonEventHandler` instead.
+pub struct JsonEventParser;
+
+impl JsonEventParser {
+    pub fn parse(input: &str, handler: &mut impl JsonEventHandler) -> Result<(), ParserError> {
+        JsonParser::new(input).parse_events(handler)
+    }
+}
+
+#[cfg(test)]
+use crate::parser::JsonValue;
+
+#[cfg(test)]
+#[derive(Debug, Default, PartialEq)]
+struct CountingHandler {
+    nulls: usize,
+    bools: usize,
+    numbers: usize,
+    strings: usize,
+    objects: usize,
+    keys: usize,
+    arrays: usize,
+}
+
+#[cfg(test)]
+impl JsonEventHandler for CountingHandler {
+    fn on_null(&mut self) {
+        self.nulls += 1;
+    }
+    fn on_bool(&mut self, _value: bool) {
+        self.bools += 1;
+    }
+    fn on_number(&mut self, _value: i64) {
+        self.numbers += 1;
+    }
+    fn on_float(&mut self, _value: f64) {
+        self.numbers += 1;
+    }
+    fn on_string(&mut self, _value: &str) {
+        self.strings += 1;
+    }
+    fn on_start_object(&mut self) {
+        self.objects += 1;
+    }
+    fn on_object_key(&mut self, _key: &str) {
+        self.keys += 1;
+    }
+    fn on_start_array(&mut self) {
+        self.arrays += 1;
+    }
+}
+
+#[cfg(test)]
+fn count_values(value: &JsonValue, counts: &mut CountingHandler) {
+    match value {
+        JsonValue::Null => counts.nulls += 1,
+        JsonValue::Bool(_) => counts.bools += 1,
+        JsonValue::Number(_) | JsonValue::Number64(_) | JsonValue::Float(_) => counts.numbers += 1,
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(_) => counts.numbers += 1,
+        JsonValue::String(_) => counts.strings += 1,
+        JsonValue::Array(array) => {
+            counts.arrays += 1;
+            array.iter().for_each(|value| count_values(value, counts));
+        }
+        JsonValue::Object(map) => {
+            counts.objects += 1;
+            counts.keys += map.len();
+            map.values().for_each(|value| count_values(value, counts));
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct RecordingHandler {
+    events: Vec<String>,
+}
+
+#[cfg(test)]
+impl JsonEventHandler for RecordingHandler {
+    fn on_null(&mut self) {
+        self.events.push("null".to_string());
+    }
+    fn on_bool(&mut self, value: bool) {
+        self.events.push(value.to_string());
+    }
+    fn on_number(&mut self, value: i64) {
+        self.events.push(value.to_string());
+    }
+    fn on_float(&mut self, value: f64) {
+        self.events.push(value.to_string());
+    }
+    fn on_string(&mut self, value: &str) {
+        self.events.push(format!("{:?}", value));
+    }
+    fn on_start_object(&mut self) {
+        self.events.push("{".to_string());
+    }
+    fn on_object_key(&mut self, key: &str) {
+        self.events.push(format!("{}:", key));
+    }
+    fn on_end_object(&mut self) {
+        self.events.push("}".to_string());
+    }
+    fn on_start_array(&mut self) {
+        self.events.push("[".to_string());
+    }
+    fn on_end_array(&mut self) {
+        self.events.push("]".to_string());
+    }
+}
+
+#[test]
+fn test_event_parser_counts_match_full_parse() {
+    let inputs = [
+        "null",
+        "[]",
+        "{}",
+        r#"{"a": [1, 2.5, "x", true, null, {}], "b": {"c": [[], [false]]}}"#,
+        r#"[{"k": "v"}, {"k": "w", "n": -3}, "tail"]"#,
+    ];
+    for input in inputs {
+        let mut handler = CountingHandler::default();
+        assert_eq!(JsonEventParser::parse(input, &mut handler), Ok(()), "input: {:?}", input);
+        let mut expected = CountingHandler::default();
+        count_values(&crate::parse_str(input).unwrap(), &mut expected);
+        assert_eq!(handler, expected, "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_event_parser_event_order() {
+    let mut handler = RecordingHandler::default();
+    JsonEventParser::parse(r#"{"a": [1, "s\n", null], "b": {}}"#, &mut handler).unwrap();
+    let expected = ["{", "a:", "[", "1", "\"s\\n\"", "null", "]", "b:", "{", "}", "}"];
+    assert_eq!(handler.events, expected);
+}
+
+#[test]
+fn test_event_parser_errors() {
+    let cases = ["[1, 2", "{\"a\" 1}", "[1] 2", "{\"a\": }", "[1,]"];
+    for input in cases {
+        let mut handler = CountingHandler::default();
+        let result = JsonEventParser::parse(input, &mut handler);
+        assert_eq!(result.err(), crate::parse_str(input).err(), "input: {:?}", input);
+    }
+}
@@ -0,0 +1,349 @@
+use crate::config::JsonParserConfig;
+#[cfg(test)]
+use crate::config::{DuplicateKeyPolicy, ParseMode};
+use crate::parser::{JsonParser, JsonValue, ParseState, ParserError};
+use crate::prelude::*;
+use crate::tokenizer::is_whitespace;
+use alloc::collections::VecDeque;
+
+/// Parses a stream of whitespace separated values that arrives in chunks,
+/// such as data read from a socket. A value may be split anywhere, even in
+/// the middle of a string or a number.
+///
+/// The parse of a value resumes where the previous chunk left off, and
+/// input is dropped from the buffer once it has been parsed, so each byte
+/// is only parsed about once. Byte offsets, lines and columns in errors
+/// count from the start of the stream.
+pub struct FeedableParser {
+    parser: JsonParser,
+    // The containers of the value being parsed.
+    state: ParseState,
+    // Set when the step at the cursor ran out of input, along with the
+    // length of the input at the time. The step is only read again once
+    // the input after that point can change its outcome.
+    waiting: Option<(usize, Wait)>,
+    // Whether any input has arrived, after which a byte order mark is no
+    // longer at the start of the stream.
+    started: bool,
+    finished: bool,
+    failed: bool,
+    // Stops parsing after the first value, leaving the rest buffered.
+    single: bool,
+    ready: VecDeque<Result<JsonValue, ParserError>>,
+}
+
+// What a step that ran out of input needs before it is read again.
+#[derive(Clone, Copy)]
+enum Wait {
+    // Any more input, after a step that read up to the end of the input
+    // without failing, such as a number that more digits may follow.
+    Input,
+    // A delimiter, after a step that failed before one.
+    Delimiter,
+    // A quote, which may close a string that ran into the end of input.
+    Quote,
+}
+
+impl Wait {
+    fn is_met(self, input: &str) -> bool {
+        match self {
+            Wait::Input => !input.is_empty(),
+            Wait::Delimiter => input.contains(is_delimiter),
+            Wait::Quote => input.contains(['"', '\'']),
+        }
+    }
+}
+
+// Characters that end a token. The parser reads each token up to one of
+// these, so an error raised before one cannot be undone by more input.
+fn is_delimiter(c: char) -> bool {
+    is_whitespace(c) || matches!(c, ',' | ':' | '[' | ']' | '{' | '}' | '"')
+}
+
+impl FeedableParser {
+    pub fn new(config: JsonParserConfig) -> Self {
+        FeedableParser {
+            parser: JsonParser::with_config(String::new(), config),
+            state: ParseState::default(),
+            waiting: None,
+            started: false,
+            finished: false,
+            failed: false,
+            single: false,
+            ready: VecDeque::new(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn single_value() -> Self {
+        FeedableParser {
+            single: true,
+            ..Self::new(JsonParserConfig::default())
+        }
+    }
+
+    // The number of bytes of input that have not been parsed yet.
+    #[cfg(feature = "std")]
+    pub(crate) fn buffered_len(&self) -> usize {
+        self.parser.input.len() - self.parser.cursor
+    }
+
+    /// Appends `chunk` to the buffered input and parses every value it
+    /// completes. Input fed after `finish` is ignored.
+    pub fn feed(&mut self, chunk: &str) {
+        if self.finished {
+            return;
+        }
+        self.parser.input.push_str(chunk);
+        if !self.started && !chunk.is_empty() {
+            self.started = true;
+            self.parser.skip_bom();
+        }
+        self.parse_available();
+    }
+
+    /// Marks the end of the input. A trailing number or string is then
+    /// complete, and a value that is still unfinished becomes an error.
+    pub fn finish(&mut self) {
+        self.finished = true;
+        self.parse_available();
+    }
+
+    /// Returns the next parsed value, or None if no complete value is
+    /// available yet. Nothing is parsed after the first error.
+    pub fn try_get(&mut self) -> Option<Result<JsonValue, ParserError>> {
+        self.ready.pop_front()
+    }
+
+    // Reads and applies steps until one runs out of input. A step that
+    // does is undone, and read again once more input arrives.
+    fn parse_available(&mut self) {
+        while !self.failed && (!self.single || self.ready.is_empty()) {
+            let end = self.parser.input.len();
+            if let Some((scanned, wait)) = self.waiting.take() {
+                if !self.finished && !wait.is_met(&self.parser.input[scanned..]) {
+                    self.waiting = Some((end, wait));
+                    break;
+                }
+            }
+            // Every step starts by skipping whitespace, so it can be
+            // skipped for good, but a comment may continue in later input.
+            self.parser.advance_whitespace();
+            let checkpoint = self.parser.checkpoint();
+            if self.state.is_empty() {
+                self.parser.start_value();
+                self.parser.skip_whitespace();
+                if self.parser.cursor >= end {
+                    self.parser.restore(checkpoint);
+                    break;
+                }
+            }
+            let result = self.parser.read_step(&self.state);
+            let cursor = self.parser.cursor;
+            // More digits may follow a number and a '[' may turn out to
+            // open an empty array, so a step that reads up to the end of
+            // the input is only complete if it ends in a closing quote or
+            // bracket.
+            let wait = match &result {
+                _ if self.finished => None,
+                Ok(_) if cursor >= end && !self.parser.input.ends_with(['"', '\'', ']', '}']) => Some(Wait::Input),
+                Err(ParserError::UnterminatedString { .. }) => Some(Wait::Quote),
+                Err(_) if !self.parser.input[cursor..].contains(is_delimiter) => Some(Wait::Delimiter),
+                _ => None,
+            };
+            if let Some(wait) = wait {
+                self.parser.restore(checkpoint);
+                self.waiting = Some((end, wait));
+                break;
+            }
+            match result.and_then(|step| self.parser.apply_step(&mut self.state, step)) {
+                Ok(Some(value)) => self.ready.push_back(Ok(value)),
+                Ok(None) => {}
+                Err(err) => {
+                    self.failed = true;
+                    self.ready.push_back(Err(err));
+                }
+            }
+        }
+        self.drop_parsed_input();
+    }
+
+    // Drops the parsed input once it makes up half of the buffer, so that
+    // the input still to be parsed is moved a bounded number of times.
+    fn drop_parsed_input(&mut self) {
+        let parsed = self.parser.cursor;
+        if parsed == 0 || parsed < self.parser.input.len() / 2 {
+            return;
+        }
+        self.parser.input.drain(..parsed);
+        self.parser.cursor = 0;
+        self.parser.offset += parsed;
+        if let Some((scanned, _)) = &mut self.waiting {
+            *scanned -= parsed;
+        }
+    }
+}
+
+#[cfg(test)]
+const FIXTURES: &[&str] = &[
+    "null",
+    "true",
+    "-12.5e3",
+    "9223372036854775807",
+    r#""plain""#,
+    r#""𝄞 é \"\\\/\b\f\n\r\t é 𝄞""#,
+    r#""nested \"quotes\" inside""#,
+    "[]",
+    " [ 1 , [ 2.5 , [ ] ] , \"x\" ] ",
+    r#"{"a": {"b": [true, false, null]}, "c": "d", "e": -0.5}"#,
+    "{\n  \"multi\": [\n    1,\n    2\n  ]\n}\n",
+    "tru",
+    "[1,]",
+    "[1 2]",
+    "[1.]",
+    r#"{"a" 1}"#,
+    r#"{"a": [1, 2}"#,
+    r#""unterminated"#,
+    r#""\x""#,
+    r#""\u12""#,
+    r#"["\ud800"]"#,
+    "[99999999999999999999]",
+    "",
+    "   ",
+];
+
+#[cfg(test)]
+fn parse_chunks(chunks: &[&str]) -> Vec<Result<JsonValue, ParserError>> {
+    parse_chunks_with_config(chunks, JsonParserConfig::default())
+}
+
+#[cfg(test)]
+fn parse_chunks_with_config(chunks: &[&str], config: JsonParserConfig) -> Vec<Result<JsonValue, ParserError>> {
+    let mut parser = FeedableParser::new(config);
+    let mut values = vec![];
+    for chunk in chunks {
+        parser.feed(chunk);
+        values.extend(core::iter::from_fn(|| parser.try_get()));
+    }
+    parser.finish();
+    values.extend(core::iter::from_fn(|| parser.try_get()));
+    values
+}
+
+#[test]
+fn test_feed_split_at_every_boundary() {
+    for input in FIXTURES {
+        let expected: Vec<_> = match input.trim() {
+            "" => vec![],
+            _ => vec![crate::parse_str(input)],
+        };
+        for split in (0..=input.len()).filter(|i| input.is_char_boundary(*i)) {
+            let (first, second) = input.split_at(split);
+            assert_eq!(parse_chunks(&[first, second]), expected, "input: {:?} split at {}", input, split);
+        }
+        let chars: Vec<String> = input.chars().map(String::from).collect();
+        let chars: Vec<&str> = chars.iter().map(String::as_str).collect();
+        assert_eq!(parse_chunks(&chars), expected, "input: {:?} fed by character", input);
+    }
+}
+
+#[test]
+fn test_feed_lenient_input_split_at_every_boundary() {
+    let cases = [
+        (ParseMode::Lenient, "[1, 2,] {\"a\": -Infinity,}"),
+        (ParseMode::Lenient, "// comment\n[1 // more\n, 2] // end"),
+        (ParseMode::Json5, "{a: 'b', /* c */ \"d\": [0x1F, +Infinity,],} 'e\\\nf'"),
+        (ParseMode::Json5, "/* only a comment */"),
+        (ParseMode::Json5, "[1 /* unterminated"),
+    ];
+    for (mode, input) in cases {
+        let config = JsonParserConfig { mode, ..JsonParserConfig::default() };
+        let expected: Vec<_> = JsonParser::with_config(input, config.clone()).parse_many().collect();
+        for split in 0..=input.len() {
+            let (first, second) = input.split_at(split);
+            assert_eq!(parse_chunks_with_config(&[first, second], config.clone()), expected, "input: {:?} split at {}", input, split);
+        }
+        let chars: Vec<String> = input.chars().map(String::from).collect();
+        let chars: Vec<&str> = chars.iter().map(String::as_str).collect();
+        assert_eq!(parse_chunks_with_config(&chars, config), expected, "input: {:?} fed by character", input);
+    }
+}
+
+#[test]
+fn test_feed_large_input_in_small_chunks() {
+    let input = format!("[{}]", vec![r#"{"id": 12345, "name": "item", "tags": ["a", "b"]}"#; 40_000].join(",\n"));
+    let chunks: Vec<&str> = input.as_bytes().chunks(64).map(|chunk| core::str::from_utf8(chunk).unwrap()).collect();
+    let whole = crate::convert::count_allocations(|| drop(crate::parse_str(&input)));
+    // A parse that started over for every chunk would build the array
+    // again each time and allocate thousands of times more.
+    let mut parser = FeedableParser::new(JsonParserConfig::default());
+    let mut buffered = 0;
+    let chunked = crate::convert::count_allocations(|| {
+        for chunk in &chunks {
+            parser.feed(chunk);
+            buffered = buffered.max(parser.parser.input.len());
+        }
+        parser.finish();
+    });
+    assert_eq!(parser.try_get(), Some(crate::parse_str(&input)));
+    assert!(chunked < whole * 2, "{} allocations in chunks, {} at once", chunked, whole);
+    // Parsed input is dropped rather than kept until the value is done.
+    assert!(buffered < 1024, "{} bytes buffered", buffered);
+}
+
+#[test]
+fn test_feed_error_offsets_count_from_start_of_stream() {
+    let config = JsonParserConfig { duplicate_key_policy: DuplicateKeyPolicy::Error, ..JsonParserConfig::default() };
+    let input = "[1, 2, 3] {\"k\": 1,\n \"k\": 2}";
+    let chunks: Vec<&str> = input.as_bytes().chunks(3).map(|chunk| core::str::from_utf8(chunk).unwrap()).collect();
+    let values = parse_chunks_with_config(&chunks, config);
+    let duplicate = ParserError::DuplicateKey { key: "k".to_string(), line: 2, column: 2, first_at: 11, second_at: 20 };
+    assert_eq!(values, vec![crate::parse_str("[1, 2, 3]"), Err(duplicate)]);
+    let values = parse_chunks(&["[1, 2, 3] [4,", " 5] \"abc"]);
+    assert_eq!(values[2], Err(ParserError::UnterminatedString { line: 1, column: 18, at_byte: 17 }));
+}
+
+#[test]
+fn test_feed_returns_values_once_complete() {
+    let mut parser = FeedableParser::new(JsonParserConfig::default());
+    parser.feed(r#"{"a": "#);
+    assert_eq!(parser.try_get(), None);
+    parser.feed(r#"[1]} 2"#);
+    assert_eq!(parser.try_get(), Some(crate::parse_str(r#"{"a": [1]}"#)));
+    assert_eq!(parser.try_get(), None);
+    parser.feed("3 \"x");
+    assert_eq!(parser.try_get(), Some(Ok(JsonValue::Number(23))));
+    parser.feed("\"");
+    assert_eq!(parser.try_get(), Some(Ok(JsonValue::String("x".into()))));
+    assert_eq!(parser.try_get(), None);
+}
+
+#[test]
+fn test_feed_adjacent_strings() {
+    let strings = |values: &[&str]| values.iter().map(|value| Ok(JsonValue::from(*value))).collect::<Vec<_>>();
+    assert_eq!(parse_chunks(&["\"a\" ", "\"b\""]), strings(&["a", "b"]));
+    assert_eq!(parse_chunks(&["\"a\"", "\"b\"\n\"", "c\""]), strings(&["a", "b", "c"]));
+    assert_eq!(parse_chunks(&["\"a\" \"b\" \"c\""]), strings(&["a", "b", "c"]));
+}
+
+#[test]
+fn test_feed_skips_byte_order_mark_at_start() {
+    assert_eq!(parse_chunks(&["", "\u{feff}", "[1] ", "2"]), vec![crate::parse_str("[1]"), Ok(JsonValue::Number(2))]);
+    assert!(parse_chunks(&["1 ", "\u{feff}2"])[1].is_err());
+    let mut parser = FeedableParser::new(JsonParserConfig { reject_bom: true, ..JsonParserConfig::default() });
+    parser.feed("\u{feff}1 ");
+    assert!(parser.try_get().unwrap().is_err());
+}
+
+#[test]
+fn test_feed_stops_after_error() {
+    let values = parse_chunks(&["1\n[", "2]\n", "[x", "] 3"]);
+    assert_eq!(
+        values,
+        vec![
+            Ok(JsonValue::Number(1)),
+            crate::parse_str("[2]"),
+            Err(ParserError::SyntaxError { line: 3, column: 2, message: "Expected a value" }),
+        ]
+    );
+}
@@ -0,0 +1,155 @@
+use crate::jcs::to_exact_jcs_bytes;
+use crate::parser::JsonValue;
+use core::fmt;
+
+/// A 32 byte digest of a value's content, created by
+/// `JsonValue::fingerprint`. Displays as lowercase hex, for example in an
+/// ETag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint([u8; 32]);
+
+impl Fingerprint {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<Fingerprint> for [u8; 32] {
+    fn from(fingerprint: Fingerprint) -> Self {
+        fingerprint.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sha2")]
+fn digest(bytes: &[u8]) -> [u8; 32] {
+    use sha2::Digest;
+    sha2::Sha256::digest(bytes).into()
+}
+
+// Four FNV-1a hashes, each of the lane number followed by the bytes.
+#[cfg(not(feature = "sha2"))]
+fn digest(bytes: &[u8]) -> [u8; 32] {
+    let mut digest = [0; 32];
+    for (lane, chunk) in digest.chunks_exact_mut(8).enumerate() {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for &byte in core::iter::once(&(lane as u8)).chain(bytes) {
+            hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+        chunk.copy_from_slice(&hash.to_be_bytes());
+    }
+    digest
+}
+
+impl JsonValue {
+    /// A digest of the value that equal values share, whatever order their
+    /// object members are in, computed over the RFC 8785 canonical form.
+    /// Unlike `to_jcs_bytes`, integers that an f64 would round keep all of
+    /// their digits, a `BigNum` keeps its exact value, and NaN and the
+    /// infinities are told apart from null. The digest is SHA-256 with the `sha2` feature, and is
+    /// otherwise built from FNV-1a hashes, which tell values apart but do
+    /// not resist deliberate collisions.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint(digest(&to_exact_jcs_bytes(self)))
+    }
+}
+
+#[test]
+fn test_fingerprint_ignores_member_order() {
+    let config = crate::JsonParserConfig { preserve_order: crate::JsonObjectKind::BTreeMap, ..Default::default() };
+    let first = crate::parse_str(r#"{"b": [1, {"y": null, "x": true}], "a": "s"}"#).unwrap();
+    let second = crate::JsonParser::with_config(r#"{"a": "s", "b": [1.0, {"x": true, "y": null}]}"#, config).parse().unwrap();
+    assert_eq!(first, second);
+    assert_eq!(first.fingerprint(), second.fingerprint());
+    assert_eq!(JsonValue::Number64(5).fingerprint(), JsonValue::Float(5.0).fingerprint());
+}
+
+#[test]
+fn test_fingerprint_tells_values_apart() {
+    let values = [
+        r#"{"name": "ada"}"#,
+        r#"{"name": "adb"}"#,
+        r#"{"namf": "ada"}"#,
+        r#"{"name": "ada "}"#,
+        r#"["ada"]"#,
+        r#""ada""#,
+        "9007199254740992",
+        "9007199254740993",
+        "18446744073709551615",
+        "1.5",
+        "null",
+        "[]",
+        "{}",
+    ];
+    for (i, a) in values.iter().enumerate() {
+        for b in &values[i + 1..] {
+            let (a, b) = (crate::parse_str(a).unwrap(), crate::parse_str(b).unwrap());
+            assert_ne!(a.fingerprint(), b.fingerprint(), "values: {} {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn test_fingerprint_tells_numbers_apart() {
+    let values = [
+        JsonValue::Null,
+        JsonValue::Float(f64::NAN),
+        JsonValue::Float(f64::INFINITY),
+        JsonValue::Float(f64::NEG_INFINITY),
+        JsonValue::from("NaN"),
+        JsonValue::Number(i64::MAX),
+        JsonValue::Float(9_223_372_036_854_775_808.0),
+    ];
+    for (i, a) in values.iter().enumerate() {
+        for b in &values[i + 1..] {
+            assert_ne!(a, b);
+            assert_ne!(a.fingerprint(), b.fingerprint(), "values: {:?} {:?}", a, b);
+        }
+    }
+    assert_eq!(JsonValue::Float(f64::NAN).fingerprint(), JsonValue::Float(-f64::NAN).fingerprint());
+}
+
+#[test]
+#[cfg(feature = "bignum")]
+fn test_fingerprint_keeps_bignum_digits() {
+    let config = crate::JsonParserConfig { bignum: true, ..Default::default() };
+    let parse = |input: &str| crate::JsonParser::with_config(input, config.clone()).parse().unwrap();
+    let inputs = [
+        "123456789012345678901234567890",
+        "123456789012345678901234567891",
+        "0.30000000000000000001",
+        "0.30000000000000000002",
+        "1e400",
+        "-1e400",
+        "null",
+        "5",
+    ];
+    for (i, a) in inputs.iter().enumerate() {
+        for b in &inputs[i + 1..] {
+            let (a, b) = (parse(a), parse(b));
+            assert_ne!(a, b);
+            assert_ne!(a.fingerprint(), b.fingerprint(), "values: {:?} {:?}", a, b);
+        }
+    }
+    let big = |input: &str| JsonValue::BigNum(input.parse().unwrap());
+    assert_eq!(big("1.50").fingerprint(), big("1.5").fingerprint());
+    assert_eq!(big("1e400").fingerprint(), big("10e399").fingerprint());
+    assert_ne!(big("5").fingerprint(), JsonValue::Number(5).fingerprint());
+}
+
+#[test]
+#[cfg(feature = "sha2")]
+fn test_fingerprint_is_sha256_of_canonical_form() {
+    let value = crate::parse_str(r#"{"a": [1.0, true]}"#).unwrap();
+    // The SHA-256 of `{"a":[1,true]}`.
+    assert_eq!(value.fingerprint().to_string(), "82e73b6eda113eba546f35e152a6cec7485f2c6fd8e4012c9fa0371761bde86d");
+    assert_eq!(<[u8; 32]>::from(value.fingerprint()), *value.fingerprint().as_bytes());
+}
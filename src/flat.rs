@@ -0,0 +1,192 @@
+use crate::iter::{children, JsonItem};
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+use crate::pointer::parse_index;
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use std::collections::HashMap;
+
+/// Returned by `JsonValue::unflatten_from_map` when the keys do not
+/// describe a single value.
+#[derive(Debug, PartialEq)]
+pub enum UnflattenError {
+    /// `key` holds a value and is also the prefix of a longer key.
+    Conflict { key: String },
+    /// The keys under an array skip the index in `key`.
+    MissingIndex { key: String },
+}
+
+// The keys read so far, as a tree whose leaves are the values.
+enum Node {
+    Leaf(JsonValue),
+    Branch(BTreeMap<String, Node>),
+}
+
+fn join(key: Option<&str>, segment: &str, separator: char) -> String {
+    match key {
+        Some(key) => format!("{}{}{}", key, separator, segment),
+        None => segment.to_string(),
+    }
+}
+
+// A branch whose segments are all array indices is an array, and any other
+// branch is an object.
+fn into_value(node: Node, key: Option<&str>, separator: char) -> Result<JsonValue, UnflattenError> {
+    let branch = match node {
+        Node::Leaf(value) => return Ok(value),
+        Node::Branch(branch) => branch,
+    };
+    let indices: Option<Vec<usize>> = branch.keys().map(|segment| parse_index(segment)).collect();
+    match indices {
+        Some(mut indices) if !indices.is_empty() => {
+            indices.sort_unstable();
+            if let Some(missing) = (0..indices.len()).find(|&i| indices[i] != i) {
+                return Err(UnflattenError::MissingIndex { key: join(key, &missing.to_string(), separator) });
+            }
+            let mut elements: Vec<(usize, JsonValue)> = Vec::with_capacity(branch.len());
+            for (segment, child) in branch {
+                let child_key = join(key, &segment, separator);
+                elements.push((parse_index(&segment).unwrap(), into_value(child, Some(&child_key), separator)?));
+            }
+            elements.sort_unstable_by_key(|(index, _)| *index);
+            Ok(JsonValue::Array(elements.into_iter().map(|(_, element)| element).collect()))
+        }
+        _ => {
+            let mut object = JsonObject::new();
+            for (segment, child) in branch {
+                let child_key = join(key, &segment, separator);
+                let value = into_value(child, Some(&child_key), separator)?;
+                object.insert(segment, value);
+            }
+            Ok(JsonValue::Object(object))
+        }
+    }
+}
+
+impl JsonValue {
+    /// Returns the leaves of the value keyed by their path, with object
+    /// keys and array indices joined by `separator`, so that
+    /// `{"a": {"b": 1}, "c": [2, 3]}` becomes `a.b`, `c.0` and `c.1` with
+    /// `.`. Empty arrays and objects are leaves too, and a value that is
+    /// not a container is keyed by the empty string. Separators in keys are
+    /// not escaped, so when two paths join to the same key only one of
+    /// their values is kept.
+    pub fn flatten_to_map(&self, separator: char) -> HashMap<String, JsonValue> {
+        let mut flat = HashMap::new();
+        let mut stack: Vec<(Option<String>, &JsonValue)> = vec![(None, self)];
+        while let Some((key, value)) = stack.pop() {
+            let Some(items) = children(value).filter(|_| value.len() != Some(0)) else {
+                flat.insert(key.unwrap_or_default(), value.clone());
+                continue;
+            };
+            for (index, item) in items.enumerate() {
+                let segment = match item {
+                    JsonItem::Element(_) => index.to_string(),
+                    JsonItem::Member(name, _) => name.to_string(),
+                };
+                let child_key = match &key {
+                    Some(key) => format!("{}{}{}", key, separator, segment),
+                    None => segment,
+                };
+                stack.push((Some(child_key), item.value()));
+            }
+        }
+        flat
+    }
+
+    /// Rebuilds a value from the leaves that `flatten_to_map` returns.
+    /// Keys are split at `separator`, and a level whose segments are all
+    /// array indices becomes an array while any other level becomes an
+    /// object, so objects keyed only by indices come back as arrays. The
+    /// empty key is the whole value, and no pairs at all make an empty
+    /// object.
+    pub fn unflatten_from_map(pairs: HashMap<String, JsonValue>, separator: char) -> Result<JsonValue, UnflattenError> {
+        let mut root = Node::Branch(BTreeMap::new());
+        for (key, value) in pairs {
+            let segments: Vec<&str> = if key.is_empty() { vec![] } else { key.split(separator).collect() };
+            let mut node = &mut root;
+            for (depth, segment) in segments.iter().enumerate() {
+                node = match node {
+                    Node::Branch(branch) => branch.entry(segment.to_string()).or_insert_with(|| Node::Branch(BTreeMap::new())),
+                    Node::Leaf(_) => return Err(UnflattenError::Conflict { key: segments[..depth].join(&separator.to_string()) }),
+                };
+            }
+            if !matches!(node, Node::Branch(branch) if branch.is_empty()) {
+                return Err(UnflattenError::Conflict { key });
+            }
+            *node = Node::Leaf(value);
+        }
+        into_value(root, None, separator)
+    }
+}
+
+#[test]
+fn test_flatten_to_map() {
+    let value = crate::parse_str(r#"{"a": {"b": 1}, "c": [2, 3]}"#).unwrap();
+    let expected = HashMap::from([
+        ("a.b".to_string(), JsonValue::Number(1)),
+        ("c.0".to_string(), JsonValue::Number(2)),
+        ("c.1".to_string(), JsonValue::Number(3)),
+    ]);
+    assert_eq!(value.flatten_to_map('.'), expected);
+
+    let value = crate::parse_str(r#"{"users": [{"name": "ada", "tags": ["x"]}, {"name": "bob", "tags": []}]}"#).unwrap();
+    let expected = HashMap::from([
+        ("users/0/name".to_string(), JsonValue::from("ada")),
+        ("users/0/tags/0".to_string(), JsonValue::from("x")),
+        ("users/1/name".to_string(), JsonValue::from("bob")),
+        ("users/1/tags".to_string(), JsonValue::Array(vec![])),
+    ]);
+    assert_eq!(value.flatten_to_map('/'), expected);
+}
+
+#[test]
+fn test_flatten_to_map_keeps_only_leaves() {
+    let value = crate::parse_str(r#"{"null": null, "bool": true, "number": 2.5, "string": "s", "array": [], "object": {}, "nested": {"deep": [[false]]}}"#).unwrap();
+    let flat = value.flatten_to_map('.');
+    let mut keys: Vec<&str> = flat.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, ["array", "bool", "nested.deep.0.0", "null", "number", "object", "string"]);
+    assert_eq!(flat["nested.deep.0.0"], JsonValue::Bool(false));
+    assert_eq!(flat["object"], crate::parse_str("{}").unwrap());
+    for leaf in flat.values() {
+        assert_eq!(leaf.len().unwrap_or(0), 0, "leaf: {}", leaf);
+    }
+    for value in crate::value::sample_values().into_iter().filter(|value| value.len().is_none()) {
+        assert_eq!(value.flatten_to_map('.'), HashMap::from([(String::new(), value.clone())]), "value: {}", value);
+    }
+}
+
+#[test]
+fn test_unflatten_round_trip() {
+    let cases = [
+        r#"{"a": {"b": 1}, "c": [2, 3]}"#,
+        r#"{"users": [{"name": "ada", "tags": ["x", "y"]}, {"name": "bob", "tags": []}], "empty": {}}"#,
+        r#"[[1, [2, [3]]], {"k": null}, true, "s", 2.5]"#,
+        r#"{"list": [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], "01": "not an index"}"#,
+        r#"{"a": {"": {"b": false}}}"#,
+        r#"[]"#,
+        r#""scalar""#,
+    ];
+    for input in cases {
+        let value = crate::parse_str(input).unwrap();
+        for separator in ['.', '/'] {
+            assert_eq!(JsonValue::unflatten_from_map(value.flatten_to_map(separator), separator), Ok(value.clone()), "input: {}", input);
+        }
+    }
+    assert_eq!(JsonValue::unflatten_from_map(HashMap::new(), '.'), Ok(crate::parse_str("{}").unwrap()));
+}
+
+#[test]
+fn test_unflatten_errors() {
+    let pairs = |pairs: &[(&str, i64)]| pairs.iter().map(|(key, value)| (key.to_string(), JsonValue::Number(*value))).collect();
+    let conflict = |key: &str| Err(UnflattenError::Conflict { key: key.to_string() });
+    assert_eq!(JsonValue::unflatten_from_map(pairs(&[("a", 1), ("a.b", 2)]), '.'), conflict("a"));
+    assert_eq!(JsonValue::unflatten_from_map(pairs(&[("a.b", 1), ("a.b.c.d", 2)]), '.'), conflict("a.b"));
+    assert_eq!(JsonValue::unflatten_from_map(pairs(&[("", 1), ("a", 2)]), '.'), conflict(""));
+    assert_eq!(
+        JsonValue::unflatten_from_map(pairs(&[("a.0", 1), ("a.2", 2)]), '.'),
+        Err(UnflattenError::MissingIndex { key: "a.1".to_string() })
+    );
+    assert_eq!(JsonValue::unflatten_from_map(pairs(&[("a.0", 1), ("a.b", 2)]), '.'), Ok(crate::parse_str(r#"{"a": {"0": 1, "b": 2}}"#).unwrap()));
+}
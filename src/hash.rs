@@ -0,0 +1,126 @@
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+use crate::prelude::*;
+use core::hash::{Hash, Hasher};
+
+// Every NaN is equal to every other, so equality is reflexive for floats too.
+impl Eq for JsonValue {}
+
+impl Eq for JsonObject {}
+
+impl Hash for JsonValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // A `Number64` equals the `Number` with the same value, and a
+        // `Float` equals the integer with the same value.
+        match self {
+            JsonValue::Number64(value) => {
+                if let Ok(value) = i64::try_from(*value) {
+                    return JsonValue::Number(value).hash(state);
+                }
+            }
+            JsonValue::Float(value) if value % 1.0 == 0.0 => {
+                if (-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(value) {
+                    return JsonValue::Number(*value as i64).hash(state);
+                }
+                if (0.0..18_446_744_073_709_551_616.0).contains(value) {
+                    return JsonValue::Number64(*value as u64).hash(state);
+                }
+            }
+            _ => {}
+        }
+        core::mem::discriminant(self).hash(state);
+        match self {
+            JsonValue::Null => {}
+            JsonValue::Bool(value) => value.hash(state),
+            JsonValue::Number(value) => value.hash(state),
+            JsonValue::Number64(value) => value.hash(state),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(value) => value.hash(state),
+            JsonValue::Float(value) => {
+                // 0.0 and -0.0 are equal and hash alike, as do all NaNs.
+                let canonical = if *value == 0.0 {
+                    0.0
+                } else if value.is_nan() {
+                    f64::NAN
+                } else {
+                    *value
+                };
+                canonical.to_bits().hash(state);
+            }
+            JsonValue::String(value) => value.hash(state),
+            JsonValue::Array(array) => array.hash(state),
+            JsonValue::Object(map) => map.hash(state),
+        }
+    }
+}
+
+// Members are hashed in key order so that equal objects hash alike,
+// whatever order their map iterates in.
+impl Hash for JsonObject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut members: Vec<(&String, &JsonValue)> = self.iter().collect();
+        members.sort_by(|a, b| a.0.cmp(b.0));
+        state.write_usize(members.len());
+        for (key, value) in members {
+            key.hash(state);
+            value.hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+fn hash_of(value: &JsonValue) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_hash_set_deduplicates() {
+    let mut set = std::collections::HashSet::new();
+    assert!(set.insert(JsonValue::String("x".into())));
+    assert!(!set.insert(JsonValue::String("x".to_string().into())));
+    assert!(set.insert(JsonValue::String("y".into())));
+    assert!(set.insert(crate::parse_str(r#"{"a": [1, 2.5], "b": null}"#).unwrap()));
+    assert!(!set.insert(crate::parse_str(r#"{"b": null, "a": [1, 2.5]}"#).unwrap()));
+    let nan = JsonValue::Array(vec![JsonValue::Float(f64::NAN)]);
+    assert!(set.insert(nan.clone()));
+    assert!(!set.insert(JsonValue::Array(vec![JsonValue::Float(-f64::NAN)])));
+    assert!(set.contains(&nan));
+    assert_eq!(set.len(), 4);
+}
+
+#[test]
+fn test_equal_values_hash_alike() {
+    let pairs = [
+        (JsonValue::Float(0.0), JsonValue::Float(-0.0)),
+        (JsonValue::Number(5), JsonValue::Number64(5)),
+        (JsonValue::Number(1), JsonValue::Float(1.0)),
+        (JsonValue::Number(0), JsonValue::Float(-0.0)),
+        (JsonValue::Number(i64::MIN), JsonValue::Float(-9_223_372_036_854_775_808.0)),
+        (JsonValue::Number64(1 << 63), JsonValue::Float(9_223_372_036_854_775_808.0)),
+        (JsonValue::Float(f64::NAN), JsonValue::Float(-f64::NAN)),
+        (crate::parse_str(r#"{"a": 1, "b": {"c": 2, "d": 3}}"#).unwrap(), crate::parse_str(r#"{"b": {"d": 3, "c": 2}, "a": 1}"#).unwrap()),
+    ];
+    for (a, b) in pairs {
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b), "value: {}", a);
+    }
+    let config = crate::JsonParserConfig { preserve_order: crate::JsonObjectKind::BTreeMap, ..Default::default() };
+    let input = r#"{"z": 1, "y": 2, "x": 3}"#;
+    let btree = crate::JsonParser::with_config(input, config).parse().unwrap();
+    assert_eq!(hash_of(&btree), hash_of(&crate::parse_str(input).unwrap()));
+}
+
+#[test]
+fn test_different_values_hash_differently() {
+    let values = crate::value::sample_values();
+    for (i, a) in values.iter().enumerate() {
+        for b in &values[i + 1..] {
+            assert_ne!(hash_of(a), hash_of(b), "values: {} {}", a, b);
+        }
+    }
+    assert_ne!(hash_of(&JsonValue::Number(1)), hash_of(&JsonValue::Float(1.5)));
+    assert_ne!(hash_of(&crate::parse_str("[[1], 2]").unwrap()), hash_of(&crate::parse_str("[[1, 2]]").unwrap()));
+}
@@ -0,0 +1,373 @@
+use crate::parser::JsonValue;
+use crate::object;
+use crate::prelude::*;
+use core::slice;
+use alloc::collections::VecDeque;
+use alloc::vec;
+
+/// An item produced when iterating over a `JsonValue`: arrays yield
+/// `Element`s and objects yield `Member`s (key/value pairs).
+#[derive(Debug, PartialEq)]
+pub enum JsonItem<K, V> {
+    Element(V),
+    Member(K, V),
+}
+
+impl<K, V> JsonItem<K, V> {
+    pub fn key(&self) -> Option<&K> {
+        match self {
+            JsonItem::Element(_) => None,
+            JsonItem::Member(key, _) => Some(key),
+        }
+    }
+
+    pub fn value(self) -> V {
+        match self {
+            JsonItem::Element(value) => value,
+            JsonItem::Member(_, value) => value,
+        }
+    }
+}
+
+pub enum IntoIter {
+    Array(vec::IntoIter<JsonValue>),
+    Object(object::IntoIter),
+}
+
+pub enum Iter<'a> {
+    Array(slice::Iter<'a, JsonValue>),
+    Object(object::Iter<'a>),
+}
+
+pub enum IterMut<'a> {
+    Array(slice::IterMut<'a, JsonValue>),
+    Object(object::IterMut<'a>),
+}
+
+impl Iterator for IntoIter {
+    type Item = JsonItem<String, JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Array(iter) => iter.next().map(JsonItem::Element),
+            IntoIter::Object(iter) => iter.next().map(|(k, v)| JsonItem::Member(k, v)),
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = JsonItem<&'a str, &'a JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Array(iter) => iter.next().map(JsonItem::Element),
+            Iter::Object(iter) => iter.next().map(|(k, v)| JsonItem::Member(k.as_str(), v)),
+        }
+    }
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = JsonItem<&'a str, &'a mut JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterMut::Array(iter) => iter.next().map(JsonItem::Element),
+            IterMut::Object(iter) => iter.next().map(|(k, v)| JsonItem::Member(k.as_str(), v)),
+        }
+    }
+}
+
+/// Visits a value and everything nested in it in depth-first pre-order,
+/// created by `JsonValue::dfs_iter`.
+pub struct Dfs<'a> {
+    root: Option<&'a JsonValue>,
+    stack: Vec<Iter<'a>>,
+}
+
+/// Visits a value and everything nested in it one level at a time,
+/// created by `JsonValue::bfs_iter`.
+pub struct Bfs<'a> {
+    queue: VecDeque<&'a JsonValue>,
+}
+
+// Arrays and objects have children to visit, and other values do not.
+pub(crate) fn children(value: &JsonValue) -> Option<Iter<'_>> {
+    match value {
+        JsonValue::Array(array) => Some(Iter::Array(array.iter())),
+        JsonValue::Object(map) => Some(Iter::Object(map.iter())),
+        _ => None,
+    }
+}
+
+impl<'a> Iterator for Dfs<'a> {
+    type Item = &'a JsonValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = match self.root.take() {
+            Some(root) => root,
+            None => loop {
+                match self.stack.last_mut()?.next() {
+                    Some(item) => break item.value(),
+                    None => drop(self.stack.pop()),
+                }
+            },
+        };
+        self.stack.extend(children(value));
+        Some(value)
+    }
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = &'a JsonValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.queue.pop_front()?;
+        self.queue.extend(children(value).into_iter().flatten().map(JsonItem::value));
+        Some(value)
+    }
+}
+
+impl IntoIterator for JsonValue {
+    type Item = JsonItem<String, JsonValue>;
+    type IntoIter = IntoIter;
+
+    /// Panics if the value is neither an array nor an object.
+    fn into_iter(mut self) -> Self::IntoIter {
+        match &mut self {
+            JsonValue::Array(array) => IntoIter::Array(core::mem::take(array).into_iter()),
+            JsonValue::Object(map) => IntoIter::Object(core::mem::take(map).into_iter()),
+            value => panic!("Cannot iterate over JSON value: {}", value),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonValue {
+    type Item = JsonItem<&'a str, &'a JsonValue>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut JsonValue {
+    type Item = JsonItem<&'a str, &'a mut JsonValue>;
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl JsonValue {
+    /// Panics if the value is neither an array nor an object.
+    pub fn iter(&self) -> Iter<'_> {
+        match self {
+            JsonValue::Array(array) => Iter::Array(array.iter()),
+            JsonValue::Object(map) => Iter::Object(map.iter()),
+            value => panic!("Cannot iterate over JSON value: {}", value),
+        }
+    }
+
+    /// Panics if the value is neither an array nor an object.
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        match self {
+            JsonValue::Array(array) => IterMut::Array(array.iter_mut()),
+            JsonValue::Object(map) => IterMut::Object(map.iter_mut()),
+            value => panic!("Cannot iterate over JSON value: {}", value),
+        }
+    }
+
+    /// Iterates over the value itself and every value nested in it, each
+    /// before its children, without recursion.
+    pub fn dfs_iter(&self) -> Dfs<'_> {
+        Dfs { root: Some(self), stack: vec![] }
+    }
+
+    /// Iterates over the value itself and every value nested in it, all
+    /// values at one depth before any at the next.
+    pub fn bfs_iter(&self) -> Bfs<'_> {
+        Bfs { queue: VecDeque::from([self]) }
+    }
+
+    /// The number of values in the tree, the value itself and every
+    /// array or object included.
+    pub fn count_nodes(&self) -> usize {
+        self.dfs_iter().count()
+    }
+
+    /// How deeply containers are nested: 0 for a value that is not an
+    /// array or an object, and one more than the deepest child for one
+    /// that is, so that `[]` and `[1]` are 1 deep and `[[1]]` is 2.
+    pub fn max_depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 0)];
+        while let Some((value, depth)) = stack.pop() {
+            if let Some(items) = children(value) {
+                max = max.max(depth + 1);
+                stack.extend(items.map(|item| (item.value(), depth + 1)));
+            }
+        }
+        max
+    }
+
+    /// The keys of an object, or None for other values.
+    pub fn keys(&self) -> Option<impl Iterator<Item = &str>> {
+        self.as_object().map(|map| map.keys().map(String::as_str))
+    }
+
+    /// The member values of an object, or None for other values.
+    pub fn values(&self) -> Option<impl Iterator<Item = &JsonValue>> {
+        self.as_object().map(object::JsonObject::values)
+    }
+
+    pub fn values_mut(&mut self) -> Option<impl Iterator<Item = &mut JsonValue>> {
+        self.as_object_mut().map(object::JsonObject::values_mut)
+    }
+
+    /// The members of an object as key/value pairs, or None for other
+    /// values.
+    pub fn entries(&self) -> Option<impl Iterator<Item = (&str, &JsonValue)>> {
+        self.as_object().map(|map| map.iter().map(|(key, value)| (key.as_str(), value)))
+    }
+}
+
+#[test]
+fn test_object_keys_values_and_entries() {
+    let mut value = crate::parse_str(r#"{"b": 2, "c": [3], "a": 1}"#).unwrap();
+    let mut keys: Vec<&str> = value.keys().unwrap().collect();
+    keys.sort();
+    assert_eq!(keys, ["a", "b", "c"]);
+    let mut entries: Vec<(&str, &JsonValue)> = value.entries().unwrap().collect();
+    entries.sort_by_key(|(key, _)| *key);
+    assert_eq!(entries[1], ("b", &JsonValue::Number(2)));
+    assert_eq!(value.values().unwrap().filter(|value| value.is_number()).count(), 2);
+    for member in value.values_mut().unwrap() {
+        if let JsonValue::Number(n) = member {
+            *n += 10;
+        }
+    }
+    assert_eq!(value, crate::parse_str(r#"{"a": 11, "b": 12, "c": [3]}"#).unwrap());
+    for value in [JsonValue::Null, crate::parse_str("[1]").unwrap(), JsonValue::from("a")] {
+        assert!(value.keys().is_none());
+        assert!(value.values().is_none());
+        assert!(value.entries().is_none());
+    }
+}
+
+#[test]
+fn test_dfs_and_bfs_order() {
+    let config = crate::JsonParserConfig { preserve_order: crate::JsonObjectKind::BTreeMap, ..Default::default() };
+    let value = crate::JsonParser::with_config(r#"{"a": [1, [2]], "b": {"c": 3}, "d": []}"#, config).parse().unwrap();
+    let nodes = ["[1, [2]]", "1", "[2]", "2", r#"{"c": 3}"#, "3", "[]"].map(|input| crate::parse_str(input).unwrap());
+    let dfs: Vec<&JsonValue> = value.dfs_iter().collect();
+    assert_eq!(dfs, [&value, &nodes[0], &nodes[1], &nodes[2], &nodes[3], &nodes[4], &nodes[5], &nodes[6]]);
+    let bfs: Vec<&JsonValue> = value.bfs_iter().collect();
+    assert_eq!(bfs, [&value, &nodes[0], &nodes[4], &nodes[6], &nodes[1], &nodes[2], &nodes[5], &nodes[3]]);
+
+    let value = crate::parse_str(r#"[{"x": "s", "y": [null, true]}, 2.5, [[[]]], {}]"#).unwrap();
+    let leaves = |iter: &mut dyn Iterator<Item = &JsonValue>| {
+        let mut leaves: Vec<String> =
+            iter.filter(|value| !value.is_array() && !value.is_object()).map(JsonValue::to_string).collect();
+        leaves.sort();
+        leaves
+    };
+    assert_eq!(leaves(&mut value.dfs_iter()), ["\"s\"", "2.5", "null", "true"]);
+    assert_eq!(leaves(&mut value.dfs_iter()), leaves(&mut value.bfs_iter()));
+    assert_eq!(value.dfs_iter().count(), value.bfs_iter().count());
+    assert_eq!(JsonValue::Null.dfs_iter().collect::<Vec<_>>(), [&JsonValue::Null]);
+    assert_eq!(JsonValue::Null.bfs_iter().collect::<Vec<_>>(), [&JsonValue::Null]);
+}
+
+#[test]
+fn test_dfs_and_bfs_deep_nesting() {
+    let input = format!("{}1{}", "[".repeat(100), "]".repeat(100));
+    let value = crate::parse_str(&input).unwrap();
+    assert_eq!(value.dfs_iter().count(), 101);
+    assert_eq!(value.bfs_iter().count(), 101);
+    assert_eq!(value.dfs_iter().last(), Some(&JsonValue::Number(1)));
+    assert_eq!(value.bfs_iter().last(), Some(&JsonValue::Number(1)));
+}
+
+#[test]
+fn test_count_nodes_and_max_depth() {
+    for value in crate::value::sample_values().into_iter().filter(|value| value.len().is_none()) {
+        assert_eq!(value.count_nodes(), 1, "value: {}", value);
+        assert_eq!(value.max_depth(), 0, "value: {}", value);
+    }
+    let cases = [
+        ("[]", 1, 1),
+        ("{}", 1, 1),
+        ("[1, 2, 3]", 4, 1),
+        (r#"{"a": 1, "b": "x", "c": null}"#, 4, 1),
+        (r#"[[1], {"a": [2, {}]}, 3]"#, 8, 4),
+        (r#"{"a": {"b": {"c": [true]}}, "d": []}"#, 6, 4),
+    ];
+    for (input, nodes, depth) in cases {
+        let value = crate::parse_str(input).unwrap();
+        assert_eq!(value.count_nodes(), nodes, "input: {}", input);
+        assert_eq!(value.max_depth(), depth, "input: {}", input);
+    }
+}
+
+#[test]
+fn test_count_nodes_and_max_depth_deep_nesting() {
+    let depth = 10_000;
+    let mut value = JsonValue::Number(1);
+    for _ in 0..depth {
+        value = JsonValue::Array(vec![value]);
+    }
+    assert_eq!(value.count_nodes(), depth + 1);
+    assert_eq!(value.max_depth(), depth);
+}
+
+#[test]
+fn test_iterate_array() {
+    let value = crate::parse_str("[1, 2, 3]").unwrap();
+    let mut sum = 0;
+    for item in &value {
+        sum += item.value().as_i64().unwrap();
+    }
+    assert_eq!(sum, 6);
+
+    let elements: Vec<JsonValue> = value.clone().into_iter().map(JsonItem::value).collect();
+    assert_eq!(JsonValue::Array(elements), value);
+}
+
+#[test]
+fn test_iterate_object() {
+    let value = crate::parse_str(r#"{"a": 1, "b": 2}"#).unwrap();
+    let mut keys: Vec<&str> = value.iter().map(|item| *item.key().unwrap()).collect();
+    keys.sort();
+    assert_eq!(keys, ["a", "b"]);
+
+    let map: crate::JsonObject = value
+        .clone()
+        .into_iter()
+        .map(|item| match item {
+            JsonItem::Member(key, value) => (key, value),
+            JsonItem::Element(_) => unreachable!(),
+        })
+        .collect();
+    assert_eq!(JsonValue::Object(map), value);
+}
+
+#[test]
+fn test_iter_mut() {
+    let mut value = crate::parse_str(r#"{"a": 1, "b": [2, 3]}"#).unwrap();
+    for item in &mut value {
+        let value = item.value();
+        if value.is_array() {
+            for element in value.iter_mut() {
+                *element.value() = JsonValue::Null;
+            }
+        }
+    }
+    assert_eq!(value, crate::parse_str(r#"{"a": 1, "b": [null, null]}"#).unwrap());
+}
+
+#[test]
+#[should_panic]
+fn test_iterate_scalar_panics() {
+    for _ in JsonValue::Number(1) {}
+}
@@ -0,0 +1,215 @@
+use crate::parser::JsonValue;
+use crate::prelude::*;
+use crate::serializer::write_escaped_string;
+use core::fmt::Write;
+
+// Returns the shortest digits that round-trip to `value`, which must be
+// positive, along with the exponent of the first digit. When two such
+// digit strings are equally close to the exact value, `{:e}` picks the
+// larger one while ECMAScript picks the one that ends in an even digit.
+fn shortest_digits(value: f64) -> (String, i32) {
+    let formatted = format!("{:e}", value);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let exponent: i32 = exponent.parse().unwrap();
+    let length = digits.len();
+    if digits.as_bytes()[length - 1] % 2 == 0 {
+        return (digits, exponent);
+    }
+    // Precision formatting is exact, and no f64 needs more than 767
+    // significant digits.
+    let exact = format!("{:.800e}", value);
+    let (exact_mantissa, exact_exponent) = exact.split_once('e').unwrap();
+    let exact_digits = exact_mantissa.replace('.', "");
+    let (lower, rest) = exact_digits.split_at(length);
+    let tie = exact_exponent == exponent.to_string() && rest.starts_with('5') && rest[1..].bytes().all(|b| b == b'0');
+    if tie && lower.as_bytes()[length - 1] % 2 == 0 {
+        let candidate = format!("{}.{}e{}", &lower[..1], &lower[1..], exponent);
+        if candidate.parse::<f64>() == Ok(value) {
+            return (lower.to_string(), exponent);
+        }
+    }
+    (digits, exponent)
+}
+
+// Formats a number the way ECMAScript's Number.prototype.toString does,
+// which RFC 8785 requires.
+fn write_number(builder: &mut String, value: f64) {
+    if !value.is_finite() {
+        builder.push_str("null");
+        return;
+    }
+    if value == 0.0 {
+        builder.push('0');
+        return;
+    }
+    let (digits, exponent) = shortest_digits(value.abs());
+    // The value is 0.digits times ten to the power of `point`.
+    let point = exponent + 1;
+    let length = digits.len() as i32;
+    if value < 0.0 {
+        builder.push('-');
+    }
+    if length <= point && point <= 21 {
+        builder.push_str(&digits);
+        builder.extend(core::iter::repeat_n('0', (point - length) as usize));
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        write!(builder, "{}.{}", integer, fraction).unwrap();
+    } else if -6 < point && point <= 0 {
+        builder.push_str("0.");
+        builder.extend(core::iter::repeat_n('0', -point as usize));
+        builder.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        builder.push_str(first);
+        if !rest.is_empty() {
+            write!(builder, ".{}", rest).unwrap();
+        }
+        write!(builder, "e{}{}", if point > 0 { "+" } else { "-" }, (point - 1).abs()).unwrap();
+    }
+}
+
+// Writes an integer as JCS does, or with all of its digits if `exact` is
+// set and an f64 would round it.
+fn write_integer(builder: &mut String, value: i128, exact: bool) {
+    if exact && value as f64 as i128 != value {
+        write!(builder, "{}", value).unwrap();
+    } else {
+        write_number(builder, value as f64);
+    }
+}
+
+// With `exact` set, values that JCS would round or write as null keep a
+// form of their own, which is not JSON: integers keep all of their digits,
+// NaN and the infinities are written as the JSON5 literals, and a `BigNum`
+// is written as its exact digits and exponent with an `m` suffix, since it
+// equals no other kind of number.
+fn write_jcs(builder: &mut String, value: &JsonValue, exact: bool) {
+    match value {
+        JsonValue::Null => builder.push_str("null"),
+        JsonValue::Bool(value) => write!(builder, "{}", value).unwrap(),
+        // Every number is an IEEE 754 double in JCS, so integers beyond
+        // 2^53 are rounded like any other.
+        JsonValue::Number(value) => write_integer(builder, *value as i128, exact),
+        JsonValue::Number64(value) => write_integer(builder, *value as i128, exact),
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(value) if exact => {
+            let (digits, scale) = value.normalized().into_bigint_and_exponent();
+            write!(builder, "{}e{}m", digits, -scale).unwrap();
+        }
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(value) => write_number(builder, bigdecimal::ToPrimitive::to_f64(value).unwrap()),
+        JsonValue::Float(value) if exact && value.is_nan() => builder.push_str("NaN"),
+        JsonValue::Float(value) if exact && value.is_infinite() => builder.push_str(if *value > 0.0 { "Infinity" } else { "-Infinity" }),
+        JsonValue::Float(value) => write_number(builder, *value),
+        JsonValue::String(value) => write_escaped_string(builder, value).unwrap(),
+        JsonValue::Array(array) => {
+            builder.push('[');
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    builder.push(',');
+                }
+                write_jcs(builder, value, exact);
+            }
+            builder.push(']');
+        }
+        JsonValue::Object(map) => {
+            let mut members: Vec<(&String, &JsonValue)> = map.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            builder.push('{');
+            for (i, (key, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    builder.push(',');
+                }
+                write_escaped_string(builder, key).unwrap();
+                builder.push(':');
+                write_jcs(builder, value, exact);
+            }
+            builder.push('}');
+        }
+    }
+}
+
+impl JsonValue {
+    /// Serializes the value in the RFC 8785 canonical form: no whitespace,
+    /// object members sorted by the UTF-16 code units of their keys and
+    /// numbers formatted like ECMAScript does. NaN and the infinities have
+    /// no canonical form and are written as null.
+    pub fn to_jcs_bytes(&self) -> Vec<u8> {
+        let mut builder = String::new();
+        write_jcs(&mut builder, self, false);
+        builder.into_bytes()
+    }
+}
+
+// Like `to_jcs_bytes`, but in the exact form of `write_jcs`, so that values
+// that are not equal never share a form.
+pub(crate) fn to_exact_jcs_bytes(value: &JsonValue) -> Vec<u8> {
+    let mut builder = String::new();
+    write_jcs(&mut builder, value, true);
+    builder.into_bytes()
+}
+
+#[test]
+fn test_jcs_number_samples() {
+    // The IEEE 754 samples from RFC 8785 appendix B.
+    let cases = [
+        (0x0000000000000000, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ];
+    for (bits, expected) in cases {
+        let value = JsonValue::Float(f64::from_bits(bits));
+        assert_eq!(String::from_utf8(value.to_jcs_bytes()).unwrap(), expected, "bits: {:016x}", bits);
+    }
+    assert_eq!(JsonValue::Number(9007199254740993).to_jcs_bytes(), b"9007199254740992");
+    assert_eq!(JsonValue::Float(f64::NAN).to_jcs_bytes(), b"null");
+}
+
+#[test]
+fn test_jcs_rfc_examples() {
+    // Section 3.2.2.
+    let input = r#"{
+        "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+        "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+        "literals": [null, true, false]
+    }"#;
+    let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+    assert_eq!(crate::parse_str(input).unwrap().to_jcs_bytes(), expected.as_bytes());
+    // Section 3.2.3, where keys sort by UTF-16 code units rather than by
+    // code points.
+    let input = r#"{
+        "\u20ac": "Euro Sign",
+        "\r": "Carriage Return",
+        "\ufb33": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "\ud83d\ude00": "Emoji: Grinning Face",
+        "\u0080": "Control",
+        "\u00f6": "Latin Small Letter O With Diaeresis"
+    }"#;
+    let expected = "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\"ö\":\"Latin Small Letter O With Diaeresis\",\
+        \"€\":\"Euro Sign\",\"😀\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}";
+    assert_eq!(String::from_utf8(crate::parse_str(input).unwrap().to_jcs_bytes()).unwrap(), expected);
+}
@@ -1,2 +1,135 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[macro_use]
+mod macros;
+mod prelude;
 pub mod parser;
 pub mod deserializer;
+pub mod serializer;
+pub mod config;
+mod value;
+mod convert;
+mod merge;
+#[cfg(feature = "std")]
+mod flat;
+mod hash;
+mod jcs;
+mod fingerprint;
+mod ord;
+#[cfg(feature = "bignum")]
+mod bignum;
+#[cfg(feature = "std")]
+mod reader;
+mod regex;
+#[cfg(feature = "tokio")]
+mod async_reader;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod iter;
+#[cfg(feature = "std")]
+pub mod lines;
+pub mod pointer;
+pub mod tokenizer;
+pub mod events;
+pub mod feed;
+pub mod object;
+pub mod array;
+pub mod path;
+pub mod patch;
+pub mod schema;
+pub mod builder;
+pub mod borrowed;
+pub mod compat;
+#[cfg(feature = "bumpalo")]
+pub mod arena;
+pub mod visitor;
+// Public only so that the benchmarks can compare both implementations.
+#[doc(hidden)]
+pub mod whitespace;
+
+// Used by `json!`, whose paths resolve in the calling crate, which may not
+// declare `extern crate alloc`.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{string::String, vec};
+}
+
+#[cfg(feature = "bumpalo")]
+pub use arena::{ArenaJsonValue, JsonParserArena};
+pub use array::JsonArray;
+pub use borrowed::{BorrowedJsonValue, JsonBorrowedParser};
+pub use builder::{JsonArrayBuilder, JsonObjectBuilder};
+pub use config::{DuplicateKeyPolicy, JsonParserConfig, ParseMode};
+pub use events::{JsonEventHandler, JsonEventParser};
+pub use feed::FeedableParser;
+pub use fingerprint::Fingerprint;
+#[cfg(feature = "std")]
+pub use flat::UnflattenError;
+#[cfg(feature = "std")]
+pub use lines::JsonLines;
+pub use object::{Entry, JsonObject, JsonObjectKind};
+pub use path::{JsonPathError, JsonPathExpr};
+pub use patch::{diff, PatchError};
+pub use parser::{DuplicateKeys, JsonParser, JsonValue, ParserError};
+pub use pointer::PointerError;
+pub use schema::ValidationError;
+#[cfg(feature = "std")]
+pub use serializer::JsonSerializer;
+pub use tokenizer::{JsonTokenizer, Token, TokenError};
+pub use value::{JsonTypeError, KeyFilterMode};
+pub use visitor::Visitor;
+
+pub fn parse_str(input: &str) -> Result<JsonValue, ParserError> {
+    JsonParser::new(input).parse()
+}
+
+/// Checks that `input` is UTF-8 and parses it in place. An error gives the
+/// offset of the first byte that is not part of a valid character.
+pub fn parse_from_bytes(input: &[u8]) -> Result<JsonValue, ParserError> {
+    match core::str::from_utf8(input) {
+        Ok(input) => parse_str(input),
+        Err(err) => Err(ParserError::InvalidUtf8 { byte_offset: err.valid_up_to() }),
+    }
+}
+
+/// Same as `parse_from_bytes`.
+pub fn parse_bytes(input: &[u8]) -> Result<JsonValue, ParserError> {
+    parse_from_bytes(input)
+}
+
+#[test]
+fn test_parse_str() {
+    assert_eq!(parse_str(" [null] "), Ok(JsonValue::Array(vec![JsonValue::Null])));
+    assert!(parse_str("nul").is_err());
+}
+
+#[test]
+fn test_parse_bytes() {
+    assert_eq!(parse_bytes("\"é\"".as_bytes()), Ok(JsonValue::String("é".into())));
+    assert_eq!(parse_bytes(b"\"\xff\""), Err(ParserError::InvalidUtf8 { byte_offset: 1 }));
+}
+
+#[test]
+fn test_parse_from_bytes() {
+    assert_eq!(parse_from_bytes(r#"{"name": "tinyserde 𝄞"}"#.as_bytes()), parse_str(r#"{"name": "tinyserde 𝄞"}"#));
+    // A byte order mark is valid UTF-8, so whether it is accepted is up to
+    // the parser.
+    assert_eq!(parse_from_bytes(b"\xef\xbb\xbf[1]"), parse_str("\u{feff}[1]"));
+    let cases: [(&[u8], usize); 6] = [
+        (b"\"\xff\"", 1),
+        (b"[\"\xc3\xa9\", \"\xc3(\"]", 8),
+        (b"\"\xe2\x82(\"", 1),
+        (b"\"\xed\xa0\x80\"", 1),
+        (b"[1, \"\xf0\x9d\x84", 5),
+        (b"\"\xc3", 1),
+    ];
+    for (input, byte_offset) in cases {
+        assert_eq!(parse_from_bytes(input), Err(ParserError::InvalidUtf8 { byte_offset }), "input: {:?}", input);
+    }
+    // Offsets are relative to the slice that is parsed.
+    let buffer = b"HEADER{\"a\": [true]}\0\xff";
+    assert_eq!(parse_from_bytes(&buffer[6..19]), parse_str(r#"{"a": [true]}"#));
+    assert_eq!(parse_from_bytes(&buffer[6..]), Err(ParserError::InvalidUtf8 { byte_offset: 14 }));
+}
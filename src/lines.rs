@@ -0,0 +1,93 @@
+use crate::parser::{JsonParser, JsonValue, ParserError};
+use std::io::{BufRead, Cursor};
+
+/// Iterates over newline-delimited JSON (NDJSON), parsing each non-blank
+/// line as a separate document. A malformed line yields an error and
+/// iteration continues with the next line.
+pub struct JsonLines<R: BufRead> {
+    reader: R,
+    buffer: Vec<u8>,
+    // The number of bytes read so far.
+    offset: usize,
+}
+
+impl<R: BufRead> JsonLines<R> {
+    pub fn new(reader: R) -> Self {
+        JsonLines {
+            reader,
+            buffer: Vec::new(),
+            offset: 0,
+        }
+    }
+}
+
+impl<'a> JsonLines<Cursor<&'a str>> {
+    // Not FromStr, since the reader borrows the input.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'a str) -> Self {
+        JsonLines::new(Cursor::new(input))
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLines<R> {
+    type Item = Result<JsonValue, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            let line_start = self.offset;
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => return None,
+                Ok(length) => self.offset += length,
+                Err(err) => return Some(Err(ParserError::Io(err))),
+            }
+            let line = match std::str::from_utf8(&self.buffer) {
+                Ok(line) => line.trim(),
+                Err(err) => return Some(Err(ParserError::InvalidUtf8 { byte_offset: line_start + err.valid_up_to() })),
+            };
+            if line.is_empty() {
+                continue;
+            }
+            return Some(JsonParser::new(line).parse());
+        }
+    }
+}
+
+#[test]
+fn test_json_lines_all_types() {
+    let input = "null\ntrue\n-3\n2.5\n\"text\"\n[1, [2]]\n{\"a\": {}}\n";
+    let values: Vec<_> = JsonLines::from_str(input).collect();
+    let expected: Vec<_> = input.lines().map(crate::parse_str).collect();
+    assert_eq!(values.len(), 7);
+    assert_eq!(values, expected);
+}
+
+#[test]
+fn test_json_lines_skips_blank_lines() {
+    let input = "\n{\"a\": 1}\r\n\n   \n{\"b\": 2}\n\n";
+    let values: Vec<_> = JsonLines::from_str(input).collect();
+    assert_eq!(values, vec![crate::parse_str("{\"a\": 1}"), crate::parse_str("{\"b\": 2}")]);
+}
+
+#[test]
+fn test_json_lines_malformed_line() {
+    let input = "1\n[2,\n3\n{\"partial\":";
+    let values: Vec<_> = JsonLines::from_str(input).collect();
+    assert_eq!(values.len(), 4);
+    assert_eq!(values[0], Ok(JsonValue::Number(1)));
+    assert_eq!(values[1], Err(ParserError::UnexpectedEof { expected: "value" }));
+    assert_eq!(values[2], Ok(JsonValue::Number(3)));
+    assert_eq!(values[3], Err(ParserError::UnexpectedEof { expected: "value" }));
+}
+
+#[test]
+fn test_json_lines_empty_input() {
+    assert_eq!(JsonLines::from_str("").count(), 0);
+}
+
+#[test]
+fn test_json_lines_invalid_utf8() {
+    let input: &[u8] = b"1\n\"\xff\"\n2\n";
+    let values: Vec<_> = JsonLines::new(input).collect();
+    assert_eq!(values, vec![Ok(JsonValue::Number(1)), Err(ParserError::InvalidUtf8 { byte_offset: 3 }), Ok(JsonValue::Number(2))]);
+}
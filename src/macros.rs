@@ -0,0 +1,106 @@
+/// Builds a `JsonValue` using JSON syntax. Object keys must be string
+/// literals or parenthesized expressions, and any Rust expression that
+/// converts into a `JsonValue` can be used as a value, for example
+/// `json!({"count": count, "tags": ["a", null]})`.
+#[macro_export]
+macro_rules! json {
+    ($($json:tt)+) => {
+        $crate::json_internal!(@value $($json)+)
+    };
+}
+
+// Arrays and objects are split on top-level commas one token at a time,
+// so that values can be arbitrary expressions.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    (@value null) => {
+        $crate::JsonValue::Null
+    };
+    (@value [$($elements:tt)*]) => {
+        $crate::JsonValue::Array($crate::json_internal!(@array [] [] $($elements)*))
+    };
+    (@value {$($members:tt)*}) => {{
+        #[allow(unused_mut)]
+        let mut object = $crate::JsonObject::new();
+        $crate::json_internal!(@object object $($members)*);
+        $crate::JsonValue::Object(object)
+    }};
+    (@value $other:expr) => {
+        $crate::JsonValue::from($other)
+    };
+
+    (@array [$($done:expr,)*] []) => {
+        $crate::__private::vec![$($done,)*]
+    };
+    (@array [$($done:expr,)*] [$($element:tt)+]) => {
+        $crate::__private::vec![$($done,)* $crate::json_internal!(@value $($element)+)]
+    };
+    (@array [$($done:expr,)*] [$($element:tt)+] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($done,)* $crate::json_internal!(@value $($element)+),] [] $($rest)*)
+    };
+    (@array [$($done:expr,)*] [$($element:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($done,)*] [$($element)* $next] $($rest)*)
+    };
+
+    (@object $object:ident) => {};
+    (@object $object:ident $key:tt : $($rest:tt)*) => {
+        $crate::json_internal!(@member $object $key [] $($rest)*)
+    };
+    (@member $object:ident $key:tt [$($value:tt)+]) => {
+        $object.insert($crate::__private::String::from($key), $crate::json_internal!(@value $($value)+));
+    };
+    (@member $object:ident $key:tt [$($value:tt)+] , $($rest:tt)*) => {
+        $object.insert($crate::__private::String::from($key), $crate::json_internal!(@value $($value)+));
+        $crate::json_internal!(@object $object $($rest)*);
+    };
+    (@member $object:ident $key:tt [$($value:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::json_internal!(@member $object $key [$($value)* $next] $($rest)*)
+    };
+}
+
+#[cfg(test)]
+use crate::parser::JsonValue;
+
+#[test]
+fn test_json_macro_object() {
+    let expected = JsonValue::Object(crate::JsonObject::from([("a".to_string(), JsonValue::Number(1))]));
+    assert_eq!(json!({"a": 1}), expected);
+}
+
+#[test]
+fn test_json_macro_all_types() {
+    assert_eq!(json!(null), JsonValue::Null);
+    assert_eq!(json!(true), JsonValue::Bool(true));
+    assert_eq!(json!(-12), JsonValue::Number(-12));
+    assert_eq!(json!(2.5), JsonValue::Float(2.5));
+    assert_eq!(json!("text"), JsonValue::String("text".into()));
+    assert_eq!(json!([]), JsonValue::Array(vec![]));
+    assert_eq!(json!({}), JsonValue::Object(crate::JsonObject::new()));
+    let value = json!({
+        "null": null,
+        "bool": false,
+        "number": 1,
+        "string": "s",
+        "array": [1, [2, 3], {"four": 4},],
+        "object": {"nested": {"deep": []}},
+    });
+    let expected = r#"{"null": null, "bool": false, "number": 1, "string": "s", "array": [1, [2, 3], {"four": 4}], "object": {"nested": {"deep": []}}}"#;
+    assert_eq!(value, crate::parse_str(expected).unwrap());
+}
+
+#[test]
+fn test_json_macro_interpolation() {
+    let count = 41;
+    let name = String::from("tinyserde");
+    let key = "dynamic";
+    let tags = vec![JsonValue::from("a"), JsonValue::from("b")];
+    let value = json!({
+        "count": count + 1,
+        "name": name,
+        (key): [count, -count, Some(true), None::<bool>],
+        "tags": tags,
+    });
+    let expected = r#"{"count": 42, "name": "tinyserde", "dynamic": [41, -41, true, null], "tags": ["a", "b"]}"#;
+    assert_eq!(value, crate::parse_str(expected).unwrap());
+}
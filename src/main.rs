@@ -21,15 +21,12 @@ OPTIONS:
     -p, --path <PATH>      Directory containing json files to combine.
 "#;
 
-fn combine_json(path: &str) -> () {
+fn combine_json(path: &str) {
     let files = fs::read_dir(path).expect("Could not find directory.");
     let mut parsed_objects: Vec<JsonValue> = vec![];
     for file in files {
         let contents = fs::read_to_string(file.unwrap().path()).expect("Could not read file.");
-        let mut parser = JsonParser {
-            input: contents, 
-            cursor: 0,
-        };
+        let mut parser = JsonParser::new(contents);
         let value: Result<JsonValue, ParserError> = parser.parse();
         match value {
             Ok(value) => parsed_objects.push(value),
@@ -0,0 +1,162 @@
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+
+impl JsonValue {
+    /// Returns a copy of this object with the members of `other` added,
+    /// replacing members with the same key. Returns a copy of `self`
+    /// unchanged if either value is not an object.
+    pub fn merge(&self, other: &JsonValue) -> JsonValue {
+        let mut merged = self.clone();
+        if let (JsonValue::Object(map), JsonValue::Object(other)) = (&mut merged, other) {
+            map.extend(other.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+        merged
+    }
+
+    /// Like `merge`, but members that are objects on both sides are merged
+    /// recursively. Any other value in `other`, arrays included, replaces
+    /// the value in `self`.
+    pub fn deep_merge(&self, other: &JsonValue) -> JsonValue {
+        let mut merged = self.clone();
+        if let (JsonValue::Object(_), JsonValue::Object(_)) = (&merged, other) {
+            merged.deep_merge_in_place(other);
+        }
+        merged
+    }
+
+    /// Applies an RFC 7396 JSON Merge Patch to a copy of this value: `null`
+    /// members of the patch remove keys, objects are merged recursively and
+    /// any other patch value, arrays included, replaces the target.
+    pub fn apply_merge_patch(&self, patch: &JsonValue) -> JsonValue {
+        let mut target = self.clone();
+        target.merge_patch_in_place(patch);
+        target
+    }
+
+    fn merge_patch_in_place(&mut self, patch: &JsonValue) {
+        let patch = match patch {
+            JsonValue::Object(patch) => patch,
+            patch => {
+                *self = patch.clone();
+                return;
+            }
+        };
+        if !self.is_object() {
+            *self = JsonValue::Object(JsonObject::new());
+        }
+        let map = self.as_object_mut().unwrap();
+        for (key, value) in patch {
+            if value.is_null() {
+                map.remove(key);
+                continue;
+            }
+            match map.get_mut(key) {
+                Some(existing) => existing.merge_patch_in_place(value),
+                None => {
+                    let mut member = JsonValue::Null;
+                    member.merge_patch_in_place(value);
+                    map.insert(key.clone(), member);
+                }
+            }
+        }
+    }
+
+    fn deep_merge_in_place(&mut self, other: &JsonValue) {
+        match (self, other) {
+            (JsonValue::Object(map), JsonValue::Object(other)) => {
+                for (key, value) in other {
+                    match map.get_mut(key) {
+                        Some(existing) => existing.deep_merge_in_place(value),
+                        None => {
+                            map.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (this, other) => *this = other.clone(),
+        }
+    }
+}
+
+#[test]
+fn test_merge_overrides_top_level_keys() {
+    let base = crate::parse_str(r#"{"a": 1, "b": {"x": 1, "y": 2}}"#).unwrap();
+    let other = crate::parse_str(r#"{"b": {"x": 3}, "c": 4}"#).unwrap();
+    assert_eq!(base.merge(&other), crate::parse_str(r#"{"a": 1, "b": {"x": 3}, "c": 4}"#).unwrap());
+}
+
+#[test]
+fn test_deep_merge_merges_nested_objects() {
+    let base = crate::parse_str(r#"{"a": 1, "b": {"x": 1, "y": {"z": 2}}}"#).unwrap();
+    let other = crate::parse_str(r#"{"b": {"x": 3, "y": {"w": 4}}, "c": 5}"#).unwrap();
+    let expected = crate::parse_str(r#"{"a": 1, "b": {"x": 3, "y": {"z": 2, "w": 4}}, "c": 5}"#).unwrap();
+    assert_eq!(base.deep_merge(&other), expected);
+}
+
+#[test]
+fn test_deep_merge_type_conflicts() {
+    let base = crate::parse_str(r#"{"a": {"nested": true}, "b": 1}"#).unwrap();
+    let other = crate::parse_str(r#"{"a": 2, "b": {"nested": false}}"#).unwrap();
+    let expected = crate::parse_str(r#"{"a": 2, "b": {"nested": false}}"#).unwrap();
+    assert_eq!(base.deep_merge(&other), expected);
+}
+
+#[test]
+fn test_deep_merge_replaces_arrays() {
+    let base = crate::parse_str(r#"{"list": [1, 2, 3]}"#).unwrap();
+    let other = crate::parse_str(r#"{"list": [4]}"#).unwrap();
+    assert_eq!(base.deep_merge(&other), other);
+}
+
+#[test]
+fn test_merge_non_objects_returns_original() {
+    let object = crate::parse_str(r#"{"a": 1}"#).unwrap();
+    for value in crate::value::sample_values().into_iter().filter(|value| !value.is_object()) {
+        assert_eq!(value.merge(&object), value);
+        assert_eq!(value.deep_merge(&object), value);
+        assert_eq!(object.merge(&value), object);
+        assert_eq!(object.deep_merge(&value), object);
+    }
+}
+
+#[test]
+fn test_apply_merge_patch_rfc7396_examples() {
+    let cases = [
+        (r#"{"a": "b"}"#, r#"{"a": "c"}"#, r#"{"a": "c"}"#),
+        (r#"{"a": "b"}"#, r#"{"b": "c"}"#, r#"{"a": "b", "b": "c"}"#),
+        (r#"{"a": "b"}"#, r#"{"a": null}"#, r#"{}"#),
+        (r#"{"a": "b", "b": "c"}"#, r#"{"a": null}"#, r#"{"b": "c"}"#),
+        (r#"{"a": ["b"]}"#, r#"{"a": "c"}"#, r#"{"a": "c"}"#),
+        (r#"{"a": "c"}"#, r#"{"a": ["b"]}"#, r#"{"a": ["b"]}"#),
+        (r#"{"a": {"b": "c"}}"#, r#"{"a": {"b": "d", "c": null}}"#, r#"{"a": {"b": "d"}}"#),
+        (r#"{"a": [{"b": "c"}]}"#, r#"{"a": [1]}"#, r#"{"a": [1]}"#),
+        (r#"["a", "b"]"#, r#"["c", "d"]"#, r#"["c", "d"]"#),
+        (r#"{"a": "b"}"#, r#"["c"]"#, r#"["c"]"#),
+        (r#"{"a": "foo"}"#, "null", "null"),
+        (r#"{"a": "foo"}"#, r#""bar""#, r#""bar""#),
+        (r#"{"e": null}"#, r#"{"a": 1}"#, r#"{"e": null, "a": 1}"#),
+        (r#"[1, 2]"#, r#"{"a": "b", "c": null}"#, r#"{"a": "b"}"#),
+        (r#"{}"#, r#"{"a": {"bb": {"ccc": null}}}"#, r#"{"a": {"bb": {}}}"#),
+    ];
+    for (target, patch, expected) in cases {
+        let target = crate::parse_str(target).unwrap();
+        let patched = target.apply_merge_patch(&crate::parse_str(patch).unwrap());
+        assert_eq!(patched, crate::parse_str(expected).unwrap(), "patch: {:?}", patch);
+    }
+}
+
+#[test]
+fn test_apply_merge_patch_deep_and_non_destructive() {
+    let target = crate::parse_str(r#"{"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}, "tags": ["example", "sample"], "content": "x"}"#).unwrap();
+    let patch = crate::parse_str(r#"{"title": "Hello!", "phoneNumber": "+01-123-456-7890", "author": {"familyName": null}, "tags": ["example"]}"#).unwrap();
+    let expected = crate::parse_str(r#"{"title": "Hello!", "author": {"givenName": "John"}, "tags": ["example"], "content": "x", "phoneNumber": "+01-123-456-7890"}"#).unwrap();
+    let original = target.clone();
+    assert_eq!(target.apply_merge_patch(&patch), expected);
+    assert_eq!(target, original);
+}
+
+#[test]
+fn test_apply_merge_patch_scalars() {
+    assert_eq!(JsonValue::Number(1).apply_merge_patch(&JsonValue::Bool(true)), JsonValue::Bool(true));
+    assert_eq!(JsonValue::Null.apply_merge_patch(&JsonValue::Number(2)), JsonValue::Number(2));
+}
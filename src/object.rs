@@ -0,0 +1,472 @@
+use crate::parser::JsonValue;
+use crate::prelude::*;
+use alloc::collections::{btree_map, BTreeMap};
+use core::fmt;
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{hash_map, HashMap};
+// Without std there is no HashMap, so objects of the HashMap kind are
+// backed by a BTreeMap as well.
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::{btree_map as hash_map, BTreeMap as HashMap};
+#[cfg(feature = "indexmap")]
+use indexmap::{map as index_map, IndexMap};
+
+/// The map that backs the members of a `JsonObject`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonObjectKind {
+    /// Members iterate in an unspecified order that can differ between runs.
+    #[cfg_attr(not(feature = "indexmap"), default)]
+    HashMap,
+    /// Members iterate in sorted key order.
+    BTreeMap,
+    /// Members iterate in the order they were inserted, which for parsed
+    /// objects is the order of the input. The default with the `indexmap`
+    /// feature.
+    #[cfg(feature = "indexmap")]
+    #[cfg_attr(feature = "indexmap", default)]
+    IndexMap,
+}
+
+#[derive(Clone)]
+enum Map {
+    Hash(HashMap<String, JsonValue>),
+    BTree(BTreeMap<String, JsonValue>),
+    #[cfg(feature = "indexmap")]
+    Index(IndexMap<String, JsonValue>),
+}
+
+/// The members of a `JsonValue::Object`. Two objects are equal when they
+/// have the same members, whatever their kind.
+///
+/// Every key is an owned `String`, so objects never share the allocations
+/// of their keys, however often a key repeats. `JsonBorrowedParser` reads
+/// keys without escape sequences as slices of the input instead.
+#[derive(Clone)]
+pub struct JsonObject(Map);
+
+impl JsonObject {
+    pub fn new() -> Self {
+        Self::with_kind(JsonObjectKind::default())
+    }
+
+    pub fn with_kind(kind: JsonObjectKind) -> Self {
+        match kind {
+            JsonObjectKind::HashMap => JsonObject(Map::Hash(HashMap::new())),
+            JsonObjectKind::BTreeMap => JsonObject(Map::BTree(BTreeMap::new())),
+            #[cfg(feature = "indexmap")]
+            JsonObjectKind::IndexMap => JsonObject(Map::Index(IndexMap::new())),
+        }
+    }
+
+    pub fn kind(&self) -> JsonObjectKind {
+        match &self.0 {
+            Map::Hash(_) => JsonObjectKind::HashMap,
+            Map::BTree(_) => JsonObjectKind::BTreeMap,
+            #[cfg(feature = "indexmap")]
+            Map::Index(_) => JsonObjectKind::IndexMap,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Map::Hash(map) => map.len(),
+            Map::BTree(map) => map.len(),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match &self.0 {
+            Map::Hash(map) => map.get(key),
+            Map::BTree(map) => map.get(key),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.get(key),
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match &mut self.0 {
+            Map::Hash(map) => map.get_mut(key),
+            Map::BTree(map) => map.get_mut(key),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.get_mut(key),
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the previous value stored under `key`, if any.
+    pub fn insert(&mut self, key: String, value: JsonValue) -> Option<JsonValue> {
+        match &mut self.0 {
+            Map::Hash(map) => map.insert(key, value),
+            Map::BTree(map) => map.insert(key, value),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.insert(key, value),
+        }
+    }
+
+    /// An `IndexMap` object keeps the order of the remaining members.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        match &mut self.0 {
+            Map::Hash(map) => map.remove(key),
+            Map::BTree(map) => map.remove(key),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.shift_remove(key),
+        }
+    }
+
+    /// Keeps only the members for which `f` returns true.
+    pub fn retain(&mut self, mut f: impl FnMut(&str, &mut JsonValue) -> bool) {
+        match &mut self.0 {
+            Map::Hash(map) => map.retain(|key, value| f(key, value)),
+            Map::BTree(map) => map.retain(|key, value| f(key, value)),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.retain(|key, value| f(key, value)),
+        }
+    }
+
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        match &mut self.0 {
+            Map::Hash(map) => Entry::Hash(map.entry(key.into())),
+            Map::BTree(map) => Entry::BTree(map.entry(key.into())),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => Entry::Index(map.entry(key.into())),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        match &self.0 {
+            Map::Hash(map) => Iter::Hash(map.iter()),
+            Map::BTree(map) => Iter::BTree(map.iter()),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => Iter::Index(map.iter()),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        match &mut self.0 {
+            Map::Hash(map) => IterMut::Hash(map.iter_mut()),
+            Map::BTree(map) => IterMut::BTree(map.iter_mut()),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => IterMut::Index(map.iter_mut()),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut JsonValue> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+}
+
+impl Default for JsonObject {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for JsonObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl fmt::Debug for JsonObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<HashMap<String, JsonValue>> for JsonObject {
+    fn from(map: HashMap<String, JsonValue>) -> Self {
+        JsonObject(Map::Hash(map))
+    }
+}
+
+impl From<BTreeMap<String, JsonValue>> for JsonObject {
+    fn from(map: BTreeMap<String, JsonValue>) -> Self {
+        JsonObject(Map::BTree(map))
+    }
+}
+
+impl<const N: usize> From<[(String, JsonValue); N]> for JsonObject {
+    fn from(members: [(String, JsonValue); N]) -> Self {
+        members.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<JsonObject> for HashMap<String, JsonValue> {
+    fn from(object: JsonObject) -> Self {
+        match object.0 {
+            Map::Hash(map) => map,
+            Map::BTree(map) => map.into_iter().collect(),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.into_iter().collect(),
+        }
+    }
+}
+
+impl FromIterator<(String, JsonValue)> for JsonObject {
+    fn from_iter<I: IntoIterator<Item = (String, JsonValue)>>(iter: I) -> Self {
+        let mut object = JsonObject::new();
+        object.extend(iter);
+        object
+    }
+}
+
+impl Extend<(String, JsonValue)> for JsonObject {
+    fn extend<I: IntoIterator<Item = (String, JsonValue)>>(&mut self, iter: I) {
+        match &mut self.0 {
+            Map::Hash(map) => map.extend(iter),
+            Map::BTree(map) => map.extend(iter),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => map.extend(iter),
+        }
+    }
+}
+
+/// A member of a `JsonObject` that may or may not exist, returned by
+/// `JsonObject::entry` and `JsonValue::entry`.
+pub enum Entry<'a> {
+    Hash(hash_map::Entry<'a, String, JsonValue>),
+    BTree(btree_map::Entry<'a, String, JsonValue>),
+    #[cfg(feature = "indexmap")]
+    Index(index_map::Entry<'a, String, JsonValue>),
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &String {
+        match self {
+            Entry::Hash(entry) => entry.key(),
+            Entry::BTree(entry) => entry.key(),
+            #[cfg(feature = "indexmap")]
+            Entry::Index(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: JsonValue) -> &'a mut JsonValue {
+        match self {
+            Entry::Hash(entry) => entry.or_insert(default),
+            Entry::BTree(entry) => entry.or_insert(default),
+            #[cfg(feature = "indexmap")]
+            Entry::Index(entry) => entry.or_insert(default),
+        }
+    }
+
+    pub fn or_insert_with(self, f: impl FnOnce() -> JsonValue) -> &'a mut JsonValue {
+        match self {
+            Entry::Hash(entry) => entry.or_insert_with(f),
+            Entry::BTree(entry) => entry.or_insert_with(f),
+            #[cfg(feature = "indexmap")]
+            Entry::Index(entry) => entry.or_insert_with(f),
+        }
+    }
+
+    /// Calls `f` on the value if the member exists.
+    pub fn and_modify(self, f: impl FnOnce(&mut JsonValue)) -> Self {
+        match self {
+            Entry::Hash(entry) => Entry::Hash(entry.and_modify(f)),
+            Entry::BTree(entry) => Entry::BTree(entry.and_modify(f)),
+            #[cfg(feature = "indexmap")]
+            Entry::Index(entry) => Entry::Index(entry.and_modify(f)),
+        }
+    }
+}
+
+pub enum IntoIter {
+    Hash(hash_map::IntoIter<String, JsonValue>),
+    BTree(btree_map::IntoIter<String, JsonValue>),
+    #[cfg(feature = "indexmap")]
+    Index(index_map::IntoIter<String, JsonValue>),
+}
+
+pub enum Iter<'a> {
+    Hash(hash_map::Iter<'a, String, JsonValue>),
+    BTree(btree_map::Iter<'a, String, JsonValue>),
+    #[cfg(feature = "indexmap")]
+    Index(index_map::Iter<'a, String, JsonValue>),
+}
+
+pub enum IterMut<'a> {
+    Hash(hash_map::IterMut<'a, String, JsonValue>),
+    BTree(btree_map::IterMut<'a, String, JsonValue>),
+    #[cfg(feature = "indexmap")]
+    Index(index_map::IterMut<'a, String, JsonValue>),
+}
+
+impl Iterator for IntoIter {
+    type Item = (String, JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Hash(iter) => iter.next(),
+            IntoIter::BTree(iter) => iter.next(),
+            #[cfg(feature = "indexmap")]
+            IntoIter::Index(iter) => iter.next(),
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Hash(iter) => iter.next(),
+            Iter::BTree(iter) => iter.next(),
+            #[cfg(feature = "indexmap")]
+            Iter::Index(iter) => iter.next(),
+        }
+    }
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a String, &'a mut JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterMut::Hash(iter) => iter.next(),
+            IterMut::BTree(iter) => iter.next(),
+            #[cfg(feature = "indexmap")]
+            IterMut::Index(iter) => iter.next(),
+        }
+    }
+}
+
+impl IntoIterator for JsonObject {
+    type Item = (String, JsonValue);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self.0 {
+            Map::Hash(map) => IntoIter::Hash(map.into_iter()),
+            Map::BTree(map) => IntoIter::BTree(map.into_iter()),
+            #[cfg(feature = "indexmap")]
+            Map::Index(map) => IntoIter::Index(map.into_iter()),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonObject {
+    type Item = (&'a String, &'a JsonValue);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut JsonObject {
+    type Item = (&'a String, &'a mut JsonValue);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[test]
+fn test_object_kinds_compare_equal() {
+    let mut hash = JsonObject::with_kind(JsonObjectKind::HashMap);
+    let mut btree = JsonObject::with_kind(JsonObjectKind::BTreeMap);
+    for (key, value) in [("b", 1), ("a", 2), ("c", 3)] {
+        hash.insert(key.to_string(), JsonValue::Number(value));
+        btree.insert(key.to_string(), JsonValue::Number(value));
+    }
+    assert_eq!(hash.kind(), JsonObjectKind::HashMap);
+    assert_eq!(btree.kind(), JsonObjectKind::BTreeMap);
+    assert_eq!(hash, btree);
+    btree.remove("c");
+    assert_ne!(hash, btree);
+}
+
+#[test]
+fn test_entry_word_count() {
+    let text = "the quick fox jumps over the lazy dog the end";
+    for kind in [JsonObjectKind::HashMap, JsonObjectKind::BTreeMap] {
+        let mut counts = JsonValue::Object(JsonObject::with_kind(kind));
+        for word in text.split(' ') {
+            counts
+                .entry(word)
+                .and_modify(|count| {
+                    if let JsonValue::Number(count) = count {
+                        *count += 1;
+                    }
+                })
+                .or_insert(JsonValue::Number(1));
+        }
+        let expected = crate::parse_str(
+            r#"{"the": 3, "quick": 1, "fox": 1, "jumps": 1, "over": 1, "lazy": 1, "dog": 1, "end": 1}"#,
+        );
+        assert_eq!(Ok(counts), expected);
+    }
+}
+
+#[test]
+fn test_entry_or_insert_with() {
+    let mut value = JsonValue::Null;
+    let mut calls = 0;
+    for _ in 0..2 {
+        let list = value.entry("list").or_insert_with(|| {
+            calls += 1;
+            JsonValue::Array(vec![])
+        });
+        list.as_array_mut().unwrap().push(JsonValue::Bool(true));
+    }
+    assert_eq!(calls, 1);
+    assert_eq!(value.entry("list").key(), "list");
+    assert_eq!(Ok(value), crate::parse_str(r#"{"list": [true, true]}"#));
+}
+
+#[test]
+#[should_panic(expected = "entry called on an array")]
+fn test_entry_on_non_object() {
+    JsonValue::Array(vec![]).entry("key");
+}
+
+#[test]
+fn test_btree_object_iterates_in_key_order() {
+    let mut object = JsonObject::with_kind(JsonObjectKind::BTreeMap);
+    object.extend(["m", "z", "a"].map(|key| (key.to_string(), JsonValue::Null)));
+    let keys: Vec<&String> = object.keys().collect();
+    assert_eq!(keys, ["a", "m", "z"]);
+}
+
+#[test]
+fn test_btree_parse_serializes_deterministically() {
+    let config = crate::JsonParserConfig { preserve_order: JsonObjectKind::BTreeMap, ..Default::default() };
+    let first = r#"{"zeta": 1, "alpha": {"y": [true, null], "b": "x"}, "mid": 2.5}"#;
+    let second = r#"{"mid": 2.5, "alpha": {"b": "x", "y": [true, null]}, "zeta": 1}"#;
+    let first = crate::JsonParser::with_config(first, config.clone()).parse().unwrap();
+    let second = crate::JsonParser::with_config(second, config).parse().unwrap();
+    assert_eq!(first.to_string(), second.to_string());
+    assert_eq!(first.to_string(), r#"{"alpha":{"b":"x","y":[true,null]},"mid":2.5,"zeta":1}"#);
+}
+
+#[test]
+#[cfg(feature = "indexmap")]
+fn test_index_map_keeps_input_order() {
+    let value = crate::parse_str(r#"{"zeta": 1, "alpha": {"y": true, "b": null}, "mid": 2}"#).unwrap();
+    assert_eq!(value.to_string(), r#"{"zeta":1,"alpha":{"y":true,"b":null},"mid":2}"#);
+    let mut object = JsonObject::try_from(value).unwrap();
+    assert_eq!(object.kind(), JsonObjectKind::IndexMap);
+    object.remove("zeta");
+    object.insert("first".to_string(), JsonValue::Null);
+    let keys: Vec<&String> = object.keys().collect();
+    assert_eq!(keys, ["alpha", "mid", "first"]);
+}
@@ -0,0 +1,186 @@
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+use crate::prelude::*;
+use core::cmp::Ordering;
+
+// Orders NaN above every other number, and all NaNs alike.
+fn compare_floats(a: f64, b: f64) -> Ordering {
+    if a.is_nan() || b.is_nan() {
+        return a.is_nan().cmp(&b.is_nan());
+    }
+    a.partial_cmp(&b).unwrap()
+}
+
+// Compares exactly, without rounding the integer to an f64 first. Every
+// integer is between -2^63 and 2^64.
+fn compare_integer_to_float(integer: i128, float: f64) -> Ordering {
+    if float.is_nan() || float >= 18_446_744_073_709_551_616.0 {
+        return Ordering::Less;
+    }
+    if float < -9_223_372_036_854_775_808.0 {
+        return Ordering::Greater;
+    }
+    let truncated = float - float % 1.0;
+    integer.cmp(&(truncated as i128)).then_with(|| compare_floats(truncated, float))
+}
+
+fn integer(value: &JsonValue) -> Option<i128> {
+    match value {
+        JsonValue::Number(value) => Some(*value as i128),
+        JsonValue::Number64(value) => Some(*value as i128),
+        _ => None,
+    }
+}
+
+// Compares exactly, converting the other number to a BigDecimal.
+#[cfg(feature = "bignum")]
+fn compare_bignum(bignum: &bigdecimal::BigDecimal, other: &JsonValue) -> Option<Ordering> {
+    match other {
+        JsonValue::BigNum(other) => Some(bignum.cmp(other)),
+        JsonValue::Float(float) if float.is_nan() || *float == f64::INFINITY => Some(Ordering::Less),
+        JsonValue::Float(float) if *float == f64::NEG_INFINITY => Some(Ordering::Greater),
+        JsonValue::Float(float) => Some(bignum.cmp(&bigdecimal::BigDecimal::try_from(*float).unwrap())),
+        other => Some(bignum.cmp(&bigdecimal::BigDecimal::from(integer(other)?))),
+    }
+}
+
+// Compares two numbers by value alone, or returns None if either value is
+// not a number.
+pub(crate) fn compare_numbers(a: &JsonValue, b: &JsonValue) -> Option<Ordering> {
+    match (a, b) {
+        #[cfg(feature = "bignum")]
+        (JsonValue::BigNum(a), b) => compare_bignum(a, b),
+        #[cfg(feature = "bignum")]
+        (a, JsonValue::BigNum(b)) => compare_bignum(b, a).map(Ordering::reverse),
+        (JsonValue::Float(a), JsonValue::Float(b)) => Some(compare_floats(*a, *b)),
+        (JsonValue::Float(a), b) => Some(compare_integer_to_float(integer(b)?, *a).reverse()),
+        (a, JsonValue::Float(b)) => Some(compare_integer_to_float(integer(a)?, *b)),
+        (a, b) => Some(integer(a)?.cmp(&integer(b)?)),
+    }
+}
+
+// Breaks ties between numbers that are equal in value but not `==`.
+// `Number`, `Number64` and `Float` are `==` when their values are equal.
+fn number_kind(value: &JsonValue) -> u8 {
+    match value {
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(_) => 1,
+        _ => 0,
+    }
+}
+
+fn rank(value: &JsonValue) -> u8 {
+    match value {
+        JsonValue::Null => 0,
+        JsonValue::Bool(_) => 1,
+        JsonValue::Number(_) | JsonValue::Number64(_) | JsonValue::Float(_) => 2,
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(_) => 2,
+        JsonValue::String(_) => 3,
+        JsonValue::Array(_) => 4,
+        JsonValue::Object(_) => 5,
+    }
+}
+
+/// Values of different types are ordered `Null`, `Bool`, numbers, `String`,
+/// `Array`, `Object`. Numbers are compared by numeric value, and an integer
+/// or `Float` comes just before an equal `BigNum`, since these are not
+/// equal. NaN comes after every other number and, as with `==`, all NaNs
+/// are equal. Arrays compare element by element
+/// and objects compare their members sorted by key.
+impl Ord for JsonValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if let Some(ordering) = compare_numbers(self, other) {
+            return ordering.then_with(|| number_kind(self).cmp(&number_kind(other)));
+        }
+        match (self, other) {
+            (JsonValue::Bool(a), JsonValue::Bool(b)) => a.cmp(b),
+            (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+            (JsonValue::Array(a), JsonValue::Array(b)) => a.cmp(b),
+            (JsonValue::Object(a), JsonValue::Object(b)) => a.cmp(b),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for JsonValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn sorted_members(object: &JsonObject) -> Vec<(&String, &JsonValue)> {
+    let mut members: Vec<(&String, &JsonValue)> = object.iter().collect();
+    members.sort_by(|a, b| a.0.cmp(b.0));
+    members
+}
+
+impl Ord for JsonObject {
+    fn cmp(&self, other: &Self) -> Ordering {
+        sorted_members(self).cmp(&sorted_members(other))
+    }
+}
+
+impl PartialOrd for JsonObject {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[test]
+fn test_sort_mixed_array() {
+    let mut values = crate::parse_str(r#"[{"b": 1}, "b", [1, 2], 2, null, 1.5, true, {"a": 2}, "a", [1], -3, false, {"a": 1, "b": 0}, 1.0, 1, []]"#)
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .clone();
+    values.sort();
+    let expected = crate::parse_str(r#"[null, false, true, -3, 1, 1.0, 1.5, 2, "a", "b", [], [1], [1, 2], {"a": 1, "b": 0}, {"a": 2}, {"b": 1}]"#).unwrap();
+    assert_eq!(JsonValue::Array(values), expected);
+}
+
+#[test]
+fn test_number_ordering() {
+    let ordered = [
+        JsonValue::Float(f64::NEG_INFINITY),
+        JsonValue::Number(i64::MIN),
+        JsonValue::Float(-0.5),
+        JsonValue::Number(0),
+        JsonValue::Float(0.25),
+        JsonValue::Float(9_007_199_254_740_992.0),
+        JsonValue::Number(9_007_199_254_740_993),
+        JsonValue::Number(i64::MAX),
+        JsonValue::Float(9_223_372_036_854_775_808.0),
+        JsonValue::Number64(u64::MAX),
+        JsonValue::Float(18_446_744_073_709_551_616.0),
+        JsonValue::Float(f64::INFINITY),
+        JsonValue::Float(f64::NAN),
+    ];
+    for (i, a) in ordered.iter().enumerate() {
+        for (j, b) in ordered.iter().enumerate() {
+            assert_eq!(a.cmp(b), i.cmp(&j), "{:?} and {:?}", a, b);
+        }
+    }
+    assert_eq!(JsonValue::Number(7).cmp(&JsonValue::Number64(7)), Ordering::Equal);
+    assert_eq!(JsonValue::Number(0).cmp(&JsonValue::Float(-0.0)), Ordering::Equal);
+    assert_eq!(JsonValue::Number64(1 << 63).cmp(&JsonValue::Float(9_223_372_036_854_775_808.0)), Ordering::Equal);
+}
+
+#[test]
+fn test_ord_consistent_with_eq() {
+    let lenient = crate::JsonParserConfig { mode: crate::ParseMode::Lenient, ..Default::default() };
+    let input = r#"[null, true, 0, 0.0, -0.0, 1, Infinity, NaN, -NaN, "x", [], [0], [NaN], [-NaN], {}, {"k": [1, 2]}, {"k": [1, 2.0]}]"#;
+    let values = crate::JsonParser::with_config(input, lenient).parse().unwrap();
+    let values = values.as_array().unwrap();
+    for a in values {
+        for b in values {
+            assert_eq!(a.cmp(b) == Ordering::Equal, a == b, "{} and {}", a, b);
+            assert_eq!(a.partial_cmp(b), Some(a.cmp(b)));
+            assert_eq!(a.cmp(b), b.cmp(a).reverse());
+        }
+    }
+    let set: std::collections::BTreeSet<JsonValue> = values.iter().cloned().collect();
+    // 0, 0.0 and -0.0 are equal, as are the two NaNs, the two arrays
+    // holding them and the two objects.
+    assert_eq!(set.len(), values.len() - 5);
+}
@@ -1,27 +1,73 @@
-use std::collections::HashMap;
+use crate::config::{DuplicateKeyPolicy, JsonParserConfig, ParseMode};
+use crate::events::JsonEventHandler;
+use crate::object::JsonObject;
+use crate::pointer::escape_token;
+use crate::prelude::*;
+use crate::tokenizer::{JsonTokenizer, Token, TokenError};
+use crate::whitespace::count_whitespace;
+use alloc::collections::BTreeMap;
+use core::fmt;
 
 #[derive(Debug)]
 pub struct JsonParser {
     pub input: String,
-    pub cursor: usize
+    pub cursor: usize,
+    pub line: usize,
+    pub column: usize,
+    config: JsonParserConfig,
+    total_values: usize,
+    duplicate_keys: Vec<DuplicateKeys>,
+    // Set while `parse` reads a single Lenient document, the only case in
+    // which an unescaped quote may be part of a string.
+    inner_quotes: bool,
+    // The number of bytes before `input` that were parsed and dropped, so
+    // that byte offsets in errors count from the start of the stream.
+    pub(crate) offset: usize,
 }
 
-#[derive(Debug, PartialEq)]
+/// Every value of a key that appeared more than once in one object, kept
+/// under `DuplicateKeyPolicy::CollectAll`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateKeys {
+    /// The RFC 6901 pointer to the member in the parsed value.
+    pub pointer: String,
+    /// The values in the order they appear in the input.
+    pub values: Vec<JsonValue>,
+}
+
+#[derive(Debug, Default)]
 pub enum JsonValue {
+    #[default]
     Null,
     Bool(bool),
     Number(i64),
-    String(String),
+    // Integers above i64::MAX. The parsers only produce this variant for
+    // integers that do not fit in a `Number`, but `Number64(y)` equals
+    // `Number(x)` whenever both hold the same value.
+    Number64(u64),
+    // Numbers that the parser only produces with `JsonParserConfig::bignum`.
+    #[cfg(feature = "bignum")]
+    BigNum(bigdecimal::BigDecimal),
+    // Numbers with a fraction or an exponent. A `Float` equals a `Number`
+    // or `Number64` with exactly the same value, so `Float(3.0)` equals
+    // `Number(3)`.
+    Float(f64),
+    // Strings from `&'static str` literals are borrowed rather than copied.
+    String(Cow<'static, str>),
     Array(Vec<JsonValue>),
-    Object(HashMap<String, JsonValue>)
+    Object(JsonObject)
 }
 
 impl Clone for JsonValue {
     fn clone(&self) -> Self {
         match &self {
             JsonValue::Null => JsonValue::Null,
-            JsonValue::Bool(val) => JsonValue::Bool(val.clone()),
-            JsonValue::Number(val) => JsonValue::Number(val.clone()),
+            JsonValue::Bool(val) => JsonValue::Bool(*val),
+            JsonValue::Number(val) => JsonValue::Number(*val),
+            JsonValue::Number64(val) => JsonValue::Number64(*val),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(val) => JsonValue::BigNum(val.clone()),
+            JsonValue::Float(val) => JsonValue::Float(*val),
             JsonValue::String(val) => JsonValue::String(val.clone()),
             JsonValue::Array(array) => JsonValue::Array(array.clone()),
             JsonValue::Object(map) => JsonValue::Object(map.clone()),
@@ -29,336 +75,1020 @@ impl Clone for JsonValue {
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum ParserError {
-    ParseError(&'static str, usize),
+// Dropping nested values recursively would overflow the stack once arrays
+// and objects nest as deeply as the parser allows without a depth limit.
+// Values recurse a bounded number of levels instead, and the containers
+// below that are moved onto a heap stack and dropped one at a time.
+impl Drop for JsonValue {
+    fn drop(&mut self) {
+        let mut stack = vec![];
+        drop_nested(self, 0, &mut stack);
+        while let Some(mut value) = stack.pop() {
+            drop_nested(&mut value, 0, &mut stack);
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-enum ParseType {
-    Null,
-    Boolean,
-    Number,
-    String,
-    Array,
-    Object,
-    Unknown,
+const MAX_DROP_DEPTH: usize = 64;
+
+// Empties `value`, dropping its elements or member values one at a time.
+// Containers among them are emptied the same way for up to
+// `MAX_DROP_DEPTH` levels and moved onto `stack` below that, so that
+// dropping each one recurses no further. Containers that only hold scalars
+// and empty containers are left to drop in place, which is cheaper than
+// moving their contents out.
+fn drop_nested(value: &mut JsonValue, depth: usize, stack: &mut Vec<JsonValue>) {
+    match value {
+        JsonValue::Array(array) if array.iter().any(is_nested) => {
+            array.drain(..).for_each(|child| drop_child(child, depth, stack))
+        }
+        JsonValue::Object(members) if members.values().any(is_nested) => {
+            core::mem::take(members).into_iter().for_each(|(_, child)| drop_child(child, depth, stack))
+        }
+        _ => {}
+    }
 }
 
-fn is_whitespace(c: char) -> bool {
-    return match c {
-        '\t' => true,
-        '\n' => true,
-        '\r' => true,
-        ' ' => true,
+// Whether `value` is a container with anything in it.
+fn is_nested(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Array(array) => !array.is_empty(),
+        JsonValue::Object(members) => !members.is_empty(),
         _ => false,
     }
 }
 
-fn is_string_terminating_symbol(c: char) -> bool {
-    return match c {
-        '}' => true,
-        ']' => true,
-        ':' => true,
-        ',' => true,
-        _ => false,
+fn drop_child(mut child: JsonValue, depth: usize, stack: &mut Vec<JsonValue>) {
+    if !is_nested(&child) {
+        return;
+    }
+    if depth == MAX_DROP_DEPTH {
+        stack.push(child);
+    } else {
+        drop_nested(&mut child, depth + 1, stack);
     }
 }
 
-fn is_numeric_char(c: char) -> bool { 
-    return match c {
-        '-' => true,
-        '0' => true,
-        '1' => true,
-        '2' => true,
-        '3' => true,
-        '4' => true,
-        '5' => true,
-        '6' => true,
-        '7' => true,
-        '8' => true,
-        '9' => true,
-        _ => false,
+/// Numbers are equal when their values are exactly equal, whatever their
+/// variant, so `Number(3) == Float(3.0)` but `Number(i64::MAX)` is not equal
+/// to `Float(9223372036854775807.0)`, which rounds to 2^63. Unlike IEEE 754,
+/// every NaN equals every other NaN, so that `Eq`, `Hash` and `Ord` hold for
+/// all values.
+impl PartialEq for JsonValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (JsonValue::Null, JsonValue::Null) => true,
+            (JsonValue::Bool(a), JsonValue::Bool(b)) => a == b,
+            (JsonValue::Number(a), JsonValue::Number(b)) => a == b,
+            (JsonValue::Number64(a), JsonValue::Number64(b)) => a == b,
+            (JsonValue::Number(a), JsonValue::Number64(b)) | (JsonValue::Number64(b), JsonValue::Number(a)) => {
+                u64::try_from(*a) == Ok(*b)
+            }
+            (JsonValue::Float(a), JsonValue::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (JsonValue::Float(_), JsonValue::Number(_) | JsonValue::Number64(_))
+            | (JsonValue::Number(_) | JsonValue::Number64(_), JsonValue::Float(_)) => {
+                crate::ord::compare_numbers(self, other) == Some(core::cmp::Ordering::Equal)
+            }
+            #[cfg(feature = "bignum")]
+            (JsonValue::BigNum(a), JsonValue::BigNum(b)) => a == b,
+            (JsonValue::String(a), JsonValue::String(b)) => a == b,
+            (JsonValue::Array(a), JsonValue::Array(b)) => a == b,
+            (JsonValue::Object(a), JsonValue::Object(b)) => a == b,
+            _ => false,
+        }
     }
 }
 
-fn determine_parse_type(c: char) -> ParseType {
-    if c == '{' {
-        ParseType::Object
-    } 
-    else if c =='[' {
-        ParseType::Array
+#[derive(Debug)]
+pub enum ParserError {
+    SyntaxError { line: usize, column: usize, message: &'static str },
+    UnterminatedString { line: usize, column: usize, at_byte: usize },
+    InvalidEscapeSequence { char: char, line: usize, column: usize, at_byte: usize },
+    InvalidSurrogatePair,
+    UnexpectedEof { expected: &'static str },
+    DepthLimitExceeded { limit: usize },
+    StringLengthExceeded { limit: usize },
+    ArrayLengthExceeded { limit: usize },
+    ObjectSizeExceeded { limit: usize },
+    TotalValuesExceeded { limit: usize },
+    InvalidNumberLiteral { raw: String },
+    NumberOverflow(String),
+    InvalidUtf8 { byte_offset: usize },
+    // The line and column are those of the second key.
+    DuplicateKey { key: String, line: usize, column: usize, first_at: usize, second_at: usize },
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+// I/O errors do not implement PartialEq, so they compare by their kind.
+impl PartialEq for ParserError {
+    fn eq(&self, other: &Self) -> bool {
+        use ParserError::*;
+        match (self, other) {
+            (SyntaxError { line, column, message }, SyntaxError { line: l, column: c, message: m }) => (line, column, message) == (l, c, m),
+            (UnterminatedString { line, column, at_byte }, UnterminatedString { line: l, column: c, at_byte: b }) => (line, column, at_byte) == (l, c, b),
+            (InvalidEscapeSequence { char, line, column, at_byte }, InvalidEscapeSequence { char: ch, line: l, column: c, at_byte: b }) => {
+                (char, line, column, at_byte) == (ch, l, c, b)
+            }
+            (InvalidSurrogatePair, InvalidSurrogatePair) => true,
+            (UnexpectedEof { expected }, UnexpectedEof { expected: e }) => expected == e,
+            (DepthLimitExceeded { limit }, DepthLimitExceeded { limit: l })
+            | (StringLengthExceeded { limit }, StringLengthExceeded { limit: l })
+            | (ArrayLengthExceeded { limit }, ArrayLengthExceeded { limit: l })
+            | (ObjectSizeExceeded { limit }, ObjectSizeExceeded { limit: l })
+            | (TotalValuesExceeded { limit }, TotalValuesExceeded { limit: l }) => limit == l,
+            (InvalidNumberLiteral { raw }, InvalidNumberLiteral { raw: r }) | (NumberOverflow(raw), NumberOverflow(r)) => raw == r,
+            (InvalidUtf8 { byte_offset }, InvalidUtf8 { byte_offset: b }) => byte_offset == b,
+            (DuplicateKey { key, line, column, first_at, second_at }, DuplicateKey { key: k, line: l, column: c, first_at: f, second_at: s }) => {
+                (key, line, column, first_at, second_at) == (k, l, c, f, s)
+            }
+            #[cfg(feature = "std")]
+            (Io(err), Io(other)) => err.kind() == other.kind(),
+            _ => false,
+        }
     }
-    else if is_numeric_char(c) {
-        ParseType::Number
-        
-    } else if c == '"' {
-        ParseType::String
-    } else if c == 't' || c == 'f' {
-        ParseType::Boolean
-    } else if c == 'n' {
-        ParseType::Null
-    } else {
-        ParseType::Unknown
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParserError::SyntaxError { line, column, message } => write!(f, "syntax error at line {}, column {}: {}", line, column, message),
+            ParserError::UnterminatedString { line, column, .. } => write!(f, "unterminated string starting at line {}, column {}", line, column),
+            ParserError::InvalidEscapeSequence { char, line, column, .. } => write!(f, "invalid escape sequence \\{} at line {}, column {}", char, line, column),
+            ParserError::InvalidSurrogatePair => write!(f, "invalid UTF-16 surrogate pair in a \\u escape"),
+            ParserError::UnexpectedEof { expected } => write!(f, "unexpected end of input, expected {}", expected),
+            ParserError::DepthLimitExceeded { limit } => write!(f, "nesting depth exceeds the limit of {}", limit),
+            ParserError::StringLengthExceeded { limit } => write!(f, "string length exceeds the limit of {} bytes", limit),
+            ParserError::ArrayLengthExceeded { limit } => write!(f, "array length exceeds the limit of {} elements", limit),
+            ParserError::ObjectSizeExceeded { limit } => write!(f, "object size exceeds the limit of {} members", limit),
+            ParserError::TotalValuesExceeded { limit } => write!(f, "number of values exceeds the limit of {}", limit),
+            ParserError::InvalidNumberLiteral { raw } => write!(f, "invalid number literal {:?}", raw),
+            ParserError::NumberOverflow(raw) => write!(f, "number {} is out of range", raw),
+            ParserError::InvalidUtf8 { byte_offset } => write!(f, "invalid UTF-8 at byte {}", byte_offset),
+            ParserError::DuplicateKey { key, line, column, .. } => write!(f, "duplicate key {:?} at line {}, column {}", key, line, column),
+            #[cfg(feature = "std")]
+            ParserError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl core::error::Error for ParserError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            ParserError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+
+// Where a key or an escape sequence starts, for errors raised once the
+// parser has moved past it.
+#[derive(Clone, Copy)]
+pub(crate) struct Position {
+    at_byte: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn syntax_error(self, message: &'static str) -> ParserError {
+        ParserError::SyntaxError { line: self.line, column: self.column, message }
+    }
+
+    fn unterminated_string(self) -> ParserError {
+        ParserError::UnterminatedString { line: self.line, column: self.column, at_byte: self.at_byte }
+    }
+
+    fn invalid_escape(self, char: char) -> ParserError {
+        ParserError::InvalidEscapeSequence { char, line: self.line, column: self.column, at_byte: self.at_byte }
+    }
+}
+
+// A container whose elements are still being parsed. Objects also hold the
+// key of the member whose value comes next.
+pub(crate) enum ParseFrame {
+    Array(Vec<JsonValue>),
+    // The key of the member being parsed and where it starts. `first_at`
+    // holds where each key first appeared, but only for
+    // `DuplicateKeyPolicy::Error`.
+    Object { members: JsonObject, key: Option<String>, key_at: Position, first_at: BTreeMap<String, usize> },
+}
+
+impl ParseFrame {
+    fn object(members: JsonObject, (key_at, key): (Position, String)) -> Self {
+        ParseFrame::Object { members, key: Some(key), key_at, first_at: BTreeMap::new() }
+    }
+
+    fn into_value(self) -> JsonValue {
+        match self {
+            ParseFrame::Array(array) => JsonValue::Array(array),
+            ParseFrame::Object { members, .. } => JsonValue::Object(members),
+        }
     }
 }
 
+// The containers that a parse has opened so far. Keeping them between
+// steps lets `FeedableParser` resume a parse once more input arrives.
+#[derive(Default)]
+pub(crate) struct ParseState {
+    stack: Vec<ParseFrame>,
+    // Whether the container at the top of the stack has just had an
+    // element added, so that a separator or its closing bracket comes next.
+    after_element: bool,
+}
+
+impl ParseState {
+    // Whether no value has been started yet.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+// The input that one step of a parse has read, before it is applied to the
+// `ParseState`.
+pub(crate) enum Step {
+    // A scalar or an empty container.
+    Value(JsonValue),
+    // An array, or an object along with its first key.
+    Open(ParseFrame),
+    // A ',' that another element follows, along with its key in an object.
+    Separator(Option<(Position, String)>),
+    // The bracket that closes the container at the top of the stack.
+    Close,
+}
+
+// Where a step started, to go back to when it runs out of input.
+#[derive(Clone, Copy)]
+pub(crate) struct Checkpoint {
+    cursor: usize,
+    line: usize,
+    column: usize,
+    total_values: usize,
+}
+
+// The value that a token starts: a scalar, or the bracket that opens a
+// container.
+enum ValueStart {
+    Scalar(JsonValue),
+    Array,
+    Object,
+}
+
 impl JsonParser {
+    pub fn new(input: impl Into<String>) -> Self {
+        Self::with_config(input, JsonParserConfig::default())
+    }
+
+    pub fn with_config(input: impl Into<String>, config: JsonParserConfig) -> Self {
+        JsonParser {
+            input: input.into(),
+            cursor: 0,
+            line: 1,
+            column: 1,
+            config,
+            total_values: 0,
+            duplicate_keys: vec![],
+            inner_quotes: false,
+            offset: 0,
+        }
+    }
+
+    /// Replaces the input so that the parser can be reused for another
+    /// document, keeping its configuration.
+    pub fn reset(&mut self, new_input: String) {
+        self.input = new_input;
+        self.rewind();
+    }
+
+    /// Like `reset`, but copies `new_input` into the existing input buffer,
+    /// which only allocates when the buffer has to grow.
+    pub fn reset_from_str(&mut self, new_input: &str) {
+        self.input.clear();
+        self.input.push_str(new_input);
+        self.rewind();
+    }
+
+    fn rewind(&mut self) {
+        self.cursor = 0;
+        self.line = 1;
+        self.column = 1;
+        self.total_values = 0;
+    }
+
     pub fn parse(&mut self) -> Result<JsonValue, ParserError> {
-        let value = self.parse_helper();
+        self.skip_bom();
+        self.inner_quotes = self.config.mode == ParseMode::Lenient;
+        let value = self.parse_helper()?;
         self.skip_whitespace();
         if !self.eof() {
-            return Err(ParserError::ParseError("Could not parse JSON.", self.cursor.clone()))
+            return Err(self.syntax_error("Expected the end of input"))
         }
-        value
+        Ok(value)
     }
 
-    fn eof(&self) -> bool {
-        return self.cursor >= self.input.chars().count();
+    /// The keys that appeared more than once in an object of the value
+    /// parsed last, when `JsonParserConfig::duplicate_key_policy` is
+    /// `CollectAll`. Empty under the other policies.
+    pub fn duplicate_keys(&self) -> &[DuplicateKeys] {
+        &self.duplicate_keys
     }
 
-    fn peek(&self) -> char {
-        if self.eof() {
-            return '|';
+    /// Checks that `input` is a single valid document, enforcing the same
+    /// limits as `parse`, without building a `JsonValue`.
+    pub fn validate(input: &str) -> Result<(), ParserError> {
+        JsonParser::new(input).parse_helper_validate()
+    }
+
+    // Runs the event-driven parse with every event ignored.
+    fn parse_helper_validate(&mut self) -> Result<(), ParserError> {
+        struct Discard;
+        impl JsonEventHandler for Discard {}
+        self.parse_events(&mut Discard)
+    }
+
+    /// Lazily parses a sequence of top-level values separated by
+    /// whitespace. Iteration stops at the end of the input or after the
+    /// first error.
+    pub fn parse_many(&mut self) -> impl Iterator<Item = Result<JsonValue, ParserError>> + '_ {
+        self.skip_bom();
+        let mut failed = false;
+        core::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            self.skip_whitespace();
+            if self.eof() {
+                return None;
+            }
+            let value = self.parse_next();
+            failed = value.is_err();
+            Some(value)
+        })
+    }
+
+    // Editors on Windows often save JSON with a byte order mark, which is
+    // skipped without moving the column, since it is not shown either.
+    pub(crate) fn skip_bom(&mut self) {
+        if self.cursor == 0 && !self.config.reject_bom && self.input.starts_with('\u{feff}') {
+            self.cursor = '\u{feff}'.len_utf8();
         }
-        // FIXME: This feels like an inefficient way to do this,
-        // i.e. we always have to do a linear scan up to the nth 
-        // character at self.cursor whenever we call peek().
-        self.input.chars().nth(self.cursor).unwrap()
     }
 
-    fn peek_after_white_space(&mut self) -> char {
+    // Parses one more top-level value, with its own budget of values.
+    pub(crate) fn parse_next(&mut self) -> Result<JsonValue, ParserError> {
+        self.start_value();
+        self.parse_helper()
+    }
+
+    pub(crate) fn start_value(&mut self) {
+        self.total_values = 0;
+        self.inner_quotes = false;
+    }
+
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { cursor: self.cursor, line: self.line, column: self.column, total_values: self.total_values }
+    }
+
+    pub(crate) fn restore(&mut self, checkpoint: Checkpoint) {
+        Checkpoint { cursor: self.cursor, line: self.line, column: self.column, total_values: self.total_values } = checkpoint;
+    }
+
+    fn eof(&self) -> bool {
+        self.cursor >= self.input.len()
+    }
+
+    // Moves the cursor forward by `bytes`, which must end on a character
+    // boundary, keeping the line and column of the cursor up to date.
+    #[inline]
+    fn advance(&mut self, bytes: usize) {
+        Position { line: self.line, column: self.column, .. } = self.position_at(self.cursor + bytes);
+        self.cursor += bytes;
+    }
+
+    fn syntax_error(&self, message: &'static str) -> ParserError {
+        self.position().syntax_error(message)
+    }
+
+    // Reports a missing token as UnexpectedEof when the input ran out and
+    // as a SyntaxError carrying `message` otherwise.
+    fn expected(&self, expected: &'static str, message: &'static str) -> ParserError {
         if self.eof() {
-            return '|';
-        }
-        let mut temp_cursor = self.cursor.clone();
-        temp_cursor += 1;
-        while !self.eof() {
-            if !is_whitespace(self.input.chars().nth(temp_cursor).unwrap()) {
-                break;
-            }
-            temp_cursor += 1;
+            ParserError::UnexpectedEof { expected }
+        } else {
+            self.syntax_error(message)
         }
-        self.input.chars().nth(temp_cursor).unwrap()
     }
 
-    fn skip_whitespace(&mut self) {
-        while !self.eof() {
-            if !is_whitespace(self.input.chars().nth(self.cursor).unwrap()) {
-                break;
+    fn position(&self) -> Position {
+        Position { at_byte: self.offset + self.cursor, line: self.line, column: self.column }
+    }
+
+    // The position of byte `at_byte`, which is not before the cursor. Only
+    // the first byte of each character moves the column.
+    #[inline]
+    fn position_at(&self, at_byte: usize) -> Position {
+        let skipped = &self.input.as_bytes()[self.cursor..at_byte];
+        let columns = |bytes: &[u8]| bytes.iter().filter(|b| **b & 0xc0 != 0x80).count();
+        let (line, column) = match skipped.iter().rposition(|b| *b == b'\n') {
+            Some(last) => (self.line + skipped.iter().filter(|b| **b == b'\n').count(), columns(&skipped[last..])),
+            None => (self.line, self.column + columns(skipped)),
+        };
+        Position { at_byte: self.offset + at_byte, line, column }
+    }
+
+    // The tokens are read by a `JsonTokenizer` that starts at the cursor,
+    // which the parser then moves past them.
+    #[inline]
+    fn tokenizer(&self) -> JsonTokenizer<'_> {
+        JsonTokenizer::at(&self.input, self.cursor, self.config.mode, self.inner_quotes)
+    }
+
+    fn lenient(&self) -> bool {
+        self.config.mode != ParseMode::Strict
+    }
+
+    fn json5(&self) -> bool {
+        self.config.mode == ParseMode::Json5
+    }
+
+    // Skips whitespace, along with the comments that lenient and JSON5
+    // input allow.
+    #[inline]
+    pub(crate) fn skip_whitespace(&mut self) {
+        let mut tokenizer = self.tokenizer();
+        tokenizer.skip_insignificant();
+        self.advance(tokenizer.offset() - self.cursor);
+    }
+
+    // Skips a run of whitespace. It never contains a multi-byte character,
+    // so the column can be worked out from the bytes.
+    pub(crate) fn advance_whitespace(&mut self) {
+        let length = count_whitespace(&self.input.as_bytes()[self.cursor..]);
+        let skipped = &self.input.as_bytes()[self.cursor..self.cursor + length];
+        match skipped.iter().rposition(|b| *b == b'\n') {
+            Some(last) => {
+                self.line += skipped.iter().filter(|b| **b == b'\n').count();
+                self.column = length - last;
             }
-            self.cursor += 1;
+            None => self.column += length,
         }
+        self.cursor += length;
     }
 
-    fn consume_specific(&mut self, expected: char) -> bool {
-        if self.peek() != expected {
+    // Consumes `expected` if it is the punctuation at the cursor.
+    #[inline]
+    fn consume_specific(&mut self, expected: Token<'static>) -> bool {
+        if self.tokenizer().next_punctuation() != Some(expected) {
             return false;
         }
-        self.cursor += 1;
+        self.advance(1);
         true
     }
 
-    fn consume_and_unescape_string(&mut self) -> Result<String, ParserError> {
-        if !self.consume_specific('"') {
-            return Err(ParserError::ParseError("Expected '\"' ", self.cursor));
+    // Converts an error from the tokenizer, which was reading an object key
+    // if `key` is set. An unexpected character past the start of the token
+    // is a control character in a string.
+    fn token_error(&self, err: TokenError, key: bool) -> ParserError {
+        match err {
+            TokenError::UnexpectedCharacter { at_byte, .. } if at_byte > self.cursor => {
+                self.position_at(at_byte).syntax_error("Unescaped control character in string")
+            }
+            TokenError::UnexpectedCharacter { .. } if key => self.syntax_error("Expected '\"'"),
+            TokenError::UnexpectedCharacter { char: 't' | 'f', .. } => self.syntax_error("Expected either true or false"),
+            TokenError::UnexpectedCharacter { char: 'n', .. } => self.syntax_error("Expected null"),
+            TokenError::UnexpectedCharacter { .. } => self.syntax_error("Expected a value"),
+            TokenError::UnterminatedString { .. } => self.position().unterminated_string(),
+            TokenError::InvalidEscapeSequence { char, at_byte } => self.position_at(at_byte).invalid_escape(char),
+            TokenError::InvalidNumberLiteral { .. } => self.invalid_number(self.cursor),
         }
-        let mut builder = String::new();
-        loop {
-            match self.peek() {
-                '"' => {
-                    if !is_string_terminating_symbol(self.peek_after_white_space()){
-                        builder.push('"');
-                        self.consume_specific('"');
-                    } else {
-                        self.consume_specific('"');
-                        return Ok(builder);
-                    }
-                },
-                '|' => {
-                    return Ok(builder);
-                },
-                ch => {
-                    builder.push(ch);
-                    self.cursor += 1;
-                }
+    }
+
+    // Decodes the contents of a string token, which start at byte `start`.
+    // Most strings have no escape sequences and are copied in one go. The
+    // length limit is checked before each copy so that an oversized string
+    // fails before allocating its full length.
+    fn unescape(&self, raw: &str, start: usize) -> Result<String, ParserError> {
+        let limit = self.config.max_string_length;
+        let exceeded = ParserError::StringLengthExceeded { limit };
+        if !raw.contains('\\') {
+            return if raw.len() > limit { Err(exceeded) } else { Ok(raw.to_string()) };
+        }
+        // Escapes never unescape to more bytes than they take up.
+        let mut builder = String::with_capacity(raw.len().min(limit));
+        let mut rest = raw;
+        while let Some(backslash) = rest.find('\\') {
+            if builder.len() + backslash > limit {
+                return Err(exceeded);
             }
+            builder.push_str(&rest[..backslash]);
+            let at_byte = start + raw.len() - rest.len() + backslash;
+            let (unescaped, length) = self.unescape_sequence(&rest[backslash + 1..], at_byte)?;
+            builder.extend(unescaped);
+            if builder.len() > limit {
+                return Err(exceeded);
+            }
+            rest = &rest[backslash + 1 + length..];
+        }
+        if builder.len() + rest.len() > limit {
+            return Err(exceeded);
         }
+        builder.push_str(rest);
+        Ok(builder)
     }
 
-    fn parse_helper(&mut self) -> Result<JsonValue, ParserError> {
-        self.skip_whitespace();
-        let type_to_parse: ParseType = determine_parse_type(self.peek());
-        return match type_to_parse {
-            ParseType::Object => self.parse_object(),
-            ParseType::Number => self.parse_number(),
-            ParseType::String => self.parse_string(),
-            ParseType::Boolean => self.parse_bool(),
-            ParseType::Null => self.parse_null(),
-            ParseType::Array => self.parse_array(),
-            _ => Err(ParserError::ParseError("parse_helper failed", self.cursor)),
+    // Decodes the escape sequence after the backslash at `at_byte`, which
+    // the tokenizer has checked in strict and lenient input, and returns
+    // the length of `escaped` that it takes up. A JSON5 line continuation
+    // produces no character at all.
+    fn unescape_sequence(&self, escaped: &str, at_byte: usize) -> Result<(Option<char>, usize), ParserError> {
+        let ch = escaped.chars().next().unwrap();
+        let unescaped = match ch {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => return self.unicode_escape(escaped, at_byte),
+            _ if !self.json5() => return Err(self.position_at(at_byte).invalid_escape(ch)),
+            '\'' => '\'',
+            'v' => '\u{b}',
+            '0' if !escaped[1..].starts_with(|c: char| c.is_ascii_digit()) => '\0',
+            'x' => return Ok((char::from_u32(self.hex(&escaped[1..], 2, 'x', at_byte)?), 3)),
+            '\r' => return Ok((None, 1 + usize::from(escaped[1..].starts_with('\n')))),
+            '\n' | '\u{2028}' | '\u{2029}' => return Ok((None, ch.len_utf8())),
+            _ if ch.is_ascii_digit() => return Err(self.position_at(at_byte).invalid_escape(ch)),
+            _ => ch,
         };
+        Ok((Some(unescaped), ch.len_utf8()))
+    }
+
+    fn hex(&self, digits: &str, length: usize, escape: char, at_byte: usize) -> Result<u32, ParserError> {
+        match digits.get(..length) {
+            Some(digits) if digits.chars().all(|c| c.is_ascii_hexdigit()) => Ok(u32::from_str_radix(digits, 16).unwrap()),
+            _ => Err(self.position_at(at_byte).invalid_escape(escape)),
+        }
     }
 
-    fn parse_object(&mut self) -> Result<JsonValue, ParserError> {
-        if !self.consume_specific('{') {
-            return Err(ParserError::ParseError("Expected '{'", self.cursor));
+    // Characters outside the Basic Multilingual Plane are escaped as a
+    // UTF-16 surrogate pair, i.e. two consecutive \uXXXX sequences.
+    fn unicode_escape(&self, escaped: &str, at_byte: usize) -> Result<(Option<char>, usize), ParserError> {
+        let high = self.hex(&escaped[1..], 4, 'u', at_byte)?;
+        if (0xDC00..=0xDFFF).contains(&high) {
+            return Err(ParserError::InvalidSurrogatePair);
+        }
+        if !(0xD800..=0xDBFF).contains(&high) {
+            return Ok((char::from_u32(high), 5));
+        }
+        let Some(low) = escaped[5..].strip_prefix("\\u") else {
+            return Err(ParserError::InvalidSurrogatePair);
+        };
+        let low = self.hex(low, 4, 'u', at_byte + 6)?;
+        if !(0xDC00..=0xDFFF).contains(&low) {
+            return Err(ParserError::InvalidSurrogatePair);
         }
-        let mut values: HashMap<String, JsonValue> = HashMap::new();
+        let code_point = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        Ok((char::from_u32(code_point), 11))
+    }
+
+    // Parses a value using an explicit stack of open containers instead of
+    // recursion, so deeply nested input cannot overflow the call stack.
+    fn parse_helper(&mut self) -> Result<JsonValue, ParserError> {
+        self.duplicate_keys.clear();
+        let mut state = ParseState::default();
         loop {
-            self.skip_whitespace();
-            if self.peek() == '}' {
-                return Err(ParserError::ParseError("'}' in an invalid position", self.cursor));
+            let step = self.read_step(&state)?;
+            if let Some(value) = self.apply_step(&mut state, step)? {
+                return Ok(value);
             }
-            self.skip_whitespace();
+        }
+    }
 
-            // Get the property key.
-            let key = self.consume_and_unescape_string().unwrap();
+    // Reads the next step of the parse in `state`: a value, or the
+    // separator after an element along with the key that follows it. Only
+    // the cursor and the count of values move, so a step that runs out of
+    // input can be read again from a checkpoint.
+    #[inline]
+    pub(crate) fn read_step(&mut self, state: &ParseState) -> Result<Step, ParserError> {
+        let is_object = match state.stack.last() {
+            _ if !state.after_element => return self.read_value(state.stack.len()),
+            Some(ParseFrame::Object { .. }) => true,
+            _ => false,
+        };
+        let closed = if is_object { self.consume_object_separator()? } else { self.consume_array_separator()? };
+        if closed {
+            return Ok(Step::Close);
+        }
+        Ok(Step::Separator(if is_object { Some(self.parse_member_key_at()?) } else { None }))
+    }
 
-            self.skip_whitespace();
-            if !self.consume_specific(':') {
-                return Err(ParserError::ParseError("Expected ':'", self.cursor));
+    // Applies a step to `state`, returning the top-level value once it is
+    // complete. Finished values are added to their container, applying the
+    // length limits and the duplicate key policy.
+    #[inline]
+    pub(crate) fn apply_step(&mut self, state: &mut ParseState, step: Step) -> Result<Option<JsonValue>, ParserError> {
+        let value = match step {
+            Step::Value(value) => value,
+            Step::Close => state.stack.pop().unwrap().into_value(),
+            Step::Open(frame) => {
+                state.stack.push(frame);
+                state.after_element = false;
+                return Ok(None);
             }
-            self.skip_whitespace();
-
-            // Get the property value.
-            let value = self.parse_helper().unwrap();
-            values.insert(key, value);
+            Step::Separator(next) => {
+                if let (Some((at, next)), Some(ParseFrame::Object { key, key_at, .. })) = (next, state.stack.last_mut()) {
+                    (*key, *key_at) = (Some(next), at);
+                }
+                state.after_element = false;
+                return Ok(None);
+            }
+        };
+        match state.stack.last_mut() {
+            None => {
+                state.after_element = false;
+                return Ok(Some(value));
+            }
+            Some(ParseFrame::Array(array)) => {
+                array.push(value);
+                if array.len() > self.config.max_array_length {
+                    return Err(ParserError::ArrayLengthExceeded { limit: self.config.max_array_length });
+                }
+            }
+            Some(ParseFrame::Object { .. }) => self.add_member(&mut state.stack, value)?,
+        }
+        state.after_element = true;
+        Ok(None)
+    }
 
-            self.skip_whitespace();
-            if self.peek() == '}' {
-                break;
+    // Adds a finished member value to the object at the top of `stack`,
+    // applying the duplicate key policy.
+    fn add_member(&mut self, stack: &mut [ParseFrame], value: JsonValue) -> Result<(), ParserError> {
+        let (last, parents) = stack.split_last_mut().unwrap();
+        let ParseFrame::Object { members, key, key_at, first_at } = last else { unreachable!() };
+        let key = key.take().unwrap();
+        let policy = self.config.duplicate_key_policy;
+        if !members.contains_key(&key) {
+            if policy == DuplicateKeyPolicy::Error {
+                first_at.insert(key.clone(), key_at.at_byte);
             }
-            if !self.consume_specific(',') {
-                return Err(ParserError::ParseError("Expected ','", self.cursor));
+            members.insert(key, value);
+            if members.len() > self.config.max_object_size {
+                return Err(ParserError::ObjectSizeExceeded { limit: self.config.max_object_size });
             }
-            self.skip_whitespace();
-            if self.peek() == '}' {
-                return Err(ParserError::ParseError("'}' in an invalid position", self.cursor));
+            return Ok(());
+        }
+        match policy {
+            DuplicateKeyPolicy::LastWins => drop(members.insert(key, value)),
+            DuplicateKeyPolicy::FirstWins => {}
+            DuplicateKeyPolicy::Error => {
+                let Position { at_byte: second_at, line, column } = *key_at;
+                return Err(ParserError::DuplicateKey { line, column, first_at: first_at[&key], second_at, key });
+            }
+            DuplicateKeyPolicy::CollectAll => {
+                let mut pointer = String::new();
+                for parent in parents.iter() {
+                    match parent {
+                        ParseFrame::Array(array) => pointer += &format!("/{}", array.len()),
+                        ParseFrame::Object { key, .. } => pointer += &format!("/{}", escape_token(key.as_ref().unwrap())),
+                    }
+                }
+                pointer += &format!("/{}", escape_token(&key));
+                let previous = members.insert(key, value.clone()).unwrap();
+                match self.duplicate_keys.iter_mut().find(|duplicate| duplicate.pointer == pointer) {
+                    Some(duplicate) => duplicate.values.push(value),
+                    None => self.duplicate_keys.push(DuplicateKeys { pointer, values: vec![previous, value] }),
+                }
             }
         }
-        if !self.consume_specific('}') {
-            return Err(ParserError::ParseError("Expected '}'", self.cursor));
+        Ok(())
+    }
+
+    // Reads a scalar, or the start of a container nested `depth` levels
+    // deep. Empty containers are read as values.
+    #[inline]
+    fn read_value(&mut self, depth: usize) -> Result<Step, ParserError> {
+        self.skip_whitespace();
+        self.count_value()?;
+        let container = match self.next_value()? {
+            ValueStart::Scalar(value) => return Ok(Step::Value(value)),
+            _ if depth >= self.config.max_depth => return Err(ParserError::DepthLimitExceeded { limit: self.config.max_depth }),
+            container => container,
+        };
+        self.skip_whitespace();
+        if let ValueStart::Array = container {
+            if self.consume_specific(Token::RBracket) {
+                return Ok(Step::Value(JsonValue::Array(vec![])));
+            }
+            return Ok(Step::Open(ParseFrame::Array(vec![])));
+        }
+        if self.consume_specific(Token::RBrace) {
+            return Ok(Step::Value(JsonValue::Object(JsonObject::with_kind(self.config.preserve_order))));
         }
-        Ok(JsonValue::Object(values))
+        let key = self.parse_member_key_at()?;
+        Ok(Step::Open(ParseFrame::object(JsonObject::with_kind(self.config.preserve_order), key)))
     }
 
-    fn parse_array(&mut self) -> Result<JsonValue, ParserError> {
-        if !self.consume_specific('[') {
-            return Err(ParserError::ParseError("Expected '['", self.cursor));
+    // Reads the token at the cursor as a value.
+    fn next_value(&mut self) -> Result<ValueStart, ParserError> {
+        if self.eof() {
+            return Err(ParserError::UnexpectedEof { expected: "value" });
         }
-        let mut array = vec![];
-        while self.peek() != ']' {
+        let mut tokenizer = self.tokenizer();
+        let token = tokenizer.next_token().map_err(|err| self.token_error(err, false))?;
+        let end = tokenizer.offset();
+        let value = match token {
+            Token::LBrace => ValueStart::Object,
+            Token::LBracket => ValueStart::Array,
+            Token::Null => ValueStart::Scalar(JsonValue::Null),
+            Token::Bool(value) => ValueStart::Scalar(JsonValue::Bool(value)),
+            Token::Number(raw) => ValueStart::Scalar(self.number_value(raw)?),
+            Token::String(raw) => ValueStart::Scalar(JsonValue::String(self.unescape(raw, self.cursor + 1)?.into())),
+            _ => return Err(self.syntax_error("Expected a value")),
+        };
+        self.advance(end - self.cursor);
+        Ok(value)
+    }
+
+    fn count_value(&mut self) -> Result<(), ParserError> {
+        if self.total_values >= self.config.max_total_values {
+            return Err(ParserError::TotalValuesExceeded { limit: self.config.max_total_values });
+        }
+        self.total_values += 1;
+        Ok(())
+    }
+
+    // Drives `handler` through the input like `parse`, without building
+    // the values. Keeps a flag per open container that is true for objects,
+    // along with the number of elements it has so far.
+    pub(crate) fn parse_events(&mut self, handler: &mut impl JsonEventHandler) -> Result<(), ParserError> {
+        self.skip_bom();
+        let mut open: Vec<(bool, usize)> = vec![];
+        'value: loop {
             self.skip_whitespace();
-            let element = self.parse_helper().unwrap();
-            array.push(element);
+            self.count_value()?;
+            match self.next_value()? {
+                ValueStart::Array | ValueStart::Object if open.len() >= self.config.max_depth => {
+                    return Err(ParserError::DepthLimitExceeded { limit: self.config.max_depth });
+                }
+                ValueStart::Object => {
+                    handler.on_start_object();
+                    self.skip_whitespace();
+                    if self.consume_specific(Token::RBrace) {
+                        handler.on_end_object();
+                    } else {
+                        handler.on_object_key(&self.parse_member_key()?);
+                        open.push((true, 0));
+                        continue;
+                    }
+                }
+                ValueStart::Array => {
+                    handler.on_start_array();
+                    self.skip_whitespace();
+                    if self.consume_specific(Token::RBracket) {
+                        handler.on_end_array();
+                    } else {
+                        open.push((false, 0));
+                        continue;
+                    }
+                }
+                ValueStart::Scalar(value) => match &value {
+                    JsonValue::Null => handler.on_null(),
+                    JsonValue::Bool(value) => handler.on_bool(*value),
+                    JsonValue::Number(value) => handler.on_number(*value),
+                    JsonValue::Number64(value) => handler.on_number64(*value),
+                    #[cfg(feature = "bignum")]
+                    JsonValue::BigNum(value) => handler.on_bignum(value),
+                    JsonValue::Float(value) => handler.on_float(*value),
+                    JsonValue::String(value) => handler.on_string(value),
+                    JsonValue::Array(_) | JsonValue::Object(_) => unreachable!(),
+                },
+            }
+            // Report the end of every container the value completed.
+            while let Some((is_object, length)) = open.last_mut() {
+                *length += 1;
+                if *is_object {
+                    if *length > self.config.max_object_size {
+                        return Err(ParserError::ObjectSizeExceeded { limit: self.config.max_object_size });
+                    }
+                    if !self.consume_object_separator()? {
+                        handler.on_object_key(&self.parse_member_key()?);
+                        continue 'value;
+                    }
+                    handler.on_end_object();
+                } else {
+                    if *length > self.config.max_array_length {
+                        return Err(ParserError::ArrayLengthExceeded { limit: self.config.max_array_length });
+                    }
+                    if !self.consume_array_separator()? {
+                        continue 'value;
+                    }
+                    handler.on_end_array();
+                }
+                open.pop();
+            }
             self.skip_whitespace();
-            if !self.consume_specific(',') && !(self.peek() == ']') {
-                return Err(ParserError::ParseError("Expected ',' or ']'", self.cursor));
+            if !self.eof() {
+                return Err(self.syntax_error("Expected the end of input"));
             }
+            return Ok(());
         }
-        if !self.consume_specific(']') {
-            return Err(ParserError::ParseError("Expected ']'", self.cursor));
+    }
+
+    // Consumes the ',' or ']' after an array element and returns whether
+    // the array was closed.
+    fn consume_array_separator(&mut self) -> Result<bool, ParserError> {
+        self.skip_whitespace();
+        if self.consume_specific(Token::RBracket) {
+            return Ok(true);
         }
-        Ok(JsonValue::Array(array))
+        if !self.consume_specific(Token::Comma) {
+            return Err(self.expected("',' or ']'", "Expected ',' or ']'"));
+        }
+        self.skip_whitespace();
+        Ok(self.lenient() && self.consume_specific(Token::RBracket))
     }
 
-    fn parse_bool(&mut self) -> Result<JsonValue, ParserError> {
-        let value: bool;
-        if &self.input[self.cursor..self.cursor+4] == "true" {
-            value = true;
-            self.cursor += 4;
-        } else if &self.input[self.cursor..self.cursor+5] == "false" {
-            value = false;
-            self.cursor += 5;
-        } else {
-            return Err(ParserError::ParseError("Expected either true or false", self.cursor));
+    // Consumes the ',' or '}' after an object member and returns whether
+    // the object was closed.
+    fn consume_object_separator(&mut self) -> Result<bool, ParserError> {
+        self.skip_whitespace();
+        if self.consume_specific(Token::RBrace) {
+            return Ok(true);
+        }
+        if !self.consume_specific(Token::Comma) {
+            return Err(self.expected("',' or '}'", "Expected ',' or '}'"));
         }
-        Ok(JsonValue::Bool(value))
+        self.skip_whitespace();
+        if self.tokenizer().next_punctuation() == Some(Token::RBrace) {
+            if self.lenient() {
+                self.advance(1);
+                return Ok(true);
+            }
+            return Err(self.syntax_error("'}' in an invalid position"));
+        }
+        Ok(false)
     }
 
-    fn parse_null(&mut self) -> Result<JsonValue, ParserError> {
-        if &self.input[self.cursor..self.cursor+4] == "null" {
-            self.cursor += 4;
-        } else {
-            return Err(ParserError::ParseError("Expected null", self.cursor));
+    // Parses a property key and the ':' that follows it.
+    fn parse_member_key(&mut self) -> Result<String, ParserError> {
+        self.skip_whitespace();
+        let key = self.parse_key()?;
+        self.skip_whitespace();
+        if !self.consume_specific(Token::Colon) {
+            return Err(self.expected("':'", "Expected ':'"));
         }
-        Ok(JsonValue::Null)
+        self.skip_whitespace();
+        Ok(key)
     }
 
-    fn parse_string(&mut self) -> Result<JsonValue, ParserError> {
-        let value = self.consume_and_unescape_string().unwrap();
-        Ok(JsonValue::String(value))
+    // Like `parse_member_key`, along with where the key starts.
+    fn parse_member_key_at(&mut self) -> Result<(Position, String), ParserError> {
+        self.skip_whitespace();
+        let at = self.position();
+        Ok((at, self.parse_member_key()?))
+    }
+
+    // JSON5 identifiers contain no escape sequences, so they are unescaped
+    // like the contents of a string.
+    fn parse_key(&mut self) -> Result<String, ParserError> {
+        if self.eof() {
+            return Err(ParserError::UnexpectedEof { expected: "string" });
+        }
+        let mut tokenizer = self.tokenizer();
+        let raw = tokenizer.next_key().map_err(|err| self.token_error(err, true))?;
+        let end = tokenizer.offset();
+        let key = self.unescape(raw, self.cursor + 1)?;
+        self.advance(end - self.cursor);
+        Ok(key)
     }
 
-    fn parse_number(&mut self) -> Result<JsonValue, ParserError> {
+    fn invalid_number(&self, start: usize) -> ParserError {
+        let raw = self.input[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+            .collect();
+        ParserError::InvalidNumberLiteral { raw }
+    }
+
+    // Converts a number token, whose grammar the tokenizer has checked.
+    fn number_value(&self, raw: &str) -> Result<JsonValue, ParserError> {
+        let negative = raw.starts_with('-');
+        let unsigned = raw.strip_prefix(['-', '+']).unwrap_or(raw);
+        match unsigned {
+            "Infinity" if negative => return Ok(JsonValue::Float(f64::NEG_INFINITY)),
+            "Infinity" => return Ok(JsonValue::Float(f64::INFINITY)),
+            "NaN" => return Ok(JsonValue::Float(f64::NAN)),
+            _ => {}
+        }
+        if let Some(hex) = unsigned.strip_prefix("0x").or_else(|| unsigned.strip_prefix("0X")) {
+            let sign = if negative { "-" } else { "" };
+            let digits = format!("{}{}", sign, hex);
+            return match (i64::from_str_radix(&digits, 16), u64::from_str_radix(&digits, 16)) {
+                (Ok(value), _) => Ok(JsonValue::Number(value)),
+                (_, Ok(value)) => Ok(JsonValue::Number64(value)),
+                _ => Err(ParserError::NumberOverflow(raw.to_string())),
+            };
+        }
+        if unsigned.contains(['.', 'e', 'E']) {
+            return self.float_literal(raw);
+        }
+        self.integer_value(raw, unsigned, negative)
+    }
+
+    // Converts the integer `digits` of the literal `raw` into a `Number`,
+    // or a `Number64` if they only fit in a u64, reporting `raw` if they
+    // fit in neither.
+    fn integer_value(&self, raw: &str, digits: &str, negative: bool) -> Result<JsonValue, ParserError> {
         let mut value: i64 = 0;
-        while !self.eof() {
-            let ch = self.peek();
-            if !(ch as u8 > b'0' && ch as u8 <= b'9') {
-                break;
-            }
-            value *= 10;
-            value += (ch as u8 - b'0') as i64;
-            self.cursor += 1;
+        for digit in digits.bytes().map(|b| (b - b'0') as i64) {
+            // Accumulate negative numbers below zero so that i64::MIN,
+            // whose magnitude does not fit in an i64, can be represented.
+            let next = value.checked_mul(10).and_then(|value| {
+                if negative {
+                    value.checked_sub(digit)
+                } else {
+                    value.checked_add(digit)
+                }
+            });
+            value = match next {
+                Some(next) => next,
+                None => {
+                    if let (false, Ok(value)) = (negative, digits.parse()) {
+                        return Ok(JsonValue::Number64(value));
+                    }
+                    #[cfg(feature = "bignum")]
+                    if self.config.bignum {
+                        return Ok(JsonValue::BigNum(raw.parse().unwrap()));
+                    }
+                    return Err(ParserError::NumberOverflow(raw.to_string()));
+                }
+            };
         }
         Ok(JsonValue::Number(value))
     }
+
+    // Converts a literal that has a fraction or an exponent.
+    fn float_literal(&self, raw: &str) -> Result<JsonValue, ParserError> {
+        #[cfg(feature = "bignum")]
+        if self.config.bignum {
+            return crate::bignum::float_or_bignum(raw);
+        }
+        float_value(raw).map(JsonValue::Float)
+    }
+}
+
+// Converts a literal that has already been checked against the number
+// grammar. Exponents too large for an f64 are reported rather than read
+// as infinity, which JSON cannot represent.
+pub(crate) fn float_value(raw: &str) -> Result<f64, ParserError> {
+    let value: f64 = raw.parse().unwrap();
+    if value.is_infinite() {
+        return Err(ParserError::NumberOverflow(raw.to_string()));
+    }
+    Ok(value)
 }
 
 #[test]
 fn test_parse_json_obj_with_number() {
     let json_input = "{ \"foo\": 123 \n, \"bar\":    456 }".to_string();
-    let mut parser = JsonParser {
-        input: json_input, 
-        cursor: 0,
-    };
-    let expected_value = JsonValue::Object(HashMap::from([("foo".to_string(), JsonValue::Number(123)), ("bar".to_string(), JsonValue::Number(456))]));
-    match parser.parse() {
+    let expected_value = JsonValue::Object(JsonObject::from([("foo".to_string(), JsonValue::Number(123)), ("bar".to_string(), JsonValue::Number(456))]));
+    match crate::parse_str(&json_input) {
         Ok(value) => assert_eq!(value, expected_value),
-        Err(_) => assert!(false),
+        Err(err) => panic!("{:?}", err),
     }
 }
 
 #[test]
 fn test_parse_json_obj_with_string() {
     let json_input = "{ \"foo\": \"abcde\" }".to_string();
-    let mut parser = JsonParser {
-        input: json_input, 
-        cursor: 0,
-    };
-    let expected_value = JsonValue::Object(HashMap::from([("foo".to_string(), JsonValue::String("abcde".to_string()))]));
-    match parser.parse() {
+    let expected_value = JsonValue::Object(JsonObject::from([("foo".to_string(), JsonValue::String("abcde".into()))]));
+    match crate::parse_str(&json_input) {
         Ok(value) => assert_eq!(value, expected_value),
-        Err(_) => assert!(false),
+        Err(err) => panic!("{:?}", err),
     };
 }
 
 #[test]
 fn test_parse_json_obj_with_bool() {
     let json_input = "{ \"foo\": false }".to_string();
-    let mut parser = JsonParser {
-        input: json_input, 
-        cursor: 0,
-    };
-    let expected_value = JsonValue::Object(HashMap::from([("foo".to_string(), JsonValue::Bool(false))]));
-    match parser.parse() {
+    let expected_value = JsonValue::Object(JsonObject::from([("foo".to_string(), JsonValue::Bool(false))]));
+    match crate::parse_str(&json_input) {
         Ok(value) => assert_eq!(value, expected_value),
-        Err(_) => assert!(false),
+        Err(err) => panic!("{:?}", err),
     };
 }
 
 #[test]
 fn test_parse_json_obj_with_null() {
     let json_input = "{ \"foo\": null }".to_string();
-    let mut parser = JsonParser {
-        input: json_input, 
-        cursor: 0,
-    };
-    let expected_value = JsonValue::Object(HashMap::from([("foo".to_string(), JsonValue::Null)]));
-    match parser.parse() {
+    let expected_value = JsonValue::Object(JsonObject::from([("foo".to_string(), JsonValue::Null)]));
+    match crate::parse_str(&json_input) {
         Ok(value) => assert_eq!(value, expected_value),
-        Err(_) => assert!(false),
+        Err(err) => panic!("{:?}", err),
     };
 }
 
@@ -381,35 +1111,841 @@ fn test_parse_json_obj_with_array() {
 	false
 ]
 ".to_string();
-    let mut parser = JsonParser {
-        input: json_input, 
-        cursor: 0,
-    };
     let expected_value = JsonValue::Array(vec![
-        JsonValue::Object(HashMap::from([("foo".to_string(), JsonValue::Null)])),
-        JsonValue::Object(HashMap::from([("bar".to_string(), JsonValue::Number(123))])),
-        JsonValue::Object(HashMap::from([("baz".to_string(), JsonValue::String("abcde".to_string()))])),
+        JsonValue::Object(JsonObject::from([("foo".to_string(), JsonValue::Null)])),
+        JsonValue::Object(JsonObject::from([("bar".to_string(), JsonValue::Number(123))])),
+        JsonValue::Object(JsonObject::from([("baz".to_string(), JsonValue::String("abcde".into()))])),
         JsonValue::Number(345),
-        JsonValue::String("efgh".to_string()),
+        JsonValue::String("efgh".into()),
         JsonValue::Null,
         JsonValue::Bool(false),
     ]);
-    match parser.parse() {
+    match crate::parse_str(&json_input) {
         Ok(value) => assert_eq!(value, expected_value),
-        Err(_) => assert!(false),
+        Err(err) => panic!("{:?}", err),
     };
 }
 
 #[test]
 fn test_parse_json_obj_with_nested_string() {
     let json_input = "{ \"foo\": \"abcde and a nested string \"nested\" right in the middle of the sentence.\" }".to_string();
-    let mut parser = JsonParser {
-        input: json_input, 
-        cursor: 0,
-    };
-    let expected_value = JsonValue::Object(HashMap::from([("foo".to_string(), JsonValue::String("abcde and a nested string \"nested\" right in the middle of the sentence.".to_string()))]));
-    match parser.parse() {
+    let expected_value = JsonValue::Object(JsonObject::from([("foo".to_string(), JsonValue::String("abcde and a nested string \"nested\" right in the middle of the sentence.".into()))]));
+    let config = JsonParserConfig { mode: ParseMode::Lenient, ..JsonParserConfig::default() };
+    match JsonParser::with_config(json_input.as_str(), config).parse() {
         Ok(value) => assert_eq!(value, expected_value),
-        Err(_) => assert!(false),
+        Err(err) => panic!("{:?}", err),
     };
 }
+
+#[test]
+fn test_parse_number() {
+    let cases = [
+        ("0", 0),
+        ("7", 7),
+        ("10", 10),
+        ("100", 100),
+        ("1020304", 1020304),
+        ("-5", -5),
+        ("-10", -10),
+        ("-0", 0),
+        ("9223372036854775807", i64::MAX),
+        ("-9223372036854775808", i64::MIN),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), Ok(JsonValue::Number(expected)), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_number_without_digits() {
+    assert!(crate::parse_str("-").is_err());
+}
+
+#[test]
+fn test_parse_empty_object() {
+    for input in ["{}", "{ }", " {\n\t} "] {
+        assert_eq!(crate::parse_str(input), Ok(JsonValue::Object(JsonObject::new())), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_parse_json_obj_with_nested_empty_object() {
+    let expected_value = JsonValue::Object(JsonObject::from([
+        ("a".to_string(), JsonValue::Number(1)),
+        ("b".to_string(), JsonValue::Object(JsonObject::new())),
+    ]));
+    assert_eq!(crate::parse_str("{\"a\":1, \"b\":{}}"), Ok(expected_value));
+}
+
+#[test]
+fn test_parse_json_obj_with_multi_byte_characters() {
+    let json_input = "{ \"καλημέρα\": \"日本語\", \"ok\": true }".to_string();
+    let expected_value = JsonValue::Object(JsonObject::from([
+        ("καλημέρα".to_string(), JsonValue::String("日本語".into())),
+        ("ok".to_string(), JsonValue::Bool(true)),
+    ]));
+    assert_eq!(crate::parse_str(&json_input), Ok(expected_value));
+}
+
+#[test]
+fn test_cursor_is_a_byte_offset_after_multi_byte_characters() {
+    // Cyrillic letters take two bytes each and 𝄞 takes four, so a cursor
+    // counting characters would land inside the literals that follow.
+    let cases = [
+        ("{\"ключ\": true}", JsonValue::Bool(true)),
+        ("{\"ключ\": false}", JsonValue::Bool(false)),
+        ("{\"𝄞𝄞\": null}", JsonValue::Null),
+        ("{\"é\": \"ü\", \"ключ\": true}", JsonValue::Bool(true)),
+    ];
+    for (input, expected) in cases {
+        let mut parser = JsonParser::new(input);
+        let value = parser.parse().unwrap();
+        let map = value.as_object().unwrap();
+        assert_eq!(map.get("ключ").or(map.get("𝄞𝄞")), Some(&expected), "input: {:?}", input);
+        assert_eq!(parser.cursor, input.len(), "input: {:?}", input);
+        assert_eq!(parser.column, input.chars().count() + 1, "input: {:?}", input);
+    }
+    let mut parser = JsonParser::new("[\"ключ\", tru]");
+    assert!(parser.parse().is_err());
+    assert_eq!(parser.cursor, "[\"ключ\", ".len());
+}
+
+#[test]
+fn test_parse_long_string_allocates_once() {
+    let text = "abcdefgh".repeat(128 * 1024);
+    // Parsing a number counts the allocations that every parse makes.
+    let baseline = crate::convert::count_allocations(|| drop(crate::parse_str("0")));
+    for (input, expected) in [
+        (format!("\"{}\"", text), text.clone()),
+        (format!("\"{}\\n\\u00e9\\\"\"", text), format!("{}\né\"", text)),
+    ] {
+        let mut value = None;
+        let allocations = crate::convert::count_allocations(|| value = Some(crate::parse_str(&input)));
+        assert_eq!(value.unwrap(), Ok(JsonValue::String(expected.into())));
+        assert_eq!(allocations, baseline + 1);
+    }
+    // Strings with an unescaped quote inside, which lenient parsing keeps,
+    // still parse once the hint turns out to be short.
+    let config = JsonParserConfig { mode: ParseMode::Lenient, ..JsonParserConfig::default() };
+    assert_eq!(JsonParser::with_config(r#"["a"b"]"#, config).parse(), Ok(JsonValue::Array(vec![JsonValue::from("a\"b")])));
+}
+
+#[test]
+fn test_parse_string_with_escape_sequences() {
+    let cases = [
+        (r#""\"""#, "\""),
+        (r#""\\""#, "\\"),
+        (r#""\/""#, "/"),
+        (r#""\b""#, "\u{8}"),
+        (r#""\f""#, "\u{c}"),
+        (r#""\n""#, "\n"),
+        (r#""\r""#, "\r"),
+        (r#""\t""#, "\t"),
+        (r#""line one\nline two\ttabbed \"quoted\" C:\\path\/to""#, "line one\nline two\ttabbed \"quoted\" C:\\path/to"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), Ok(JsonValue::String(expected.to_string().into())), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_json_obj_with_escaped_strings() {
+    let json_input = r#"{ "a\tb": ["\r\n", { "c": "\"x\"" }] }"#.to_string();
+    let expected_value = JsonValue::Object(JsonObject::from([(
+        "a\tb".to_string(),
+        JsonValue::Array(vec![
+            JsonValue::String("\r\n".into()),
+            JsonValue::Object(JsonObject::from([("c".to_string(), JsonValue::String("\"x\"".into()))])),
+        ]),
+    )]));
+    assert_eq!(crate::parse_str(&json_input), Ok(expected_value));
+}
+
+#[test]
+fn test_parse_string_with_invalid_escape_sequence() {
+    assert_eq!(crate::parse_str(r#""\q""#), Err(ParserError::InvalidEscapeSequence { char: 'q', line: 1, column: 2, at_byte: 1 }));
+}
+
+#[test]
+fn test_parse_string_with_unicode_escapes() {
+    let cases = [
+        (r#""\u0041""#, "A"),
+        (r#""\u00e9\u00E9""#, "éé"),
+        (r#""\u4e2d\u6587""#, "中文"),
+        (r#""\uD83D\uDE00""#, "😀"),
+        (r#""\uD800\uDFFF""#, "\u{103FF}"),
+        (r#""abc \u00fcber \uD83D\uDE00 done""#, "abc über 😀 done"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), Ok(JsonValue::String(expected.to_string().into())), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_string_with_invalid_unicode_escapes() {
+    let cases = [
+        (r#""\uD83D""#, ParserError::InvalidSurrogatePair),
+        (r#""\uD83D abc""#, ParserError::InvalidSurrogatePair),
+        (r#""\uD83DA""#, ParserError::InvalidSurrogatePair),
+        (r#""\uDE00""#, ParserError::InvalidSurrogatePair),
+        (r#""\u12G4""#, ParserError::InvalidEscapeSequence { char: 'u', line: 1, column: 2, at_byte: 1 }),
+        (r#""\u12""#, ParserError::InvalidEscapeSequence { char: 'u', line: 1, column: 2, at_byte: 1 }),
+        (r#""\uD83D\u12""#, ParserError::InvalidEscapeSequence { char: 'u', line: 1, column: 8, at_byte: 7 }),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), Err(expected), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_float() {
+    let cases = [
+        ("0.0", 0.0),
+        ("-1.5", -1.5),
+        ("2.75", 2.75),
+        ("1e10", 1e10),
+        ("1.5E-3", 1.5e-3),
+        ("1.0e+2", 100.0),
+        ("-0.25e1", -2.5),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), Ok(JsonValue::Float(expected)), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_scientific_notation() {
+    let cases = [
+        ("1e0", 1.0),
+        ("1e100", 1e100),
+        ("1e308", 1e308),
+        ("1.5e-3", 0.0015),
+        ("6.022E23", 6.022e23),
+        ("2.5E-3", 0.0025),
+        ("1.5e10", 15_000_000_000.0),
+        ("-1E+2", -100.0),
+        ("0e5", 0.0),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), Ok(JsonValue::Float(expected)), "input: {}", input);
+    }
+    for input in ["1e309", "-1e309", "1.7976931348623159e308"] {
+        assert_eq!(crate::parse_str(input), Err(ParserError::NumberOverflow(input.to_string())), "input: {}", input);
+    }
+    for input in ["1e", "1e+", "1E-", "1.5ee3", "1e1.5"] {
+        assert!(crate::parse_str(input).is_err(), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_integer_is_not_float() {
+    let value = crate::parse_str("[3, 3.0]").unwrap();
+    assert!(matches!(value.as_array().unwrap()[..], [JsonValue::Number(3), JsonValue::Float(f)] if f == 3.0));
+}
+
+#[test]
+fn test_integers_equal_floats_with_the_same_value() {
+    let cases = [
+        (JsonValue::Number(3), JsonValue::Float(3.0), true),
+        (JsonValue::Number(0), JsonValue::Float(-0.0), true),
+        (JsonValue::Number(-7), JsonValue::Float(-7.0), true),
+        (JsonValue::Number(3), JsonValue::Float(3.5), false),
+        (JsonValue::Number(i64::MIN), JsonValue::Float(-9_223_372_036_854_775_808.0), true),
+        // The literal rounds to 2^63, which is one more than `i64::MAX`.
+        (JsonValue::Number(i64::MAX), JsonValue::Float(9_223_372_036_854_775_807.0), false),
+        (JsonValue::Number64(1 << 63), JsonValue::Float(9_223_372_036_854_775_807.0), true),
+        (JsonValue::Number64(5), JsonValue::Float(5.0), true),
+        (JsonValue::Number64(u64::MAX), JsonValue::Float(18_446_744_073_709_551_615.0), false),
+        (JsonValue::Number(9_007_199_254_740_993), JsonValue::Float(9_007_199_254_740_993.0), false),
+        (JsonValue::Number(1), JsonValue::Float(f64::NAN), false),
+        (JsonValue::Number(i64::MAX), JsonValue::Float(f64::INFINITY), false),
+        (JsonValue::Float(0.0), JsonValue::Float(-0.0), true),
+    ];
+    for (a, b, equal) in cases {
+        assert_eq!(a == b, equal, "{:?} and {:?}", a, b);
+        assert_eq!(b == a, equal, "{:?} and {:?}", b, a);
+    }
+    assert_eq!(crate::parse_str(r#"{"a": [1, 2.0]}"#), crate::parse_str(r#"{"a": [1.0, 2]}"#));
+}
+
+#[test]
+fn test_nan_equals_nan() {
+    // Reflexive rather than IEEE 754 equality, so that `Eq` holds.
+    let nan = JsonValue::Float(f64::NAN);
+    assert_eq!(nan, nan.clone());
+    assert_eq!(nan, JsonValue::Float(-f64::NAN));
+    assert_eq!(JsonValue::Array(vec![nan.clone()]), JsonValue::Array(vec![JsonValue::Float(f64::NAN)]));
+    for other in [JsonValue::Float(f64::INFINITY), JsonValue::Float(0.0), JsonValue::Number(0), JsonValue::Number64(u64::MAX), JsonValue::Null] {
+        assert_ne!(nan, other);
+        assert_ne!(other, nan);
+    }
+}
+
+#[test]
+fn test_parse_float_without_digits() {
+    for input in ["1.", "1.e5", "1e", "1e+", "-.5"] {
+        assert!(crate::parse_str(input).is_err(), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_number_overflow() {
+    let cases = [
+        ("9223372036854775807", Ok(JsonValue::Number(i64::MAX))),
+        ("-9223372036854775808", Ok(JsonValue::Number(i64::MIN))),
+        ("9223372036854775808", Ok(JsonValue::Number64(9223372036854775808))),
+        ("18446744073709551615", Ok(JsonValue::Number64(u64::MAX))),
+        ("18446744073709551616", Err(ParserError::NumberOverflow("18446744073709551616".to_string()))),
+        ("-9223372036854775809", Err(ParserError::NumberOverflow("-9223372036854775809".to_string()))),
+        ("1e400", Err(ParserError::NumberOverflow("1e400".to_string()))),
+        ("-2.5E+309", Err(ParserError::NumberOverflow("-2.5E+309".to_string()))),
+        ("123456789012345678901234567890", Err(ParserError::NumberOverflow("123456789012345678901234567890".to_string()))),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn test_default_is_null() {
+    assert_eq!(JsonValue::default(), JsonValue::Null);
+    let mut values = vec![JsonValue::Bool(true)];
+    values.resize_with(5, Default::default);
+    assert_eq!(values[1..], [JsonValue::Null, JsonValue::Null, JsonValue::Null, JsonValue::Null]);
+    let mut map = std::collections::HashMap::<String, JsonValue>::new();
+    assert_eq!(*map.entry("k".into()).or_default(), JsonValue::Null);
+    assert_eq!(map.get("k"), Some(&JsonValue::Null));
+
+    #[derive(Default)]
+    struct Document {
+        body: JsonValue,
+    }
+    assert_eq!(Document::default().body, JsonValue::Null);
+}
+
+#[test]
+fn test_number64() {
+    let value = crate::parse_str(r#"{"id": 9999999999999999999}"#).unwrap();
+    assert_eq!(value.as_object().unwrap().get("id"), Some(&JsonValue::Number64(9999999999999999999)));
+    assert_eq!(value.to_string(), r#"{"id":9999999999999999999}"#);
+    assert_eq!(JsonValue::Number(5), JsonValue::Number64(5));
+    assert_eq!(JsonValue::Number64(5), JsonValue::Number(5));
+    assert_ne!(JsonValue::Number(-1), JsonValue::Number64(u64::MAX));
+    assert_eq!(JsonValue::Number64(5).as_i64(), Some(5));
+    assert_eq!(JsonValue::Number64(u64::MAX).as_i64(), None);
+}
+
+#[test]
+fn test_syntax_error_line_and_column() {
+    let cases = [
+        ("{\n  \"a\": 1\n  \"b\": 2\n}", 3, 3, "Expected ',' or '}'"),
+        ("\n\n   x", 3, 4, "Expected a value"),
+        ("{}\n  }", 2, 3, "Expected the end of input"),
+        ("\"é\" }", 1, 5, "Expected the end of input"),
+        ("{\"a\", 1}", 1, 5, "Expected ':'"),
+    ];
+    for (input, line, column, message) in cases {
+        assert_eq!(
+            crate::parse_str(input),
+            Err(ParserError::SyntaxError { line, column, message }),
+            "input: {:?}", input
+        );
+    }
+}
+
+#[test]
+fn test_parser_tracks_line_and_column() {
+    let mut parser = JsonParser::new("[\n  1,\n  \"ab\"\n]");
+    parser.parse().unwrap();
+    assert_eq!((parser.line, parser.column), (4, 2));
+}
+
+#[test]
+fn test_parse_malformed_input_returns_error() {
+    let cases = [
+        ("{\"key\": }", 1, 9, "Expected a value"),
+        ("{\"key\": 1,}", 1, 11, "'}' in an invalid position"),
+        ("{1: 2}", 1, 2, "Expected '\"'"),
+        ("{\"a\": [}", 1, 8, "Expected a value"),
+        ("[true,]", 1, 7, "Expected a value"),
+        ("[1 2]", 1, 4, "Expected ',' or ']'"),
+        ("[nul]", 1, 2, "Expected null"),
+        ("[tru]", 1, 2, "Expected either true or false"),
+        ("[\"a\tb\"]", 1, 4, "Unescaped control character in string"),
+        ("\"line\nbreak\"", 1, 6, "Unescaped control character in string"),
+    ];
+    for (input, line, column, message) in cases {
+        assert_eq!(
+            crate::parse_str(input),
+            Err(ParserError::SyntaxError { line, column, message }),
+            "input: {:?}", input
+        );
+    }
+}
+
+#[test]
+fn test_parse_empty_array() {
+    for input in ["[]", "[ ]", "[\n]", "[[], [ ]]"] {
+        assert!(crate::parse_str(input).is_ok(), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_parse_errors_with_structured_data() {
+    let max_depth = JsonParserConfig::default().max_depth;
+    let nested_too_deep = "[".repeat(max_depth + 1) + &"]".repeat(max_depth + 1);
+    let cases = [
+        ("\"abc", ParserError::UnterminatedString { line: 1, column: 1, at_byte: 0 }),
+        ("[1,\n \"ab", ParserError::UnterminatedString { line: 2, column: 2, at_byte: 5 }),
+        ("\"a\\xb\"", ParserError::InvalidEscapeSequence { char: 'x', line: 1, column: 3, at_byte: 2 }),
+        ("[\"é\",\n\"\\x\"]", ParserError::InvalidEscapeSequence { char: 'x', line: 2, column: 2, at_byte: 8 }),
+        ("", ParserError::UnexpectedEof { expected: "value" }),
+        ("[1,", ParserError::UnexpectedEof { expected: "value" }),
+        ("[", ParserError::UnexpectedEof { expected: "value" }),
+        ("[[]", ParserError::UnexpectedEof { expected: "',' or ']'" }),
+        ("{\"key\" 1", ParserError::SyntaxError { line: 1, column: 8, message: "Expected ':'" }),
+        ("[1", ParserError::UnexpectedEof { expected: "',' or ']'" }),
+        ("{", ParserError::UnexpectedEof { expected: "string" }),
+        ("{\"a\"", ParserError::UnexpectedEof { expected: "':'" }),
+        ("{\"a\": 1", ParserError::UnexpectedEof { expected: "',' or '}'" }),
+        (nested_too_deep.as_str(), ParserError::DepthLimitExceeded { limit: max_depth }),
+        ("-", ParserError::InvalidNumberLiteral { raw: "-".to_string() }),
+        ("[1.e5]", ParserError::InvalidNumberLiteral { raw: "1.e5".to_string() }),
+        ("2e+", ParserError::InvalidNumberLiteral { raw: "2e+".to_string() }),
+        ("012", ParserError::InvalidNumberLiteral { raw: "012".to_string() }),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(crate::parse_str(input), Err(expected), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_parse_nesting_up_to_depth_limit() {
+    let max_depth = JsonParserConfig::default().max_depth;
+    let input = "[".repeat(max_depth) + &"]".repeat(max_depth);
+    assert!(crate::parse_str(&input).is_ok());
+}
+
+#[test]
+fn test_depth_limit_counts_siblings_separately() {
+    assert_eq!(JsonParserConfig::default().max_depth, 512);
+    let config = JsonParserConfig { max_depth: 3, ..JsonParserConfig::default() };
+    let parse = |input: &str| JsonParser::with_config(input, config.clone()).parse();
+    let cases = [
+        ("[[]]", true),
+        ("[[[]]]", true),
+        ("[[[[]]]]", false),
+        (r#"{"a": {"b": []}}"#, true),
+        (r#"{"a": {"b": [{}]}}"#, false),
+        // Closing a container gives its depth back to the siblings after it.
+        ("[[[]], [[]], [[]], {\"a\": []}]", true),
+        (r#"[{"a": [], "b": {"c": 1}}, [{}]]"#, true),
+        ("[[[]], [[[]]]]", false),
+    ];
+    for (input, ok) in cases {
+        if ok {
+            assert!(parse(input).is_ok(), "input: {:?}", input);
+        } else {
+            assert_eq!(parse(input), Err(ParserError::DepthLimitExceeded { limit: 3 }), "input: {:?}", input);
+        }
+    }
+    let mut parser = JsonParser::with_config("[[[]]] [[[]]] [[[]]]", config.clone());
+    let values: Vec<_> = parser.parse_many().collect();
+    assert_eq!(values.len(), 3);
+    assert!(values.iter().all(Result::is_ok));
+}
+
+#[test]
+fn test_parse_deep_nesting_without_depth_limit() {
+    let depth = 100_000;
+    let config = JsonParserConfig { max_depth: usize::MAX, ..JsonParserConfig::default() };
+
+    let input = "[".repeat(depth) + &"]".repeat(depth);
+    let value = JsonParser::with_config(input, config.clone()).parse().unwrap();
+    let mut levels = 0;
+    let mut inner = &value;
+    while let JsonValue::Array(array) = inner {
+        levels += 1;
+        match array.first() {
+            Some(next) => inner = next,
+            None => break,
+        }
+    }
+    assert_eq!(levels, depth);
+    // Dropping the value does not recurse once per level.
+    drop(value);
+
+    let input = "{\"a\": ".repeat(depth) + "null" + &"}".repeat(depth);
+    let value = JsonParser::with_config(input, config).parse().unwrap();
+    let mut levels = 0;
+    let mut inner = &value;
+    while let JsonValue::Object(members) = inner {
+        levels += 1;
+        inner = members.get("a").unwrap();
+    }
+    assert_eq!(levels, depth);
+    assert_eq!(inner, &JsonValue::Null);
+}
+
+#[test]
+fn test_parser_config_limits() {
+    let config = JsonParserConfig {
+        max_depth: 3,
+        max_string_length: 4,
+        max_array_length: 3,
+        max_object_size: 2,
+        max_total_values: 7,
+        ..JsonParserConfig::default()
+    };
+    let cases = [
+        ("[[[]]]", Ok(())),
+        ("[[[[]]]]", Err(ParserError::DepthLimitExceeded { limit: 3 })),
+        ("{\"a\": {\"b\": {}}}", Ok(())),
+        ("{\"a\": {\"b\": {\"c\": {}}}}", Err(ParserError::DepthLimitExceeded { limit: 3 })),
+        ("\"abcd\"", Ok(())),
+        ("\"abcde\"", Err(ParserError::StringLengthExceeded { limit: 4 })),
+        ("\"\\u00e9\\u00e9\"", Ok(())),
+        ("\"ééé\"", Err(ParserError::StringLengthExceeded { limit: 4 })),
+        ("{\"abcde\": 1}", Err(ParserError::StringLengthExceeded { limit: 4 })),
+        ("[1, 2, 3]", Ok(())),
+        ("[1, 2, 3, 4]", Err(ParserError::ArrayLengthExceeded { limit: 3 })),
+        ("{\"a\": 1, \"b\": 2}", Ok(())),
+        ("{\"a\": 1, \"b\": 2, \"c\": 3}", Err(ParserError::ObjectSizeExceeded { limit: 2 })),
+        ("[[1, 2], [3, 4]]", Ok(())),
+        ("[[1, 2], [3, 4], 5]", Err(ParserError::TotalValuesExceeded { limit: 7 })),
+    ];
+    for (input, expected) in cases {
+        let mut parser = JsonParser::with_config(input, config.clone());
+        assert_eq!(parser.parse().map(|_| ()), expected, "input: {:?}", input);
+    }
+}
+
+#[cfg(test)]
+fn parser_with_policy(input: &str, duplicate_key_policy: DuplicateKeyPolicy) -> JsonParser {
+    JsonParser::with_config(input, JsonParserConfig { duplicate_key_policy, ..JsonParserConfig::default() })
+}
+
+#[test]
+fn test_duplicate_key_policy() {
+    let input = r#"{"a":1,"a":2}"#;
+    let cases = [
+        (DuplicateKeyPolicy::LastWins, Ok(r#"{"a": 2}"#)),
+        (DuplicateKeyPolicy::FirstWins, Ok(r#"{"a": 1}"#)),
+        (DuplicateKeyPolicy::Error, Err(ParserError::DuplicateKey { key: "a".to_string(), line: 1, column: 8, first_at: 1, second_at: 7 })),
+        (DuplicateKeyPolicy::CollectAll, Ok(r#"{"a": 2}"#)),
+    ];
+    for (policy, expected) in cases {
+        let mut parser = parser_with_policy(input, policy);
+        assert_eq!(parser.parse(), expected.map(|expected| crate::parse_str(expected).unwrap()), "policy: {:?}", policy);
+        let collected = match policy {
+            DuplicateKeyPolicy::CollectAll => vec![DuplicateKeys { pointer: "/a".to_string(), values: vec![JsonValue::Number(1), JsonValue::Number(2)] }],
+            _ => vec![],
+        };
+        assert_eq!(parser.duplicate_keys(), collected, "policy: {:?}", policy);
+    }
+    assert_eq!(JsonParserConfig::default().duplicate_key_policy, DuplicateKeyPolicy::LastWins);
+}
+
+#[test]
+fn test_duplicate_key_policy_in_nested_objects() {
+    let input = r#"[0, {"x": {"k~/": 1, "b": true, "k~/": 2, "k~/": 3}, "y": [{"d": null, "d": {}}], "x": 4}]"#;
+    let mut parser = parser_with_policy(input, DuplicateKeyPolicy::CollectAll);
+    assert_eq!(parser.parse(), crate::parse_str(r#"[0, {"x": 4, "y": [{"d": {}}]}]"#));
+    let expected = [
+        ("/1/x/k~0~1", "[1, 2, 3]"),
+        ("/1/y/0/d", "[null, {}]"),
+        ("/1/x", r#"[{"k~/": 3, "b": true}, 4]"#),
+    ]
+    .map(|(pointer, values)| DuplicateKeys {
+        pointer: pointer.to_string(),
+        values: crate::parse_str(values).unwrap().into_array().unwrap(),
+    });
+    assert_eq!(parser.duplicate_keys(), expected);
+
+    let mut parser = parser_with_policy(input, DuplicateKeyPolicy::FirstWins);
+    assert_eq!(parser.parse(), crate::parse_str(r#"[0, {"x": {"k~/": 1, "b": true}, "y": [{"d": null}]}]"#));
+    let mut parser = parser_with_policy(input, DuplicateKeyPolicy::Error);
+    let error = parser.parse().unwrap_err();
+    assert_eq!(error, ParserError::DuplicateKey { key: "k~/".to_string(), line: 1, column: 33, first_at: 11, second_at: 32 });
+    assert_eq!(error.to_string(), r#"duplicate key "k~/" at line 1, column 33"#);
+    // Keys only clash within one object.
+    assert!(parser_with_policy(r#"[{"a": 1}, {"a": {"a": 2}}]"#, DuplicateKeyPolicy::Error).parse().is_ok());
+
+    // Each value from parse_many has its own duplicates.
+    let mut parser = parser_with_policy(r#"{"a": 1, "a": 2} {"b": 1}"#, DuplicateKeyPolicy::CollectAll);
+    let mut values = parser.parse_many();
+    assert!(values.next().unwrap().is_ok());
+    drop(values);
+    assert_eq!(parser.duplicate_keys().len(), 1);
+    assert!(parser.parse_many().next().unwrap().is_ok());
+    assert_eq!(parser.duplicate_keys(), []);
+}
+
+#[test]
+fn test_byte_order_mark() {
+    let expected = crate::parse_str(r#"{"k": 1}"#).unwrap();
+    assert_eq!(crate::parse_str("\u{FEFF}{\"k\":1}"), Ok(expected.clone()));
+    assert_eq!(crate::parse_from_bytes(b"\xef\xbb\xbf{\"k\":1}"), Ok(expected));
+    assert!(JsonParser::validate("\u{feff}[1, 2]").is_ok());
+    let mut parser = JsonParser::new("\u{feff}1 2");
+    assert_eq!(parser.parse_many().collect::<Vec<_>>(), [Ok(JsonValue::Number(1)), Ok(JsonValue::Number(2))]);
+
+    for input in ["\u{feff}", "\u{feff}\u{feff}1", "[\u{feff}1]"] {
+        assert!(crate::parse_str(input).is_err(), "input: {:?}", input);
+    }
+    // The column does not count the byte order mark.
+    let error = ParserError::SyntaxError { line: 1, column: 2, message: "Expected a value" };
+    assert_eq!(crate::parse_str("\u{feff} x"), Err(error));
+
+    let config = JsonParserConfig { reject_bom: true, ..JsonParserConfig::default() };
+    let error = ParserError::SyntaxError { line: 1, column: 1, message: "Expected a value" };
+    assert_eq!(JsonParser::with_config("\u{feff}{}", config.clone()).parse(), Err(error));
+    assert!(JsonParser::with_config("{}", config).parse().is_ok());
+}
+
+#[test]
+fn test_lenient_mode() {
+    let lenient = JsonParserConfig {
+        mode: ParseMode::Lenient,
+        ..JsonParserConfig::default()
+    };
+    let cases = [
+        ("[1, 2,]", "[1, 2]"),
+        ("[[],\n]", "[[]]"),
+        ("{\"a\": 1,}", "{\"a\": 1}"),
+        ("{\"a\": [true,], }", "{\"a\": [true]}"),
+        ("// leading\n[1, // one\n 2 // two\n]", "[1, 2]"),
+        ("{\"a\": \"b\" // trailing\n}", "{\"a\": \"b\"}"),
+        ("null // no newline at the end", "null"),
+    ];
+    for (input, expected) in cases {
+        let mut parser = JsonParser::with_config(input, lenient.clone());
+        assert_eq!(parser.parse(), crate::parse_str(expected), "input: {:?}", input);
+        assert!(crate::parse_str(input).is_err(), "strict input: {:?}", input);
+    }
+    let input = "{\"url\": \"http://example.com\"}";
+    let mut parser = JsonParser::with_config(input, lenient);
+    assert_eq!(parser.parse(), crate::parse_str(input));
+}
+
+#[test]
+fn test_lenient_mode_still_rejects_invalid_json() {
+    let lenient = JsonParserConfig {
+        mode: ParseMode::Lenient,
+        ..JsonParserConfig::default()
+    };
+    for input in ["[,]", "[1,,]", "{,}", "[1 2]", "/ comment\n1"] {
+        let mut parser = JsonParser::with_config(input, lenient.clone());
+        assert!(parser.parse().is_err(), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_json5_mode() {
+    let json5 = JsonParserConfig {
+        mode: ParseMode::Json5,
+        ..JsonParserConfig::default()
+    };
+    let cases = [
+        ("{unquoted: 1, $dollar_1: 2}", r#"{"unquoted": 1, "$dollar_1": 2}"#),
+        ("{'single': 'quoted \"string\"'}", r#"{"single": "quoted \"string\""}"#),
+        ("'it\\'s'", r#""it's""#),
+        ("'line \\\ncontinued \\\r\nagain'", r#""line continued again""#),
+        ("'\\v\\0\\x41\\q'", r#""\u000b\u0000Aq""#),
+        ("0x1F", "31"),
+        ("-0XfF", "-255"),
+        ("0xFFFFFFFFFFFFFFFF", "18446744073709551615"),
+        ("[+1, .5, 5., +.25e1]", "[1, 0.5, 5.0, 2.5]"),
+        ("[1, 2,]", "[1, 2]"),
+        ("{a: 1,}", r#"{"a": 1}"#),
+        ("/* block\n comment */ [1, /* inline */ 2] // line", "[1, 2]"),
+        ("\u{feff}\u{a0}[\u{b}1\u{2028}]", "[1]"),
+        ("[\"a\"\u{a0}]", r#"["a"]"#),
+        ("[\"a\"\u{feff}]", r#"["a"]"#),
+        ("{\"a\"\u{2028}:1}", r#"{"a": 1}"#),
+        ("['a'\u{3000}, \"b\"\u{b}/* c */]", r#"["a", "b"]"#),
+    ];
+    for (input, expected) in cases {
+        let mut parser = JsonParser::with_config(input, json5.clone());
+        assert_eq!(parser.parse(), crate::parse_str(expected), "input: {:?}", input);
+        assert!(crate::parse_str(input).is_err(), "strict input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_json5_infinity_and_nan() {
+    let json5 = JsonParserConfig {
+        mode: ParseMode::Json5,
+        ..JsonParserConfig::default()
+    };
+    let mut parser = JsonParser::with_config("[+Infinity, -Infinity, Infinity]", json5.clone());
+    let expected_value = JsonValue::Array(vec![
+        JsonValue::Float(f64::INFINITY),
+        JsonValue::Float(f64::NEG_INFINITY),
+        JsonValue::Float(f64::INFINITY),
+    ]);
+    assert_eq!(parser.parse(), Ok(expected_value));
+    assert!(crate::parse_str("[Infinity]").is_err());
+    for input in ["NaN", "+NaN", "-NaN"] {
+        let mut parser = JsonParser::with_config(input, json5.clone());
+        match parser.parse() {
+            Ok(JsonValue::Float(value)) => assert!(value.is_nan(), "input: {}", input),
+            other => panic!("input: {}, got {:?}", input, other),
+        }
+        assert!(crate::parse_str(input).is_err(), "strict input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_lenient_infinity_and_nan() {
+    let lenient = JsonParserConfig { mode: ParseMode::Lenient, ..JsonParserConfig::default() };
+    let cases = [
+        ("Infinity", f64::INFINITY),
+        ("-Infinity", f64::NEG_INFINITY),
+        ("[1, Infinity, -Infinity]", f64::NEG_INFINITY),
+        ("{\"a\": NaN}", f64::NAN),
+    ];
+    for (input, expected) in cases {
+        let value = JsonParser::with_config(input, lenient.clone()).parse().unwrap();
+        let float = match &value {
+            JsonValue::Array(array) => array.last().unwrap().as_f64().unwrap(),
+            JsonValue::Object(map) => map.get("a").unwrap().as_f64().unwrap(),
+            value => value.as_f64().unwrap(),
+        };
+        assert!(float == expected || (float.is_nan() && expected.is_nan()), "input: {}", input);
+        assert!(crate::parse_str(input).is_err(), "strict input: {:?}", input);
+    }
+    for input in ["+Infinity", "Inf", "-NaNa", "infinity", "Infinityy"] {
+        assert!(JsonParser::with_config(input, lenient.clone()).parse().is_err(), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_json5_mode_rejects_invalid_input() {
+    let json5 = JsonParserConfig {
+        mode: ParseMode::Json5,
+        ..JsonParserConfig::default()
+    };
+    for input in ["{1a: 1}", "'unterminated", "'\\1'", "0x", ".", "[1 /* unterminated", "Inf"] {
+        let mut parser = JsonParser::with_config(input, json5.clone());
+        assert!(parser.parse().is_err(), "input: {:?}", input);
+    }
+    let mut parser = JsonParser::with_config("[\n 'a\\", json5);
+    assert_eq!(parser.parse(), Err(ParserError::UnterminatedString { line: 2, column: 2, at_byte: 3 }));
+}
+
+#[test]
+fn test_validate_agrees_with_parse() {
+    let cases = [
+        "null",
+        " [1, -2.5e3, \"a\\u00e9\", {\"b\": [true, false]}] ",
+        "{}",
+        "",
+        "[1,]",
+        "{\"a\" 1}",
+        "[1] 2",
+        "\"unterminated",
+        "[1e400]",
+        "\"\\ud800\"",
+    ];
+    for input in cases {
+        assert_eq!(JsonParser::validate(input), crate::parse_str(input).map(|_| ()), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_validate_enforces_limits() {
+    let nested = format!("{}{}", "[".repeat(513), "]".repeat(513));
+    assert_eq!(JsonParser::validate(&nested), Err(ParserError::DepthLimitExceeded { limit: 512 }));
+    let config = JsonParserConfig { max_array_length: 2, max_object_size: 1, ..Default::default() };
+    let cases = [
+        ("[1, 2]", Ok(())),
+        ("[1, 2, 3]", Err(ParserError::ArrayLengthExceeded { limit: 2 })),
+        ("{\"a\": {\"b\": 1}}", Ok(())),
+        ("[{\"a\": 1, \"b\": 2}]", Err(ParserError::ObjectSizeExceeded { limit: 1 })),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(JsonParser::with_config(input, config.clone()).parse_helper_validate(), expected, "input: {:?}", input);
+        assert_eq!(JsonParser::with_config(input, config.clone()).parse().map(|_| ()), expected, "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_reset() {
+    let config = JsonParserConfig { max_total_values: 3, ..Default::default() };
+    let mut parser = JsonParser::with_config("[1, 2]", config);
+    assert_eq!(parser.parse(), crate::parse_str("[1, 2]"));
+    parser.reset("\n[3,\n 4]".to_string());
+    assert_eq!(parser.parse(), crate::parse_str("[3, 4]"));
+    assert_eq!((parser.line, parser.column), (3, 4));
+    parser.reset_from_str("[5, 6, 7]");
+    assert_eq!(parser.parse(), Err(ParserError::TotalValuesExceeded { limit: 3 }));
+    parser.reset_from_str("\n}");
+    assert_eq!(parser.parse(), Err(ParserError::SyntaxError { line: 2, column: 1, message: "Expected a value" }));
+}
+
+#[test]
+fn test_parse_many() {
+    let mut parser = JsonParser::new("{\"a\": 1}\n{\"b\": 2}\n");
+    let values: Vec<_> = parser.parse_many().collect();
+    assert_eq!(values, vec![crate::parse_str("{\"a\": 1}"), crate::parse_str("{\"b\": 2}")]);
+
+    let mut parser = JsonParser::new("[1, 2] 3 true");
+    let values: Vec<_> = parser.parse_many().collect();
+    assert_eq!(values, vec![crate::parse_str("[1, 2]"), Ok(JsonValue::Number(3)), Ok(JsonValue::Bool(true))]);
+
+    let mut parser = JsonParser::new("  ");
+    assert_eq!(parser.parse_many().count(), 0);
+
+    for input in ["\"a\" \"b\"", "\"a\"\n\"b\"\n", "\"a\"\"b\""] {
+        let values: Vec<_> = JsonParser::new(input).parse_many().collect();
+        assert_eq!(values, vec![Ok(JsonValue::from("a")), Ok(JsonValue::from("b"))], "input: {:?}", input);
+    }
+    for mode in [ParseMode::Lenient, ParseMode::Json5] {
+        let config = JsonParserConfig { mode, ..JsonParserConfig::default() };
+        let values: Vec<_> = JsonParser::with_config("\"a\" \"b\" \"c\"", config).parse_many().collect();
+        assert_eq!(values, vec![Ok(JsonValue::from("a")), Ok(JsonValue::from("b")), Ok(JsonValue::from("c"))], "mode: {:?}", mode);
+    }
+}
+
+#[test]
+fn test_unescaped_quote_ends_string() {
+    for input in [r#"["a" "b"]"#, r#""a"b""#, r#"{"a" "b": 1}"#, r#"["a"b"]"#] {
+        assert!(crate::parse_str(input).is_err(), "input: {}", input);
+    }
+    let config = JsonParserConfig { mode: ParseMode::Json5, ..JsonParserConfig::default() };
+    assert!(JsonParser::with_config(r#"["a" "b"]"#, config).parse().is_err());
+}
+
+#[test]
+fn test_parse_many_stops_at_first_error() {
+    let mut parser = JsonParser::new("1 [2, 3] } 4");
+    let mut values = parser.parse_many();
+    assert_eq!(values.next(), Some(Ok(JsonValue::Number(1))));
+    assert_eq!(values.next(), Some(crate::parse_str("[2, 3]")));
+    assert!(matches!(values.next(), Some(Err(ParserError::SyntaxError { .. }))));
+    assert_eq!(values.next(), None);
+}
+
+#[test]
+fn test_parser_error_is_std_error() {
+    let err = crate::parse_str("[1,\n  x]").unwrap_err();
+    assert_eq!(err.to_string(), "syntax error at line 2, column 3: Expected a value");
+    let boxed: Box<dyn core::error::Error> = Box::new(err);
+    assert!(boxed.source().is_none());
+    assert!(boxed.downcast_ref::<ParserError>().is_some());
+    let cases = [
+        ParserError::UnterminatedString { line: 1, column: 1, at_byte: 0 },
+        ParserError::InvalidEscapeSequence { char: 'x', line: 1, column: 3, at_byte: 2 },
+        ParserError::InvalidSurrogatePair,
+        ParserError::UnexpectedEof { expected: "value" },
+        ParserError::DepthLimitExceeded { limit: 512 },
+        ParserError::NumberOverflow("1e999".to_string()),
+        ParserError::InvalidUtf8 { byte_offset: 3 },
+        #[cfg(feature = "std")]
+        ParserError::Io(std::io::ErrorKind::BrokenPipe.into()),
+    ];
+    for err in cases {
+        assert!(!err.to_string().is_empty(), "error: {:?}", err);
+    }
+}
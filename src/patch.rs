@@ -0,0 +1,341 @@
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+use crate::pointer::{escape_token, parse_index, parse_pointer, PointerError};
+use crate::prelude::*;
+
+#[derive(Debug, PartialEq)]
+pub enum PatchError {
+    /// The patch is not an array of well-formed operation objects.
+    InvalidPatchDocument,
+    TestFailed { path: String },
+    PathNotFound { path: String },
+    TypeMismatch { path: String },
+}
+
+// Reads a string member of an operation object.
+fn member<'a>(operation: &'a JsonValue, key: &str) -> Result<&'a str, PatchError> {
+    operation
+        .as_object()
+        .and_then(|map| map.get(key))
+        .and_then(JsonValue::as_str)
+        .ok_or(PatchError::InvalidPatchDocument)
+}
+
+fn value_member(operation: &JsonValue) -> Result<&JsonValue, PatchError> {
+    operation.as_object().and_then(|map| map.get("value")).ok_or(PatchError::InvalidPatchDocument)
+}
+
+fn not_found(path: &str) -> PatchError {
+    PatchError::PathNotFound { path: path.to_string() }
+}
+
+fn get<'a>(target: &'a JsonValue, path: &str) -> Result<&'a JsonValue, PatchError> {
+    parse_pointer(path).ok_or(PatchError::InvalidPatchDocument)?;
+    target.pointer(path).ok_or_else(|| not_found(path))
+}
+
+fn add(target: &mut JsonValue, path: &str, value: JsonValue) -> Result<(), PatchError> {
+    let mut tokens = parse_pointer(path).ok_or(PatchError::InvalidPatchDocument)?;
+    let last = match tokens.pop() {
+        Some(last) => last,
+        None => {
+            *target = value;
+            return Ok(());
+        }
+    };
+    // Reference tokens cannot contain an unescaped '/', so the parent is
+    // everything before the last one.
+    let parent_path = &path[..path.rfind('/').unwrap()];
+    match target.pointer_mut(parent_path).ok_or_else(|| not_found(path))? {
+        JsonValue::Object(map) => {
+            map.insert(last, value);
+        }
+        JsonValue::Array(array) => {
+            let index = if last == "-" { Some(array.len()) } else { parse_index(&last) };
+            match index {
+                Some(index) if index <= array.len() => array.insert(index, value),
+                _ => return Err(not_found(path)),
+            }
+        }
+        _ => return Err(PatchError::TypeMismatch { path: path.to_string() }),
+    }
+    Ok(())
+}
+
+fn remove(target: &mut JsonValue, path: &str) -> Result<JsonValue, PatchError> {
+    target.pointer_remove(path).map_err(|err| match err {
+        PointerError::InvalidPointer if !path.is_empty() => PatchError::InvalidPatchDocument,
+        PointerError::NotAContainer => PatchError::TypeMismatch { path: path.to_string() },
+        _ => not_found(path),
+    })
+}
+
+fn apply_operation(target: &mut JsonValue, operation: &JsonValue) -> Result<(), PatchError> {
+    let path = member(operation, "path")?;
+    match member(operation, "op")? {
+        "add" => add(target, path, value_member(operation)?.clone()),
+        "remove" => remove(target, path).map(|_| ()),
+        "replace" => {
+            let value = value_member(operation)?.clone();
+            get(target, path)?;
+            *target.pointer_mut(path).unwrap() = value;
+            Ok(())
+        }
+        "move" => {
+            let from = member(operation, "from")?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(PatchError::InvalidPatchDocument);
+            }
+            let value = remove(target, from)?;
+            add(target, path, value)
+        }
+        "copy" => {
+            let value = get(target, member(operation, "from")?)?.clone();
+            add(target, path, value)
+        }
+        "test" => {
+            if get(target, path)? != value_member(operation)? {
+                return Err(PatchError::TestFailed { path: path.to_string() });
+            }
+            Ok(())
+        }
+        _ => Err(PatchError::InvalidPatchDocument),
+    }
+}
+
+impl JsonValue {
+    /// Applies an RFC 6902 JSON Patch to a copy of this value. The
+    /// operations are applied in order and the first failure aborts the
+    /// whole patch.
+    pub fn apply_patch(&self, patch: &JsonValue) -> Result<JsonValue, PatchError> {
+        let operations = patch.as_array().ok_or(PatchError::InvalidPatchDocument)?;
+        let mut target = self.clone();
+        for operation in operations {
+            apply_operation(&mut target, operation)?;
+        }
+        Ok(target)
+    }
+}
+
+fn operation(op: &str, path: &str, value: Option<&JsonValue>) -> JsonValue {
+    let mut operation = JsonObject::from([
+        ("op".to_string(), JsonValue::from(op)),
+        ("path".to_string(), JsonValue::from(path)),
+    ]);
+    if let Some(value) = value {
+        operation.insert("value".to_string(), value.clone());
+    }
+    JsonValue::Object(operation)
+}
+
+/// Returns the RFC 6902 patch that turns `before` into `after` when passed
+/// to `apply_patch`. Arrays are compared using their longest common
+/// subsequence so that insertions and removals only touch the elements
+/// that changed.
+pub fn diff(before: &JsonValue, after: &JsonValue) -> JsonValue {
+    let mut operations = vec![];
+    diff_values(&mut operations, "", before, after);
+    JsonValue::Array(operations)
+}
+
+fn diff_values(operations: &mut Vec<JsonValue>, path: &str, before: &JsonValue, after: &JsonValue) {
+    match (before, after) {
+        _ if before == after => {}
+        (JsonValue::Object(before), JsonValue::Object(after)) => diff_objects(operations, path, before, after),
+        (JsonValue::Array(before), JsonValue::Array(after)) => diff_arrays(operations, path, before, after),
+        _ => operations.push(operation("replace", path, Some(after))),
+    }
+}
+
+fn diff_objects(operations: &mut Vec<JsonValue>, path: &str, before: &JsonObject, after: &JsonObject) {
+    // Sort the keys so that the patch does not depend on the map order.
+    let mut removed: Vec<&String> = before.keys().filter(|key| !after.contains_key(key)).collect();
+    removed.sort();
+    for key in removed {
+        operations.push(operation("remove", &format!("{}/{}", path, escape_token(key)), None));
+    }
+    let mut members: Vec<(&String, &JsonValue)> = after.iter().collect();
+    members.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, value) in members {
+        let member_path = format!("{}/{}", path, escape_token(key));
+        match before.get(key) {
+            Some(previous) => diff_values(operations, &member_path, previous, value),
+            None => operations.push(operation("add", &member_path, Some(value))),
+        }
+    }
+}
+
+fn diff_arrays(operations: &mut Vec<JsonValue>, path: &str, before: &[JsonValue], after: &[JsonValue]) {
+    // lcs[i][j] is the length of the longest common subsequence of
+    // before[i..] and after[j..].
+    let mut lcs = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lcs[i][j] = if before[i] == after[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    // `index` is the position in the array as patched so far.
+    let (mut i, mut j, mut index) = (0, 0, 0);
+    while i < before.len() || j < after.len() {
+        let element_path = format!("{}/{}", path, index);
+        if i < before.len() && j < after.len() && (before[i] == after[j] || lcs[i + 1][j + 1] == lcs[i][j]) {
+            diff_values(operations, &element_path, &before[i], &after[j]);
+            i += 1;
+            j += 1;
+            index += 1;
+        } else if j == after.len() || (i < before.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            operations.push(operation("remove", &element_path, None));
+            i += 1;
+        } else {
+            operations.push(operation("add", &element_path, Some(&after[j])));
+            j += 1;
+            index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+fn apply(document: &str, patch: &str) -> Result<JsonValue, PatchError> {
+    crate::parse_str(document).unwrap().apply_patch(&crate::parse_str(patch).unwrap())
+}
+
+#[test]
+fn test_apply_patch_rfc6902_examples() {
+    let cases = [
+        // A.1 to A.8, A.10, A.11, A.14 and A.16.
+        (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#, r#"{"baz": "qux", "foo": "bar"}"#),
+        (r#"{"foo": ["bar", "baz"]}"#, r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#, r#"{"foo": ["bar", "qux", "baz"]}"#),
+        (r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "remove", "path": "/baz"}]"#, r#"{"foo": "bar"}"#),
+        (r#"{"foo": ["bar", "qux", "baz"]}"#, r#"[{"op": "remove", "path": "/foo/1"}]"#, r#"{"foo": ["bar", "baz"]}"#),
+        (r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#, r#"{"baz": "boo", "foo": "bar"}"#),
+        (
+            r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+            r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+            r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+        ),
+        (r#"{"foo": ["all", "grass", "cows", "eat"]}"#, r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#, r#"{"foo": ["all", "cows", "eat", "grass"]}"#),
+        (
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]"#,
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+        ),
+        (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#, r#"{"foo": "bar", "child": {"grandchild": {}}}"#),
+        (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]"#, r#"{"foo": "bar", "baz": "qux"}"#),
+        (r#"{"/": 9, "~1": 10}"#, r#"[{"op": "test", "path": "/~01", "value": 10}]"#, r#"{"/": 9, "~1": 10}"#),
+        (r#"{"foo": ["bar"]}"#, r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#, r#"{"foo": ["bar", ["abc", "def"]]}"#),
+    ];
+    for (document, patch, expected) in cases {
+        assert_eq!(apply(document, patch), Ok(crate::parse_str(expected).unwrap()), "patch: {:?}", patch);
+    }
+}
+
+#[test]
+fn test_apply_patch_rfc6902_errors() {
+    let cases = [
+        // A.9, A.12, A.13 and A.15. The parser keeps the last of duplicate
+        // keys, so the patch in A.13 becomes a remove of a missing member.
+        (r#"{"baz": "qux"}"#, r#"[{"op": "test", "path": "/baz", "value": "bar"}]"#, PatchError::TestFailed { path: "/baz".to_string() }),
+        (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#, PatchError::PathNotFound { path: "/baz/bat".to_string() }),
+        (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux", "op": "remove"}]"#, PatchError::PathNotFound { path: "/baz".to_string() }),
+        (r#"{"/": 9, "~1": 10}"#, r#"[{"op": "test", "path": "/~01", "value": "10"}]"#, PatchError::TestFailed { path: "/~01".to_string() }),
+    ];
+    for (document, patch, expected) in cases {
+        assert_eq!(apply(document, patch), Err(expected), "patch: {:?}", patch);
+    }
+}
+
+#[test]
+fn test_apply_patch_invalid_documents() {
+    let cases = [
+        (r#"{"op": "add", "path": "/a", "value": 1}"#, PatchError::InvalidPatchDocument),
+        (r#"[{"path": "/a", "value": 1}]"#, PatchError::InvalidPatchDocument),
+        (r#"[{"op": "add", "value": 1}]"#, PatchError::InvalidPatchDocument),
+        (r#"[{"op": "add", "path": "/a"}]"#, PatchError::InvalidPatchDocument),
+        (r#"[{"op": "frobnicate", "path": "/a"}]"#, PatchError::InvalidPatchDocument),
+        (r#"[{"op": "copy", "path": "/a"}]"#, PatchError::InvalidPatchDocument),
+        (r#"[{"op": "remove", "path": "a"}]"#, PatchError::InvalidPatchDocument),
+        (r#"[{"op": "move", "from": "/a", "path": "/a/b"}]"#, PatchError::InvalidPatchDocument),
+        (r#"[{"op": "add", "path": "/a/b/c", "value": 1}]"#, PatchError::TypeMismatch { path: "/a/b/c".to_string() }),
+        (r#"[{"op": "replace", "path": "/missing", "value": 1}]"#, PatchError::PathNotFound { path: "/missing".to_string() }),
+        (r#"[{"op": "add", "path": "/list/3", "value": 1}]"#, PatchError::PathNotFound { path: "/list/3".to_string() }),
+    ];
+    for (patch, expected) in cases {
+        assert_eq!(apply(r#"{"a": {"b": 1}, "list": [1]}"#, patch), Err(expected), "patch: {:?}", patch);
+    }
+}
+
+#[test]
+fn test_apply_patch_is_non_destructive() {
+    let document = crate::parse_str(r#"{"a": [1, 2]}"#).unwrap();
+    let patch = crate::parse_str(r#"[{"op": "copy", "from": "/a", "path": "/b"}, {"op": "remove", "path": "/a/0"}, {"op": "replace", "path": "", "value": {"c": 1}}]"#).unwrap();
+    assert_eq!(document.apply_patch(&patch), Ok(crate::parse_str(r#"{"c": 1}"#).unwrap()));
+    assert_eq!(document, crate::parse_str(r#"{"a": [1, 2]}"#).unwrap());
+    // A failing operation leaves no partial changes behind.
+    let patch = crate::parse_str(r#"[{"op": "remove", "path": "/a"}, {"op": "test", "path": "/a", "value": 1}]"#).unwrap();
+    assert_eq!(document.apply_patch(&patch), Err(PatchError::PathNotFound { path: "/a".to_string() }));
+}
+
+#[cfg(test)]
+fn assert_diff(before: &str, after: &str, expected: &str) {
+    let before = crate::parse_str(before).unwrap();
+    let after = crate::parse_str(after).unwrap();
+    let patch = diff(&before, &after);
+    assert_eq!(patch, crate::parse_str(expected).unwrap(), "after: {}", after);
+    assert_eq!(before.apply_patch(&patch), Ok(after));
+}
+
+#[test]
+fn test_diff_objects() {
+    assert_diff(r#"{"a": 1}"#, r#"{"a": 1}"#, "[]");
+    assert_diff(r#"{"a": 1}"#, r#"{"a": 1, "b": [2]}"#, r#"[{"op": "add", "path": "/b", "value": [2]}]"#);
+    assert_diff(r#"{"a": 1, "b/c": 2}"#, r#"{"a": 1}"#, r#"[{"op": "remove", "path": "/b~1c"}]"#);
+    assert_diff(r#"{"a": 1, "b": 2}"#, r#"{"a": "1", "b": 2}"#, r#"[{"op": "replace", "path": "/a", "value": "1"}]"#);
+}
+
+#[test]
+fn test_diff_nested() {
+    assert_diff(
+        r#"{"config": {"name": "x", "flags": {"debug": true}}, "old": null}"#,
+        r#"{"config": {"name": "y", "flags": {"debug": true, "trace": false}}}"#,
+        r#"[
+            {"op": "remove", "path": "/old"},
+            {"op": "add", "path": "/config/flags/trace", "value": false},
+            {"op": "replace", "path": "/config/name", "value": "y"}
+        ]"#,
+    );
+    assert_diff(r#"{"a": {"b": 1}}"#, r#"{"a": [1]}"#, r#"[{"op": "replace", "path": "/a", "value": [1]}]"#);
+    assert_diff("1", r#""x""#, r#"[{"op": "replace", "path": "", "value": "x"}]"#);
+}
+
+#[test]
+fn test_diff_arrays() {
+    assert_diff("[1, 2, 3]", "[0, 1, 2, 2.5, 3, 4]", r#"[
+        {"op": "add", "path": "/0", "value": 0},
+        {"op": "add", "path": "/3", "value": 2.5},
+        {"op": "add", "path": "/5", "value": 4}
+    ]"#);
+    assert_diff("[1, 2, 3, 4]", "[2, 4]", r#"[{"op": "remove", "path": "/0"}, {"op": "remove", "path": "/1"}]"#);
+    assert_diff(r#"[1, {"a": 1}, 3]"#, r#"[1, {"a": 2}, 3]"#, r#"[{"op": "replace", "path": "/1/a", "value": 2}]"#);
+    assert_diff("[]", "[1, 2]", r#"[{"op": "add", "path": "/0", "value": 1}, {"op": "add", "path": "/1", "value": 2}]"#);
+}
+
+#[test]
+fn test_diff_round_trips() {
+    let values = [
+        r#"{"a": [1, 2, {"b": [3, 4]}], "c": "d"}"#,
+        r#"{"a": [2, {"b": [4, 5]}, 1], "e": {"f": null}}"#,
+        r#"[[1, 2], [3], "x", true]"#,
+        r#"{"~": {"/": 1}}"#,
+    ];
+    for before in values {
+        for after in values {
+            let before = crate::parse_str(before).unwrap();
+            let after = crate::parse_str(after).unwrap();
+            assert_eq!(before.apply_patch(&diff(&before, &after)), Ok(after));
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use crate::parser::JsonValue;
+use crate::prelude::*;
+
+#[derive(Debug, PartialEq)]
+pub enum JsonPathError {
+    UnexpectedCharacter { char: char, at_byte: usize },
+    UnexpectedEnd,
+    InvalidEscapeSequence { at_byte: usize },
+    /// The index or slice bound does not fit in an i64.
+    InvalidNumber { at_byte: usize },
+    /// Filter selectors such as `[?@.price < 10]` are not supported yet.
+    UnsupportedFilter { at_byte: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice { start: Option<i64>, end: Option<i64>, step: Option<i64> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+/// A parsed RFC 9535 JSONPath query, without filter selectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPathExpr {
+    segments: Vec<Segment>,
+}
+
+fn is_blank(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+fn is_name_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_first(c) || c.is_ascii_digit()
+}
+
+struct PathParser<'a> {
+    path: &'a str,
+    cursor: usize,
+}
+
+impl<'a> PathParser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.path[self.cursor..].chars().next()
+    }
+
+    fn unexpected(&self) -> JsonPathError {
+        match self.peek() {
+            Some(char) => JsonPathError::UnexpectedCharacter { char, at_byte: self.cursor },
+            None => JsonPathError::UnexpectedEnd,
+        }
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() != Some(expected) {
+            return false;
+        }
+        self.cursor += expected.len_utf8();
+        true
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonPathError> {
+        if !self.consume(expected) {
+            return Err(self.unexpected());
+        }
+        Ok(())
+    }
+
+    fn skip_blanks(&mut self) {
+        while self.peek().is_some_and(is_blank) {
+            self.cursor += 1;
+        }
+    }
+
+    fn parse(&mut self) -> Result<JsonPathExpr, JsonPathError> {
+        self.expect('$')?;
+        let mut segments = vec![];
+        loop {
+            self.skip_blanks();
+            let Some(ch) = self.peek() else {
+                return Ok(JsonPathExpr { segments });
+            };
+            let segment = match ch {
+                '[' => Segment { descendant: false, selectors: self.parse_bracket()? },
+                '.' => {
+                    self.cursor += 1;
+                    let descendant = self.consume('.');
+                    let selectors = match self.peek() {
+                        Some('[') if descendant => self.parse_bracket()?,
+                        Some('*') => {
+                            self.cursor += 1;
+                            vec![Selector::Wildcard]
+                        }
+                        _ => vec![Selector::Name(self.parse_member_name()?)],
+                    };
+                    Segment { descendant, selectors }
+                }
+                _ => return Err(self.unexpected()),
+            };
+            segments.push(segment);
+        }
+    }
+
+    fn parse_member_name(&mut self) -> Result<String, JsonPathError> {
+        if !self.peek().is_some_and(is_name_first) {
+            return Err(self.unexpected());
+        }
+        let rest = &self.path[self.cursor..];
+        let length = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+        self.cursor += length;
+        Ok(rest[..length].to_string())
+    }
+
+    fn parse_bracket(&mut self) -> Result<Vec<Selector>, JsonPathError> {
+        self.expect('[')?;
+        let mut selectors = vec![];
+        loop {
+            self.skip_blanks();
+            selectors.push(self.parse_selector()?);
+            self.skip_blanks();
+            if self.consume(']') {
+                return Ok(selectors);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.parse_string()?)),
+            Some('*') => {
+                self.cursor += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => Err(JsonPathError::UnsupportedFilter { at_byte: self.cursor }),
+            _ => {
+                let start = self.parse_integer()?;
+                self.skip_blanks();
+                if !self.consume(':') {
+                    return start.map(Selector::Index).ok_or_else(|| self.unexpected());
+                }
+                self.skip_blanks();
+                let end = self.parse_integer()?;
+                self.skip_blanks();
+                let step = match self.consume(':') {
+                    true => {
+                        self.skip_blanks();
+                        self.parse_integer()?
+                    }
+                    false => None,
+                };
+                Ok(Selector::Slice { start, end, step })
+            }
+        }
+    }
+
+    // Integers have no leading zeros, and there is no negative zero.
+    fn parse_integer(&mut self) -> Result<Option<i64>, JsonPathError> {
+        let start = self.cursor;
+        let rest = &self.path[start..];
+        let negative = rest.starts_with('-');
+        let digits = rest[usize::from(negative)..].bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            if negative {
+                self.cursor += 1;
+                return Err(self.unexpected());
+            }
+            return Ok(None);
+        }
+        let length = usize::from(negative) + digits;
+        let literal = &rest[..length];
+        if literal.trim_start_matches('-').starts_with('0') && (digits > 1 || negative) {
+            return Err(JsonPathError::UnexpectedCharacter { char: '0', at_byte: start + usize::from(negative) });
+        }
+        self.cursor += length;
+        literal.parse().map(Some).map_err(|_| JsonPathError::InvalidNumber { at_byte: start })
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonPathError> {
+        let quote = self.peek().unwrap();
+        self.cursor += 1;
+        let mut builder = String::new();
+        loop {
+            let ch = self.peek().ok_or(JsonPathError::UnexpectedEnd)?;
+            let at_byte = self.cursor;
+            self.cursor += ch.len_utf8();
+            match ch {
+                ch if ch == quote => return Ok(builder),
+                '\\' => builder.push(self.parse_escape(quote, at_byte)?),
+                ch if ch < ' ' => return Err(JsonPathError::UnexpectedCharacter { char: ch, at_byte }),
+                ch => builder.push(ch),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, quote: char, at_byte: usize) -> Result<char, JsonPathError> {
+        let escaped = self.peek().ok_or(JsonPathError::UnexpectedEnd)?;
+        self.cursor += escaped.len_utf8();
+        let unescaped = match escaped {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '/' | '\\' => escaped,
+            ch if ch == quote => ch,
+            'u' => {
+                let high = self.parse_hex(at_byte)?;
+                if !(0xD800..=0xDBFF).contains(&high) {
+                    return char::from_u32(high).ok_or(JsonPathError::InvalidEscapeSequence { at_byte });
+                }
+                if !self.path[self.cursor..].starts_with("\\u") {
+                    return Err(JsonPathError::InvalidEscapeSequence { at_byte });
+                }
+                self.cursor += 2;
+                let low = self.parse_hex(at_byte)?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(JsonPathError::InvalidEscapeSequence { at_byte });
+                }
+                return Ok(char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap());
+            }
+            _ => return Err(JsonPathError::InvalidEscapeSequence { at_byte }),
+        };
+        Ok(unescaped)
+    }
+
+    fn parse_hex(&mut self, at_byte: usize) -> Result<u32, JsonPathError> {
+        let digits = match self.path.get(self.cursor..self.cursor + 4) {
+            Some(digits) if digits.chars().all(|c| c.is_ascii_hexdigit()) => digits,
+            _ => return Err(JsonPathError::InvalidEscapeSequence { at_byte }),
+        };
+        self.cursor += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap())
+    }
+}
+
+// The indices selected by a slice, in order, following RFC 9535 section
+// 2.3.4.2.2.
+fn slice_indices(len: usize, start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    let mut indices = vec![];
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            let Some(next) = i.checked_add(step) else { break };
+            i = next;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = end.map_or(-1, |end| normalize(end).clamp(-1, len - 1));
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            let Some(next) = i.checked_add(step) else { break };
+            i = next;
+        }
+    }
+    indices
+}
+
+fn select<'a>(value: &'a JsonValue, selector: &Selector, nodes: &mut Vec<&'a JsonValue>) {
+    match (selector, value) {
+        (Selector::Name(name), JsonValue::Object(map)) => nodes.extend(map.get(name)),
+        (Selector::Wildcard, JsonValue::Object(map)) => nodes.extend(map.values()),
+        (Selector::Wildcard, JsonValue::Array(array)) => nodes.extend(array),
+        (Selector::Index(index), JsonValue::Array(array)) => {
+            let index = if *index < 0 { array.len() as i64 + index } else { *index };
+            nodes.extend(usize::try_from(index).ok().and_then(|index| array.get(index)));
+        }
+        (Selector::Slice { start, end, step }, JsonValue::Array(array)) => {
+            nodes.extend(slice_indices(array.len(), *start, *end, *step).into_iter().map(|i| &array[i]));
+        }
+        _ => {}
+    }
+}
+
+// Collects `value` and everything inside it, each node before its
+// descendants.
+fn descendants<'a>(value: &'a JsonValue, nodes: &mut Vec<&'a JsonValue>) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        nodes.push(value);
+        match value {
+            JsonValue::Array(array) => stack.extend(array.iter().rev()),
+            JsonValue::Object(map) => {
+                let members: Vec<&JsonValue> = map.values().collect();
+                stack.extend(members.into_iter().rev());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl JsonPathExpr {
+    pub fn parse(path: &str) -> Result<Self, JsonPathError> {
+        PathParser { path, cursor: 0 }.parse()
+    }
+
+    /// Returns the nodes selected from `value`, in the order RFC 9535
+    /// gives them. Members of an object are visited in its iteration order.
+    pub fn query<'a>(&self, value: &'a JsonValue) -> Vec<&'a JsonValue> {
+        let mut nodes = vec![value];
+        for segment in &self.segments {
+            let inputs = match segment.descendant {
+                true => {
+                    let mut inputs = vec![];
+                    for node in nodes {
+                        descendants(node, &mut inputs);
+                    }
+                    inputs
+                }
+                false => nodes,
+            };
+            nodes = vec![];
+            for node in inputs {
+                for selector in &segment.selectors {
+                    select(node, selector, &mut nodes);
+                }
+            }
+        }
+        nodes
+    }
+}
+
+impl JsonValue {
+    /// Evaluates a JSONPath query such as `$.store.book[*].author` against
+    /// the value. See `JsonPathExpr` for parsing a query once and running
+    /// it many times.
+    pub fn query(&self, path: &str) -> Result<Vec<&JsonValue>, JsonPathError> {
+        Ok(JsonPathExpr::parse(path)?.query(self))
+    }
+}
+
+// The example document from RFC 9535 section 1.5.
+#[cfg(test)]
+const BOOKSTORE: &str = r#"{ "store": {
+    "book": [
+      { "category": "reference",
+        "author": "Nigel Rees",
+        "title": "Sayings of the Century",
+        "price": 8.95
+      },
+      { "category": "fiction",
+        "author": "Evelyn Waugh",
+        "title": "Sword of Honour",
+        "price": 12.99
+      },
+      { "category": "fiction",
+        "author": "Herman Melville",
+        "title": "Moby Dick",
+        "isbn": "0-553-21311-3",
+        "price": 8.99
+      },
+      { "category": "fiction",
+        "author": "J. R. R. Tolkien",
+        "title": "The Lord of the Rings",
+        "isbn": "0-395-19395-8",
+        "price": 22.99
+      }
+    ],
+    "bicycle": {
+      "color": "red",
+      "price": 399
+    }
+  }
+}"#;
+
+// Checks the nodes selected by each query against a JSON array. Queries
+// that pass through an object with several members have no fixed order, so
+// those are compared after sorting.
+#[cfg(test)]
+fn assert_queries(document: &str, cases: &[(&str, &str, bool)]) {
+    let document = crate::parse_str(document).unwrap();
+    for (path, expected, ordered) in cases {
+        let mut nodes: Vec<JsonValue> = document.query(path).unwrap().into_iter().cloned().collect();
+        let mut expected = crate::parse_str(expected).unwrap().as_array().unwrap().clone();
+        if !ordered {
+            nodes.sort();
+            expected.sort();
+        }
+        assert_eq!(nodes, expected, "path: {}", path);
+    }
+}
+
+#[test]
+fn test_query_bookstore_examples() {
+    let document = crate::parse_str(BOOKSTORE).unwrap();
+    let books = document.pointer("/store/book").unwrap().as_array().unwrap();
+    let authors = r#"["Nigel Rees", "Evelyn Waugh", "Herman Melville", "J. R. R. Tolkien"]"#;
+    let cases: [(&str, &str, bool); 10] = [
+        ("$.store.book[*].author", authors, true),
+        ("$..author", authors, true),
+        ("$.store.*", &format!("[{}, {{\"color\": \"red\", \"price\": 399}}]", JsonValue::Array(books.clone())), false),
+        ("$.store..price", "[8.95, 12.99, 8.99, 22.99, 399]", false),
+        ("$..book[2]", &format!("[{}]", books[2]), true),
+        ("$..book[2].author", r#"["Herman Melville"]"#, true),
+        ("$..book[2].publisher", "[]", true),
+        ("$..book[-1]", &format!("[{}]", books[3]), true),
+        ("$..book[0,1]", &format!("[{}, {}]", books[0], books[1]), true),
+        ("$..book[:2]", &format!("[{}, {}]", books[0], books[1]), true),
+    ];
+    assert_queries(BOOKSTORE, &cases);
+    assert_eq!(document.query("$..*").unwrap().len(), 27);
+    assert_eq!(document.query("$").unwrap(), [&document]);
+}
+
+#[test]
+fn test_query_filters_are_unsupported() {
+    let document = crate::parse_str(BOOKSTORE).unwrap();
+    assert_eq!(document.query("$..book[?@.isbn]"), Err(JsonPathError::UnsupportedFilter { at_byte: 8 }));
+    assert_eq!(document.query("$..book[?@.price<10]"), Err(JsonPathError::UnsupportedFilter { at_byte: 8 }));
+    assert_eq!(document.query("$..*[?@.price<10]"), Err(JsonPathError::UnsupportedFilter { at_byte: 5 }));
+}
+
+#[test]
+fn test_query_name_selector_examples() {
+    let document = r#"{"o": {"j j": {"k.k": 3}}, "'": {"@": 2}}"#;
+    assert_queries(document, &[
+        ("$.o['j j']", r#"[{"k.k": 3}]"#, true),
+        ("$.o['j j']['k.k']", "[3]", true),
+        (r#"$.o["j j"]["k.k"]"#, "[3]", true),
+        (r#"$["'"]["@"]"#, "[2]", true),
+    ]);
+}
+
+#[test]
+fn test_query_wildcard_selector_examples() {
+    let document = r#"{"o": {"j": 1, "k": 2}, "a": [5, 3]}"#;
+    assert_queries(document, &[
+        ("$[*]", r#"[{"j": 1, "k": 2}, [5, 3]]"#, false),
+        ("$.o[*]", "[1, 2]", false),
+        ("$.o[*, *]", "[1, 2, 2, 1]", false),
+        ("$.a[*]", "[5, 3]", true),
+    ]);
+}
+
+#[test]
+fn test_query_index_selector_examples() {
+    assert_queries(r#"["a", "b"]"#, &[("$[1]", r#"["b"]"#, true), ("$[-2]", r#"["a"]"#, true), ("$[2]", "[]", true)]);
+}
+
+#[test]
+fn test_query_slice_selector_examples() {
+    assert_queries(r#"["a", "b", "c", "d", "e", "f", "g"]"#, &[
+        ("$[1:3]", r#"["b", "c"]"#, true),
+        ("$[5:]", r#"["f", "g"]"#, true),
+        ("$[1:5:2]", r#"["b", "d"]"#, true),
+        ("$[5:1:-2]", r#"["f", "d"]"#, true),
+        ("$[::-1]", r#"["g", "f", "e", "d", "c", "b", "a"]"#, true),
+        ("$[-2:]", r#"["f", "g"]"#, true),
+        ("$[1:5:0]", "[]", true),
+        ("$[-100:100:3]", r#"["a", "d", "g"]"#, true),
+    ]);
+}
+
+#[test]
+fn test_query_slice_extreme_bounds() {
+    assert_queries("[1, 2, 3]", &[
+        ("$[1::9223372036854775807]", "[2]", true),
+        ("$[::-9223372036854775808]", "[3]", true),
+        ("$[1:-9223372036854775808:-9223372036854775807]", "[2]", true),
+        ("$[-9223372036854775808:9223372036854775807]", "[1, 2, 3]", true),
+        ("$[9223372036854775807:-9223372036854775808:-1]", "[3, 2, 1]", true),
+        ("$[-9223372036854775808:9223372036854775807:9223372036854775807]", "[1]", true),
+    ]);
+}
+
+#[test]
+fn test_query_descendant_segment_examples() {
+    let document = r#"{"o": {"j": 1, "k": 2}, "a": [5, 3, [{"j": 4}, {"k": 6}]]}"#;
+    assert_queries(document, &[
+        ("$..j", "[1, 4]", false),
+        ("$..[0]", r#"[5, {"j": 4}]"#, true),
+        ("$..[*]", r#"[{"j": 1, "k": 2}, [5, 3, [{"j": 4}, {"k": 6}]], 1, 2, 5, 3, [{"j": 4}, {"k": 6}], {"j": 4}, {"k": 6}, 4, 6]"#, false),
+        ("$..*", r#"[{"j": 1, "k": 2}, [5, 3, [{"j": 4}, {"k": 6}]], 1, 2, 5, 3, [{"j": 4}, {"k": 6}], {"j": 4}, {"k": 6}, 4, 6]"#, false),
+        ("$..o", r#"[{"j": 1, "k": 2}]"#, true),
+        ("$.o..[*, *]", "[1, 2, 2, 1]", false),
+        ("$.a..[0, 1]", r#"[5, 3, {"j": 4}, {"k": 6}]"#, true),
+    ]);
+}
+
+#[test]
+fn test_query_null_semantics_examples() {
+    let document = r#"{"a": null, "b": [null], "c": [{}], "null": 1}"#;
+    assert_queries(document, &[
+        ("$.a", "[null]", true),
+        ("$.a[0]", "[]", true),
+        ("$.a.d", "[]", true),
+        ("$.b[0]", "[null]", true),
+        ("$.b[*]", "[null]", true),
+        ("$.null", "[1]", true),
+    ]);
+}
+
+#[test]
+fn test_query_invalid_paths() {
+    let cases = [
+        ("", JsonPathError::UnexpectedEnd),
+        ("store", JsonPathError::UnexpectedCharacter { char: 's', at_byte: 0 }),
+        ("$.", JsonPathError::UnexpectedEnd),
+        ("$.1a", JsonPathError::UnexpectedCharacter { char: '1', at_byte: 2 }),
+        ("$[01]", JsonPathError::UnexpectedCharacter { char: '0', at_byte: 2 }),
+        ("$[-0]", JsonPathError::UnexpectedCharacter { char: '0', at_byte: 3 }),
+        ("$[1", JsonPathError::UnexpectedEnd),
+        ("$['a]", JsonPathError::UnexpectedEnd),
+        ("$['\\x']", JsonPathError::InvalidEscapeSequence { at_byte: 3 }),
+        ("$[99999999999999999999]", JsonPathError::InvalidNumber { at_byte: 2 }),
+        ("$...a", JsonPathError::UnexpectedCharacter { char: '.', at_byte: 3 }),
+    ];
+    for (path, expected) in cases {
+        assert_eq!(JsonPathExpr::parse(path), Err(expected), "path: {:?}", path);
+    }
+}
+
+#[test]
+fn test_query_string_escapes() {
+    let document = r#"{"a'b": 1, "é\n": 2, "😀": 3}"#;
+    assert_queries(document, &[
+        (r#"$['a\'b']"#, "[1]", true),
+        (r#"$["é\n"]"#, "[2]", true),
+        (r#"$['😀']"#, "[3]", true),
+        ("$.😀", "[3]", true),
+    ]);
+}
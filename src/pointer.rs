@@ -0,0 +1,314 @@
+use crate::iter::{children, Iter, JsonItem};
+use crate::parser::JsonValue;
+use crate::object::JsonObject;
+use crate::prelude::*;
+
+#[derive(Debug, PartialEq)]
+pub enum PointerError {
+    InvalidPointer,
+    IndexOutOfBounds { index: usize, len: usize },
+    KeyNotFound { key: String },
+    NotAContainer,
+}
+
+// Splits an RFC 6901 pointer into its unescaped reference tokens. The
+// empty pointer refers to the whole document and yields no tokens.
+pub(crate) fn parse_pointer(ptr: &str) -> Option<Vec<String>> {
+    if ptr.is_empty() {
+        return Some(vec![]);
+    }
+    let tokens = ptr.strip_prefix('/')?;
+    Some(tokens.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+// Escapes a key for use as a reference token, the reverse of
+// `parse_pointer`.
+pub(crate) fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+// Array indices are decimal numbers without leading zeros.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+// Resolves an array token, where `-` refers to the slot past the end.
+fn array_index(token: &str, len: usize) -> Result<usize, PointerError> {
+    if token == "-" {
+        return Ok(len);
+    }
+    parse_index(token).ok_or(PointerError::InvalidPointer)
+}
+
+/// Visits a value and every value nested in it in depth-first pre-order,
+/// along with the pointer to each, created by `JsonValue::walk`.
+pub struct Walk<'a> {
+    root: Option<&'a JsonValue>,
+    // The pointer to each container being visited, the index of its next
+    // element and the iterator over its children.
+    stack: Vec<(String, usize, Iter<'a>)>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (String, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (ptr, value) = match self.root.take() {
+            Some(root) => (String::new(), root),
+            None => loop {
+                let (parent, index, iter) = self.stack.last_mut()?;
+                match iter.next() {
+                    Some(JsonItem::Element(value)) => {
+                        *index += 1;
+                        break (format!("{}/{}", parent, *index - 1), value);
+                    }
+                    Some(JsonItem::Member(key, value)) => break (format!("{}/{}", parent, escape_token(key)), value),
+                    None => drop(self.stack.pop()),
+                }
+            },
+        };
+        if let Some(iter) = children(value) {
+            self.stack.push((ptr.clone(), 0, iter));
+        }
+        Some((ptr, value))
+    }
+}
+
+impl JsonValue {
+    /// Iterates over the value itself and every value nested in it, each
+    /// before its children, paired with its RFC 6901 pointer. The pointer
+    /// of the value itself is the empty string.
+    pub fn walk(&self) -> Walk<'_> {
+        Walk { root: Some(self), stack: vec![] }
+    }
+
+    pub fn pointer(&self, ptr: &str) -> Option<&JsonValue> {
+        let mut target = self;
+        for token in parse_pointer(ptr)? {
+            target = match target {
+                JsonValue::Object(map) => map.get(&token)?,
+                JsonValue::Array(array) => array.get(parse_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    pub fn pointer_mut(&mut self, ptr: &str) -> Option<&mut JsonValue> {
+        let mut target = self;
+        for token in parse_pointer(ptr)? {
+            target = match target {
+                JsonValue::Object(map) => map.get_mut(&token)?,
+                JsonValue::Array(array) => array.get_mut(parse_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Sets the value at `ptr`, creating missing intermediate containers.
+    /// A missing parent becomes an array when the next token is an index or
+    /// `-`, and an object otherwise. `-` or an index equal to the length
+    /// appends to an array.
+    pub fn pointer_set(&mut self, ptr: &str, value: JsonValue) -> Result<(), PointerError> {
+        let tokens = parse_pointer(ptr).ok_or(PointerError::InvalidPointer)?;
+        let mut target = self;
+        for (i, token) in tokens.iter().enumerate() {
+            let next = tokens.get(i + 1);
+            let container = || match next {
+                Some(next) if next == "-" || parse_index(next).is_some() => JsonValue::Array(vec![]),
+                _ => JsonValue::Object(JsonObject::new()),
+            };
+            target = match target {
+                JsonValue::Object(map) => {
+                    if !map.contains_key(token) {
+                        map.insert(token.clone(), container());
+                    }
+                    map.get_mut(token).unwrap()
+                }
+                JsonValue::Array(array) => {
+                    let index = array_index(token, array.len())?;
+                    if index > array.len() {
+                        return Err(PointerError::IndexOutOfBounds { index, len: array.len() });
+                    }
+                    if index == array.len() {
+                        array.push(container());
+                    }
+                    &mut array[index]
+                }
+                _ => return Err(PointerError::NotAContainer),
+            };
+        }
+        *target = value;
+        Ok(())
+    }
+
+    /// Removes and returns the value at `ptr`. Later array elements shift
+    /// down to fill the gap.
+    pub fn pointer_remove(&mut self, ptr: &str) -> Result<JsonValue, PointerError> {
+        let mut tokens = parse_pointer(ptr).ok_or(PointerError::InvalidPointer)?;
+        let last = tokens.pop().ok_or(PointerError::InvalidPointer)?;
+        let mut parent = self;
+        for token in tokens {
+            parent = match parent {
+                JsonValue::Object(map) => map.get_mut(&token).ok_or(PointerError::KeyNotFound { key: token })?,
+                JsonValue::Array(array) => {
+                    let (index, len) = (array_index(&token, array.len())?, array.len());
+                    array.get_mut(index).ok_or(PointerError::IndexOutOfBounds { index, len })?
+                }
+                _ => return Err(PointerError::NotAContainer),
+            };
+        }
+        match parent {
+            JsonValue::Object(map) => map.remove(&last).ok_or(PointerError::KeyNotFound { key: last }),
+            JsonValue::Array(array) => {
+                let index = array_index(&last, array.len())?;
+                if index >= array.len() {
+                    return Err(PointerError::IndexOutOfBounds { index, len: array.len() });
+                }
+                Ok(array.remove(index))
+            }
+            _ => Err(PointerError::NotAContainer),
+        }
+    }
+}
+
+#[cfg(test)]
+fn rfc6901_document() -> JsonValue {
+    crate::parse_str(r#"{
+        "foo": ["bar", "baz"],
+        "": 0,
+        "a/b": 1,
+        "c%d": 2,
+        "e^f": 3,
+        "g|h": 4,
+        "i\\j": 5,
+        "k\"l": 6,
+        " ": 7,
+        "m~n": 8
+    }"#).unwrap()
+}
+
+#[test]
+fn test_pointer_rfc6901_examples() {
+    let document = rfc6901_document();
+    assert_eq!(document.pointer(""), Some(&document));
+    assert_eq!(document.pointer("/foo"), Some(&crate::parse_str(r#"["bar", "baz"]"#).unwrap()));
+    let cases = [
+        ("/foo/0", JsonValue::String("bar".into())),
+        ("/", JsonValue::Number(0)),
+        ("/a~1b", JsonValue::Number(1)),
+        ("/c%d", JsonValue::Number(2)),
+        ("/e^f", JsonValue::Number(3)),
+        ("/g|h", JsonValue::Number(4)),
+        ("/i\\j", JsonValue::Number(5)),
+        ("/k\"l", JsonValue::Number(6)),
+        ("/ ", JsonValue::Number(7)),
+        ("/m~0n", JsonValue::Number(8)),
+    ];
+    for (ptr, expected) in cases {
+        assert_eq!(document.pointer(ptr), Some(&expected), "pointer: {:?}", ptr);
+    }
+}
+
+#[test]
+fn test_walk() {
+    let config = crate::JsonParserConfig { preserve_order: crate::JsonObjectKind::BTreeMap, ..Default::default() };
+    let input = r#"{"a": [1, {"b/c": null}], "m~n": {"x": [true]}, "": "empty"}"#;
+    let value = crate::JsonParser::with_config(input, config).parse().unwrap();
+    let expected = [
+        ("", input),
+        ("/", r#""empty""#),
+        ("/a", r#"[1, {"b/c": null}]"#),
+        ("/a/0", "1"),
+        ("/a/1", r#"{"b/c": null}"#),
+        ("/a/1/b~1c", "null"),
+        ("/m~0n", r#"{"x": [true]}"#),
+        ("/m~0n/x", "[true]"),
+        ("/m~0n/x/0", "true"),
+    ];
+    let walked: Vec<(String, &JsonValue)> = value.walk().collect();
+    assert_eq!(walked.len(), expected.len());
+    for ((ptr, node), (expected_ptr, expected_node)) in walked.into_iter().zip(expected) {
+        assert_eq!(ptr, expected_ptr);
+        assert_eq!(node, &crate::parse_str(expected_node).unwrap(), "pointer: {:?}", ptr);
+        assert_eq!(value.pointer(&ptr), Some(node), "pointer: {:?}", ptr);
+    }
+    assert_eq!(JsonValue::Null.walk().collect::<Vec<_>>(), [(String::new(), &JsonValue::Null)]);
+}
+
+#[test]
+fn test_pointer_missing_targets() {
+    let document = rfc6901_document();
+    for ptr in ["/missing", "/foo/2", "/foo/-", "/foo/01", "/foo/bar", "/foo/0/x", "foo", "/a~1b/c"] {
+        assert_eq!(document.pointer(ptr), None, "pointer: {:?}", ptr);
+    }
+}
+
+#[test]
+fn test_pointer_mut() {
+    let mut document = rfc6901_document();
+    *document.pointer_mut("/foo/1").unwrap() = JsonValue::Null;
+    assert_eq!(document.pointer("/foo"), Some(&crate::parse_str(r#"["bar", null]"#).unwrap()));
+    assert!(document.pointer_mut("/foo/5").is_none());
+}
+
+#[test]
+fn test_pointer_set() {
+    let mut document = rfc6901_document();
+    assert_eq!(document.pointer_set("/foo/0", JsonValue::Bool(true)), Ok(()));
+    assert_eq!(document.pointer("/foo/0"), Some(&JsonValue::Bool(true)));
+    assert_eq!(document.pointer_set("/foo/-", JsonValue::Number(9)), Ok(()));
+    assert_eq!(document.pointer("/foo/2"), Some(&JsonValue::Number(9)));
+    assert_eq!(document.pointer_set("/m~0n", JsonValue::Null), Ok(()));
+    assert_eq!(document.pointer("/m~0n"), Some(&JsonValue::Null));
+    assert_eq!(document.pointer_set("", JsonValue::Null), Ok(()));
+    assert_eq!(document, JsonValue::Null);
+}
+
+#[test]
+fn test_pointer_set_creates_intermediate_nodes() {
+    let mut document = JsonValue::Object(JsonObject::new());
+    assert_eq!(document.pointer_set("/a/b/0/c", JsonValue::Number(1)), Ok(()));
+    assert_eq!(document.pointer_set("/a/b/-", JsonValue::Number(2)), Ok(()));
+    assert_eq!(document, crate::parse_str(r#"{"a": {"b": [{"c": 1}, 2]}}"#).unwrap());
+}
+
+#[test]
+fn test_pointer_set_errors() {
+    let mut document = rfc6901_document();
+    assert_eq!(document.pointer_set("foo", JsonValue::Null), Err(PointerError::InvalidPointer));
+    assert_eq!(document.pointer_set("/foo/x", JsonValue::Null), Err(PointerError::InvalidPointer));
+    assert_eq!(document.pointer_set("/foo/5", JsonValue::Null), Err(PointerError::IndexOutOfBounds { index: 5, len: 2 }));
+    assert_eq!(document.pointer_set("/a~1b/c", JsonValue::Null), Err(PointerError::NotAContainer));
+    assert_eq!(document, rfc6901_document());
+}
+
+#[test]
+fn test_pointer_remove() {
+    let mut document = crate::parse_str(r#"{"list": [1, 2, 3], "key": {"inner": true}}"#).unwrap();
+    assert_eq!(document.pointer_remove("/list/0"), Ok(JsonValue::Number(1)));
+    assert_eq!(document.pointer("/list"), Some(&crate::parse_str("[2, 3]").unwrap()));
+    assert_eq!(document.pointer_remove("/key/inner"), Ok(JsonValue::Bool(true)));
+    assert_eq!(document, crate::parse_str(r#"{"list": [2, 3], "key": {}}"#).unwrap());
+}
+
+#[test]
+fn test_pointer_remove_errors() {
+    let mut document = crate::parse_str(r#"{"list": [1], "n": 0}"#).unwrap();
+    let cases = [
+        ("", PointerError::InvalidPointer),
+        ("list", PointerError::InvalidPointer),
+        ("/missing", PointerError::KeyNotFound { key: "missing".to_string() }),
+        ("/missing/x", PointerError::KeyNotFound { key: "missing".to_string() }),
+        ("/list/1", PointerError::IndexOutOfBounds { index: 1, len: 1 }),
+        ("/list/-", PointerError::IndexOutOfBounds { index: 1, len: 1 }),
+        ("/n/x", PointerError::NotAContainer),
+    ];
+    for (ptr, expected) in cases {
+        assert_eq!(document.pointer_remove(ptr), Err(expected), "pointer: {:?}", ptr);
+    }
+}
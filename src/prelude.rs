@@ -0,0 +1,7 @@
+// The alloc types that std's prelude would otherwise provide, so that the
+// crate builds without std. Modules that need them import `prelude::*`.
+pub(crate) use alloc::borrow::Cow;
+pub(crate) use alloc::boxed::Box;
+pub(crate) use alloc::string::{String, ToString};
+pub(crate) use alloc::vec::Vec;
+pub(crate) use alloc::{format, vec};
@@ -0,0 +1,166 @@
+use crate::feed::FeedableParser;
+use crate::parser::{JsonParser, JsonValue, ParserError};
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+
+// Decodes the chunks of a byte stream and feeds them to a parser until a
+// whole value is available.
+pub(crate) struct ChunkedInput {
+    parser: FeedableParser,
+    // The start of a character that was split between two chunks.
+    pending: Vec<u8>,
+    // The number of bytes decoded so far.
+    decoded: usize,
+}
+
+impl ChunkedInput {
+    pub(crate) fn new() -> Self {
+        ChunkedInput { parser: FeedableParser::single_value(), pending: vec![], decoded: 0 }
+    }
+
+    // Feeds the next chunk. Once the value is complete, returns it along
+    // with the number of bytes of `chunk` that it used.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Option<(Result<JsonValue, ParserError>, usize)> {
+        self.pending.extend_from_slice(chunk);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text,
+            Err(err) if err.error_len().is_none() => std::str::from_utf8(&self.pending[..err.valid_up_to()]).unwrap(),
+            Err(err) => {
+                let byte_offset = self.decoded + err.valid_up_to();
+                return Some((Err(ParserError::InvalidUtf8 { byte_offset }), chunk.len()));
+            }
+        };
+        let valid_length = valid.len();
+        self.parser.feed(valid);
+        self.pending.drain(..valid_length);
+        self.decoded += valid_length;
+        let result = self.parser.try_get()?;
+        // Only hand back input from the current chunk. Any unparsed input
+        // from earlier chunks is whitespace.
+        let unparsed = self.parser.buffered_len() + self.pending.len();
+        Some((result, chunk.len().saturating_sub(unparsed)))
+    }
+
+    // Ends the input once the reader has no more bytes.
+    pub(crate) fn finish(mut self) -> Result<JsonValue, ParserError> {
+        if !self.pending.is_empty() {
+            return Err(ParserError::InvalidUtf8 { byte_offset: self.decoded });
+        }
+        self.parser.finish();
+        self.parser.try_get().unwrap_or(Err(ParserError::UnexpectedEof { expected: "value" }))
+    }
+}
+
+impl JsonParser {
+    /// Parses a single value from `reader`, reading it in buffered chunks
+    /// instead of loading the whole input into a string first.
+    pub fn parse_from_reader(reader: impl Read) -> Result<JsonValue, ParserError> {
+        Self::parse_from_buf_reader(&mut BufReader::new(reader))
+    }
+
+    /// Like `parse_from_reader`, but stops reading at the end of the value
+    /// and leaves any input after it in the buffer of `reader`.
+    pub fn parse_from_buf_reader(reader: &mut impl BufRead) -> Result<JsonValue, ParserError> {
+        let mut input = ChunkedInput::new();
+        loop {
+            let chunk = match reader.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(ParserError::Io(err)),
+            };
+            if chunk.is_empty() {
+                return input.finish();
+            }
+            let length = chunk.len();
+            match input.feed(chunk) {
+                Some((result, used)) => {
+                    reader.consume(used);
+                    return result;
+                }
+                None => reader.consume(length),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+const DOCUMENT: &str = "{\n  \"name\": \"tinyserde 𝄞\",\n  \"values\": [1, -2.5, true, null, \"é\"],\n  \"nested\": {\"a\": [[], {}]}\n}\n";
+
+#[test]
+fn test_parse_from_file_and_cursor() {
+    let path = std::env::temp_dir().join(format!("tinyserde-reader-{}.json", std::process::id()));
+    std::fs::write(&path, DOCUMENT).unwrap();
+    let from_file = JsonParser::parse_from_reader(std::fs::File::open(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+    let from_cursor = JsonParser::parse_from_reader(std::io::Cursor::new(DOCUMENT));
+    assert_eq!(from_file, crate::parse_str(DOCUMENT));
+    assert_eq!(from_cursor, from_file);
+}
+
+#[test]
+fn test_parse_from_reader_large_input() {
+    // Many times the size of the reader's buffer, with characters split
+    // between chunks. Reading it costs about as much as parsing it from a
+    // string, and only input that is not parsed yet stays buffered.
+    let document = format!("[{}]", vec![DOCUMENT.trim_end(); 20_000].join(",\n"));
+    let whole = crate::convert::count_allocations(|| drop(crate::parse_str(&document)));
+    let mut value = None;
+    let allocations = crate::convert::count_allocations(|| value = Some(JsonParser::parse_from_reader(std::io::Cursor::new(&document))));
+    assert_eq!(value.unwrap(), crate::parse_str(&document));
+    assert!(allocations < whole * 2, "{} allocations from the reader, {} from a string", allocations, whole);
+    let mut input = ChunkedInput::new();
+    let chunks: Vec<&[u8]> = document.as_bytes().chunks(8 * 1024).collect();
+    let (last, chunks) = chunks.split_last().unwrap();
+    for chunk in chunks {
+        assert!(input.feed(chunk).is_none());
+        assert!(input.parser.buffered_len() + input.pending.len() < 1024);
+    }
+    assert_eq!(input.feed(last), Some((crate::parse_str(&document), last.len())));
+}
+
+#[test]
+fn test_parse_from_reader_in_small_chunks() {
+    let cases = [DOCUMENT, "12345", "\"𝄞\"", "  ", "[1, 2", "{\"a\" 1}", "nul", "\u{feff}{\"k\": 1}"];
+    for input in cases {
+        for capacity in 1..8 {
+            let mut reader = BufReader::with_capacity(capacity, input.as_bytes());
+            assert_eq!(JsonParser::parse_from_buf_reader(&mut reader), crate::parse_str(input), "input: {:?}", input);
+        }
+    }
+}
+
+#[test]
+fn test_parse_from_reader_leaves_remaining_input() {
+    let cases = [("[1, 2] [3]", " [3]"), ("12 rest", " rest"), ("\"a\"\n, \"b\"", "\n, \"b\""), ("{}", "")];
+    for (input, remaining) in cases {
+        let mut reader = std::io::Cursor::new(input);
+        assert_eq!(JsonParser::parse_from_buf_reader(&mut reader), JsonParser::new(input).parse_many().next().unwrap());
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, remaining, "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_parse_from_reader_invalid_utf8() {
+    assert_eq!(JsonParser::parse_from_reader(&b"[\"\xff\"]"[..]), Err(ParserError::InvalidUtf8 { byte_offset: 2 }));
+    assert_eq!(JsonParser::parse_from_reader(&b"\"\xf0\x9d"[..]), Err(ParserError::InvalidUtf8 { byte_offset: 1 }));
+    for capacity in 1..8 {
+        let mut reader = BufReader::with_capacity(capacity, &b"[\"\xc3\xa9\", \"\xc3\"]"[..]);
+        assert_eq!(JsonParser::parse_from_buf_reader(&mut reader), Err(ParserError::InvalidUtf8 { byte_offset: 8 }));
+    }
+}
+
+#[test]
+fn test_parse_from_reader_keeps_io_error() {
+    struct Failing;
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(ErrorKind::ConnectionReset, "connection reset"))
+        }
+    }
+    let err = JsonParser::parse_from_reader((&b"[1, "[..]).chain(Failing)).unwrap_err();
+    assert_eq!(err, ParserError::Io(ErrorKind::ConnectionReset.into()));
+    assert_eq!(err.to_string(), "I/O error: connection reset");
+    let source = core::error::Error::source(&err).unwrap();
+    assert_eq!(source.downcast_ref::<std::io::Error>().unwrap().kind(), ErrorKind::ConnectionReset);
+}
@@ -0,0 +1,526 @@
+// A small backtracking matcher for the ECMA-262 regular expressions used by
+// JSON Schema `pattern` keywords. It supports literals, `.`, character
+// classes, `\d \w \s` and their negations, anchors, word boundaries,
+// groups, alternation and greedy or lazy quantifiers. Lookaround and
+// backreferences are rejected when the pattern is compiled.
+//
+// Patterns are compiled to a list of instructions that are run with an
+// explicit stack of the alternatives left to try, so that long strings
+// cannot overflow the call stack.
+
+use crate::prelude::*;
+
+#[derive(Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class { ranges: Vec<(u32, u32)>, negated: bool },
+    Start,
+    End,
+    // True for `\b`, false for `\B`.
+    WordBoundary(bool),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat(Repeat),
+}
+
+#[derive(Debug)]
+struct Repeat {
+    node: Box<Node>,
+    min: u32,
+    max: Option<u32>,
+    greedy: bool,
+}
+
+const DIGIT: &[(u32, u32)] = &[(0x30, 0x39)];
+const WORD: &[(u32, u32)] = &[(0x30, 0x39), (0x41, 0x5A), (0x5F, 0x5F), (0x61, 0x7A)];
+const SPACE: &[(u32, u32)] = &[
+    (0x09, 0x0D),
+    (0x20, 0x20),
+    (0xA0, 0xA0),
+    (0x1680, 0x1680),
+    (0x2000, 0x200A),
+    (0x2028, 0x2029),
+    (0x202F, 0x202F),
+    (0x205F, 0x205F),
+    (0x3000, 0x3000),
+    (0xFEFF, 0xFEFF),
+];
+
+// The ranges not covered by `ranges`, which must be sorted and disjoint.
+fn complement(ranges: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut complement = vec![];
+    let mut start = 0;
+    for &(low, high) in ranges {
+        if low > start {
+            complement.push((start, low - 1));
+        }
+        start = high + 1;
+    }
+    if start <= char::MAX as u32 {
+        complement.push((start, char::MAX as u32));
+    }
+    complement
+}
+
+fn in_ranges(ranges: &[(u32, u32)], c: char) -> bool {
+    ranges.iter().any(|&(low, high)| (low..=high).contains(&(c as u32)))
+}
+
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.cursor).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.cursor += 1;
+        Some(c)
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() != Some(expected) {
+            return false;
+        }
+        self.cursor += 1;
+        true
+    }
+
+    fn parse_alternation(&mut self) -> Option<Node> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.consume('|') {
+            branches.push(self.parse_concat()?);
+        }
+        if branches.len() == 1 {
+            return branches.pop();
+        }
+        Some(Node::Alternation(branches))
+    }
+
+    fn parse_concat(&mut self) -> Option<Node> {
+        let mut nodes = vec![];
+        while !matches!(self.peek(), None | Some('|' | ')')) {
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_quantifier(atom)?);
+        }
+        Some(Node::Concat(nodes))
+    }
+
+    fn parse_atom(&mut self) -> Option<Node> {
+        let atom = match self.next()? {
+            '(' => {
+                if self.consume('?') && !self.consume(':') {
+                    return None;
+                }
+                let group = self.parse_alternation()?;
+                if !self.consume(')') {
+                    return None;
+                }
+                group
+            }
+            '[' => self.parse_class()?,
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '\\' => match self.next()? {
+                'b' => Node::WordBoundary(true),
+                'B' => Node::WordBoundary(false),
+                escaped => match self.parse_escape(escaped)? {
+                    Ok(c) => Node::Char(c),
+                    Err(ranges) => Node::Class { ranges, negated: false },
+                },
+            },
+            '*' | '+' | '?' => return None,
+            c => Node::Char(c),
+        };
+        Some(atom)
+    }
+
+    // Returns a character, or the ranges of a class escape such as `\d`.
+    fn parse_escape(&mut self, escaped: char) -> Option<Result<char, Vec<(u32, u32)>>> {
+        let c = match escaped {
+            'd' => return Some(Err(DIGIT.to_vec())),
+            'D' => return Some(Err(complement(DIGIT))),
+            'w' => return Some(Err(WORD.to_vec())),
+            'W' => return Some(Err(complement(WORD))),
+            's' => return Some(Err(SPACE.to_vec())),
+            'S' => return Some(Err(complement(SPACE))),
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'f' => '\u{c}',
+            'v' => '\u{b}',
+            '0' => '\0',
+            'x' => self.parse_hex(2)?,
+            'u' => self.parse_hex(4)?,
+            c if c.is_ascii_alphanumeric() => return None,
+            c => c,
+        };
+        Some(Ok(c))
+    }
+
+    fn parse_hex(&mut self, digits: usize) -> Option<char> {
+        let hex: String = self.chars.get(self.cursor..self.cursor + digits)?.iter().collect();
+        self.cursor += digits;
+        char::from_u32(u32::from_str_radix(&hex, 16).ok()?)
+    }
+
+    fn parse_class(&mut self) -> Option<Node> {
+        let negated = self.consume('^');
+        let mut ranges = vec![];
+        loop {
+            let low = match self.next()? {
+                ']' => return Some(Node::Class { ranges, negated }),
+                '\\' => match self.next()? {
+                    'b' => '\u{8}',
+                    escaped => match self.parse_escape(escaped)? {
+                        Ok(c) => c,
+                        Err(escape) => {
+                            ranges.extend(escape);
+                            continue;
+                        }
+                    },
+                },
+                c => c,
+            };
+            let high = match (self.peek(), self.chars.get(self.cursor + 1)) {
+                (Some('-'), Some(&high)) if high != ']' => {
+                    self.cursor += 2;
+                    match high {
+                        '\\' => {
+                            let escaped = self.next()?;
+                            self.parse_escape(escaped)?.ok()?
+                        }
+                        high => high,
+                    }
+                }
+                _ => low,
+            };
+            if high < low {
+                return None;
+            }
+            ranges.push((low as u32, high as u32));
+        }
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Option<Node> {
+        let start = self.cursor;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match self.parse_braces() {
+                Some(bounds) => bounds,
+                // Not a quantifier, so the brace is read as a literal.
+                None => {
+                    self.cursor = start;
+                    return Some(atom);
+                }
+            },
+            _ => return Some(atom),
+        };
+        if max.is_some_and(|max| max < min) {
+            return None;
+        }
+        if self.cursor == start {
+            self.cursor += 1;
+        }
+        let greedy = !self.consume('?');
+        Some(Node::Repeat(Repeat { node: Box::new(atom), min, max, greedy }))
+    }
+
+    // Parses `{n}`, `{n,}` or `{n,m}`, leaving the cursor after the brace.
+    fn parse_braces(&mut self) -> Option<(u32, Option<u32>)> {
+        self.cursor += 1;
+        let min = self.parse_decimal()?;
+        let max = match self.consume(',') {
+            true if self.peek() == Some('}') => None,
+            true => Some(self.parse_decimal()?),
+            false => Some(min),
+        };
+        if !self.consume('}') {
+            return None;
+        }
+        Some((min, max))
+    }
+
+    fn parse_decimal(&mut self) -> Option<u32> {
+        let start = self.cursor;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.cursor += 1;
+        }
+        self.chars[start..self.cursor].iter().collect::<String>().parse().ok()
+    }
+}
+
+fn is_word_at(text: &[char], at: Option<usize>) -> bool {
+    at.and_then(|at| text.get(at)).is_some_and(|c| in_ranges(WORD, *c))
+}
+
+#[derive(Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class { ranges: Vec<(u32, u32)>, negated: bool },
+    Start,
+    End,
+    WordBoundary(bool),
+    // Continues at the first target, and at the second if that fails.
+    Split(usize, usize),
+    Jump(usize),
+    // Records the position at the start of an optional iteration of a
+    // quantifier in a slot.
+    Mark(usize),
+    // Fails if the iteration begun at the matching `Mark` matched the empty
+    // string, which ends the loop as in ECMA-262.
+    Progress(usize),
+    Match,
+}
+
+// Patterns whose quantifiers unroll to more instructions are rejected.
+const MAX_PROGRAM_LENGTH: usize = 100_000;
+
+struct Compiler {
+    program: Vec<Inst>,
+    slots: usize,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> usize {
+        self.program.push(inst);
+        self.program.len() - 1
+    }
+
+    // Points the Split or Jump at `at` to `target`, after `target` is known.
+    fn patch(&mut self, at: usize, target: usize) {
+        match &mut self.program[at] {
+            Inst::Split(_, second) => *second = target,
+            Inst::Jump(to) => *to = target,
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile(&mut self, node: &Node) -> Option<()> {
+        if self.program.len() > MAX_PROGRAM_LENGTH {
+            return None;
+        }
+        match node {
+            Node::Char(c) => drop(self.emit(Inst::Char(*c))),
+            Node::Any => drop(self.emit(Inst::Any)),
+            Node::Class { ranges, negated } => drop(self.emit(Inst::Class { ranges: ranges.clone(), negated: *negated })),
+            Node::Start => drop(self.emit(Inst::Start)),
+            Node::End => drop(self.emit(Inst::End)),
+            Node::WordBoundary(expected) => drop(self.emit(Inst::WordBoundary(*expected))),
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node)?;
+                }
+            }
+            Node::Alternation(branches) => {
+                let mut jumps = vec![];
+                for (i, branch) in branches.iter().enumerate() {
+                    let split = (i + 1 < branches.len()).then(|| self.emit(Inst::Split(self.program.len() + 1, 0)));
+                    self.compile(branch)?;
+                    jumps.push(self.emit(Inst::Jump(0)));
+                    if let Some(split) = split {
+                        self.patch(split, self.program.len());
+                    }
+                }
+                for jump in jumps {
+                    self.patch(jump, self.program.len());
+                }
+            }
+            Node::Repeat(repeat) => self.compile_repeat(repeat)?,
+        }
+        Some(())
+    }
+
+    // The required iterations are unrolled. Each optional one is a Split
+    // between the iteration and the end of the quantifier, tried in the
+    // order the quantifier prefers, and an unbounded quantifier loops back
+    // to its Split.
+    fn compile_repeat(&mut self, repeat: &Repeat) -> Option<()> {
+        for _ in 0..repeat.min {
+            self.compile(&repeat.node)?;
+        }
+        let optional = repeat.max.map(|max| max - repeat.min);
+        let mut exits = vec![];
+        for _ in 0..optional.unwrap_or(1) {
+            let split = self.emit(Inst::Split(0, 0));
+            exits.push(split);
+            let slot = self.slots;
+            self.slots += 1;
+            self.emit(Inst::Mark(slot));
+            self.compile(&repeat.node)?;
+            self.emit(Inst::Progress(slot));
+            if optional.is_none() {
+                self.emit(Inst::Jump(split));
+            }
+        }
+        let end = self.program.len();
+        for split in exits {
+            self.program[split] = match repeat.greedy {
+                true => Inst::Split(split + 1, end),
+                false => Inst::Split(end, split + 1),
+            };
+        }
+        Some(())
+    }
+}
+
+// Where to resume when the current path fails: another alternative, or the
+// value a slot had before a `Mark` on the failed path changed it.
+enum Backtrack {
+    Resume { pc: usize, at: usize },
+    Restore { slot: usize, value: usize },
+}
+
+fn matches_at(program: &[Inst], slots: &mut [usize], text: &[char], start: usize) -> bool {
+    let mut backtrack: Vec<Backtrack> = vec![];
+    let (mut pc, mut at) = (0, start);
+    loop {
+        let matched = match &program[pc] {
+            Inst::Char(c) => text.get(at) == Some(c),
+            Inst::Any => text.get(at).is_some_and(|c| !is_line_terminator(*c)),
+            Inst::Class { ranges, negated } => text.get(at).is_some_and(|c| in_ranges(ranges, *c) != *negated),
+            Inst::Start => at == 0,
+            Inst::End => at == text.len(),
+            Inst::WordBoundary(expected) => (is_word_at(text, at.checked_sub(1)) != is_word_at(text, Some(at))) == *expected,
+            Inst::Split(first, second) => {
+                backtrack.push(Backtrack::Resume { pc: *second, at });
+                pc = *first;
+                continue;
+            }
+            Inst::Jump(target) => {
+                pc = *target;
+                continue;
+            }
+            Inst::Mark(slot) => {
+                backtrack.push(Backtrack::Restore { slot: *slot, value: slots[*slot] });
+                slots[*slot] = at;
+                true
+            }
+            Inst::Progress(slot) => slots[*slot] != at,
+            Inst::Match => return true,
+        };
+        if matched {
+            if matches!(program[pc], Inst::Char(_) | Inst::Any | Inst::Class { .. }) {
+                at += 1;
+            }
+            pc += 1;
+            continue;
+        }
+        loop {
+            match backtrack.pop() {
+                None => return false,
+                Some(Backtrack::Restore { slot, value }) => slots[slot] = value,
+                Some(Backtrack::Resume { pc: resume, at: position }) => {
+                    (pc, at) = (resume, position);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+    slots: usize,
+}
+
+impl Regex {
+    /// Compiles `pattern`, or returns None if it is malformed or uses
+    /// syntax this matcher does not support.
+    pub(crate) fn new(pattern: &str) -> Option<Regex> {
+        let mut parser = RegexParser { chars: pattern.chars().collect(), cursor: 0 };
+        let root = parser.parse_alternation()?;
+        if parser.cursor != parser.chars.len() {
+            return None;
+        }
+        let mut compiler = Compiler { program: vec![], slots: 0 };
+        compiler.compile(&root)?;
+        compiler.emit(Inst::Match);
+        Some(Regex { program: compiler.program, slots: compiler.slots })
+    }
+
+    /// Returns whether the pattern matches anywhere in `text`. Patterns
+    /// are not implicitly anchored.
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let mut slots = vec![0; self.slots];
+        (0..=text.len()).any(|start| matches_at(&self.program, &mut slots, &text, start))
+    }
+}
+
+#[test]
+fn test_regex_matches() {
+    let cases = [
+        ("^a*$", "aaa", true),
+        ("^a*$", "abc", false),
+        ("^a*$", "", true),
+        ("a+", "xxaxx", true),
+        ("a+", "xxx", false),
+        ("f.o", "xfooy", true),
+        ("f.o", "f\no", false),
+        ("^á", "ábc", true),
+        ("^[a-z]+\\d{2,3}$", "abc12", true),
+        ("^[a-z]+\\d{2,3}$", "abc1234", false),
+        ("^[^0-9]*$", "abc", true),
+        ("^[^0-9]*$", "ab1", false),
+        ("^(cat|dog)s?$", "dogs", true),
+        ("^(cat|dog)s?$", "cow", false),
+        ("^(?:ab)+$", "ababab", true),
+        ("^(?:ab)+$", "ababa", false),
+        ("^\\w+@\\w+\\.com$", "me@host.com", true),
+        ("\\bis\\b", "this is", true),
+        ("\\bis\\b", "this", false),
+        ("^[\\d\\s-]+$", "12 34-5", true),
+        ("^\\S+$", "a b", false),
+        ("^a{2}$", "aa", true),
+        ("^a{2,}$", "a", false),
+        ("^x{1$", "x{1", true),
+        ("^(a|ab)(c|bcd)(d*)$", "abcd", true),
+        ("^(a*)*$", "aaab", false),
+        ("^a+?b$", "aaab", true),
+        ("^\\u00e9\\x41$", "éA", true),
+        ("^[\\-.]$", "-", true),
+    ];
+    for (pattern, text, expected) in cases {
+        let regex = Regex::new(pattern).unwrap();
+        assert_eq!(regex.is_match(text), expected, "pattern: {:?}, text: {:?}", pattern, text);
+    }
+}
+
+#[test]
+fn test_regex_rejects_unsupported_patterns() {
+    for pattern in ["(a", "a)", "[a", "*a", "a**", "(?=a)", "(?!a)", "\\1", "\\p{L}", "[z-a]", "a{3,1}"] {
+        assert!(Regex::new(pattern).is_none(), "pattern: {:?}", pattern);
+    }
+}
+
+#[test]
+fn test_regex_long_input() {
+    let long = "a".repeat(200_000);
+    let cases = [
+        ("^a*$", long.clone(), true),
+        ("^a*$", format!("{}b", long), false),
+        ("^a+?$", long.clone(), true),
+        ("^(?:a|b)*c$", long.clone(), false),
+        ("^(a*)*$", long.clone(), true),
+        ("^[a-z]{1,}$", long.clone(), true),
+    ];
+    for (pattern, text, expected) in cases {
+        assert_eq!(Regex::new(pattern).unwrap().is_match(&text), expected, "pattern: {:?}", pattern);
+    }
+    assert!(Regex::new("(?:a{1000}){1000}").is_none());
+}
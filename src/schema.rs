@@ -0,0 +1,326 @@
+use crate::object::JsonObject;
+use crate::ord::compare_numbers;
+use crate::parser::JsonValue;
+use crate::pointer::escape_token;
+use crate::prelude::*;
+use crate::regex::Regex;
+use crate::value::article_and_type;
+use core::cmp::Ordering;
+
+/// A constraint from a JSON Schema that a value does not satisfy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// JSON Pointer to the part of the value that failed, which is empty
+    /// for the value itself.
+    pub path: String,
+    pub message: String,
+}
+
+fn error(path: &str, message: String) -> ValidationError {
+    ValidationError { path: path.to_string(), message }
+}
+
+fn type_matches(name: &str, value: &JsonValue) -> bool {
+    match (name, value) {
+        ("null", JsonValue::Null) => true,
+        ("boolean", JsonValue::Bool(_)) => true,
+        ("string", JsonValue::String(_)) => true,
+        ("array", JsonValue::Array(_)) => true,
+        ("object", JsonValue::Object(_)) => true,
+        ("number", JsonValue::Number(_) | JsonValue::Number64(_) | JsonValue::Float(_)) => true,
+        ("integer", JsonValue::Number(_) | JsonValue::Number64(_)) => true,
+        ("integer", JsonValue::Float(float)) => float.is_finite() && float % 1.0 == 0.0,
+        #[cfg(feature = "bignum")]
+        ("number", JsonValue::BigNum(_)) => true,
+        #[cfg(feature = "bignum")]
+        ("integer", JsonValue::BigNum(value)) => value.is_integer(),
+        _ => false,
+    }
+}
+
+// JSON Schema compares numbers by value, so `1` and `1.0` are equal.
+fn schema_equal(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Array(a), JsonValue::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| schema_equal(a, b)),
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| schema_equal(a, b)))
+        }
+        _ => compare_numbers(a, b).map_or(a == b, |ordering| ordering == Ordering::Equal),
+    }
+}
+
+// Decodes the `%XX` escapes that URI fragments use.
+fn percent_decode(fragment: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = fragment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte != b'%' {
+            bytes.push(byte);
+            rest = tail;
+            continue;
+        }
+        let hex = core::str::from_utf8(tail.get(..2)?).ok()?;
+        bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        rest = &tail[2..];
+    }
+    String::from_utf8(bytes).ok()
+}
+
+struct Validator<'s, 'v> {
+    root: &'s JsonValue,
+    // The `$ref` keywords being expanded and the values they apply to. A
+    // reference that comes back to the same pair adds no new constraints.
+    active_refs: Vec<(&'s JsonValue, &'v JsonValue)>,
+}
+
+impl<'s, 'v> Validator<'s, 'v> {
+    fn is_valid(&mut self, schema: &'s JsonValue, value: &'v JsonValue, path: &str) -> bool {
+        let mut errors = vec![];
+        self.validate(schema, value, path, &mut errors);
+        errors.is_empty()
+    }
+
+    fn validate(&mut self, schema: &'s JsonValue, value: &'v JsonValue, path: &str, errors: &mut Vec<ValidationError>) {
+        let keywords = match schema {
+            JsonValue::Bool(false) => return errors.push(error(path, "no value is allowed here".to_string())),
+            JsonValue::Object(keywords) => keywords,
+            _ => return,
+        };
+        // In draft-07 a `$ref` replaces all of its sibling keywords.
+        if let Some(reference) = keywords.get("$ref") {
+            return self.validate_ref(reference, value, path, errors);
+        }
+        if let Some(expected) = keywords.get("type") {
+            let names: Vec<&str> = match expected {
+                JsonValue::String(name) => vec![name],
+                JsonValue::Array(names) => names.iter().filter_map(JsonValue::as_str).collect(),
+                _ => vec![],
+            };
+            if !names.iter().any(|name| type_matches(name, value)) {
+                errors.push(error(path, format!("expected {}, found {}", names.join(" or "), article_and_type(value))));
+            }
+        }
+        if let Some(JsonValue::Array(allowed)) = keywords.get("enum") {
+            if !allowed.iter().any(|allowed| schema_equal(allowed, value)) {
+                errors.push(error(path, "value is not one of the allowed values".to_string()));
+            }
+        }
+        if let Some(minimum) = keywords.get("minimum") {
+            if compare_numbers(value, minimum) == Some(Ordering::Less) {
+                errors.push(error(path, format!("{} is less than the minimum of {}", value, minimum)));
+            }
+        }
+        if let Some(maximum) = keywords.get("maximum") {
+            if compare_numbers(value, maximum) == Some(Ordering::Greater) {
+                errors.push(error(path, format!("{} is greater than the maximum of {}", value, maximum)));
+            }
+        }
+        if let JsonValue::String(string) = value {
+            self.validate_string(keywords, string, path, errors);
+        }
+        if let JsonValue::Array(array) = value {
+            self.validate_array(keywords, array, path, errors);
+        }
+        if let JsonValue::Object(object) = value {
+            self.validate_object(keywords, object, path, errors);
+        }
+        self.validate_combinators(keywords, value, path, errors);
+    }
+
+    fn validate_ref(&mut self, reference: &'s JsonValue, value: &'v JsonValue, path: &str, errors: &mut Vec<ValidationError>) {
+        let target = reference
+            .as_str()
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(percent_decode)
+            .and_then(|fragment| self.root.pointer(&fragment));
+        let Some(target) = target else {
+            return errors.push(error(path, format!("cannot resolve $ref {}", reference)));
+        };
+        if self.active_refs.iter().any(|(schema, active)| core::ptr::eq(*schema, target) && core::ptr::eq(*active, value)) {
+            return;
+        }
+        self.active_refs.push((target, value));
+        self.validate(target, value, path, errors);
+        self.active_refs.pop();
+    }
+
+    fn validate_string(&mut self, keywords: &JsonObject, string: &str, path: &str, errors: &mut Vec<ValidationError>) {
+        // Lengths count characters, not bytes.
+        let length = string.chars().count() as i64;
+        if let Some(min_length) = keywords.get("minLength").and_then(JsonValue::as_i64) {
+            if length < min_length {
+                errors.push(error(path, format!("string is shorter than {} characters", min_length)));
+            }
+        }
+        if let Some(max_length) = keywords.get("maxLength").and_then(JsonValue::as_i64) {
+            if length > max_length {
+                errors.push(error(path, format!("string is longer than {} characters", max_length)));
+            }
+        }
+        if let Some(pattern) = keywords.get("pattern").and_then(JsonValue::as_str) {
+            match Regex::new(pattern) {
+                Some(regex) if !regex.is_match(string) => {
+                    errors.push(error(path, format!("string does not match the pattern {:?}", pattern)))
+                }
+                Some(_) => {}
+                None => errors.push(error(path, format!("unsupported pattern {:?}", pattern))),
+            }
+        }
+    }
+
+    fn validate_array(&mut self, keywords: &'s JsonObject, array: &'v [JsonValue], path: &str, errors: &mut Vec<ValidationError>) {
+        let length = array.len() as i64;
+        if let Some(min_items) = keywords.get("minItems").and_then(JsonValue::as_i64) {
+            if length < min_items {
+                errors.push(error(path, format!("array has fewer than {} items", min_items)));
+            }
+        }
+        if let Some(max_items) = keywords.get("maxItems").and_then(JsonValue::as_i64) {
+            if length > max_items {
+                errors.push(error(path, format!("array has more than {} items", max_items)));
+            }
+        }
+        for (index, item) in array.iter().enumerate() {
+            // An array of schemas applies to the items at the same
+            // positions and `additionalItems` to the items after them. A
+            // single schema applies to every item.
+            let schema = match keywords.get("items") {
+                Some(JsonValue::Array(schemas)) => schemas.get(index).or(keywords.get("additionalItems")),
+                items => items,
+            };
+            if let Some(schema) = schema {
+                self.validate(schema, item, &format!("{}/{}", path, index), errors);
+            }
+        }
+    }
+
+    fn validate_object(&mut self, keywords: &'s JsonObject, object: &'v JsonObject, path: &str, errors: &mut Vec<ValidationError>) {
+        if let Some(JsonValue::Array(required)) = keywords.get("required") {
+            for key in required.iter().filter_map(JsonValue::as_str) {
+                if !object.contains_key(key) {
+                    errors.push(error(path, format!("missing required property {:?}", key)));
+                }
+            }
+        }
+        let properties = keywords.get("properties").and_then(JsonValue::as_object);
+        let mut patterns = vec![];
+        for (pattern, schema) in keywords.get("patternProperties").and_then(JsonValue::as_object).into_iter().flatten() {
+            match Regex::new(pattern) {
+                Some(regex) => patterns.push((regex, schema)),
+                None => errors.push(error(path, format!("unsupported pattern {:?}", pattern))),
+            }
+        }
+        let additional = keywords.get("additionalProperties");
+        for (key, member) in object.iter() {
+            let member_path = format!("{}/{}", path, escape_token(key));
+            let mut matched = false;
+            if let Some(schema) = properties.and_then(|properties| properties.get(key)) {
+                matched = true;
+                self.validate(schema, member, &member_path, errors);
+            }
+            for (regex, schema) in &patterns {
+                if regex.is_match(key) {
+                    matched = true;
+                    self.validate(schema, member, &member_path, errors);
+                }
+            }
+            match additional {
+                _ if matched => {}
+                Some(JsonValue::Bool(false)) => errors.push(error(path, format!("additional property {:?} is not allowed", key))),
+                Some(schema) => self.validate(schema, member, &member_path, errors),
+                None => {}
+            }
+        }
+    }
+
+    fn validate_combinators(&mut self, keywords: &'s JsonObject, value: &'v JsonValue, path: &str, errors: &mut Vec<ValidationError>) {
+        if let Some(JsonValue::Array(schemas)) = keywords.get("allOf") {
+            for schema in schemas {
+                self.validate(schema, value, path, errors);
+            }
+        }
+        if let Some(JsonValue::Array(schemas)) = keywords.get("anyOf") {
+            if !schemas.iter().any(|schema| self.is_valid(schema, value, path)) {
+                errors.push(error(path, "value does not match any schema in anyOf".to_string()));
+            }
+        }
+        if let Some(JsonValue::Array(schemas)) = keywords.get("oneOf") {
+            let matches = schemas.iter().filter(|schema| self.is_valid(schema, value, path)).count();
+            if matches != 1 {
+                errors.push(error(path, format!("value matches {} schemas in oneOf instead of one", matches)));
+            }
+        }
+        if let Some(schema) = keywords.get("not") {
+            if self.is_valid(schema, value, path) {
+                errors.push(error(path, "value matches the schema in not".to_string()));
+            }
+        }
+    }
+}
+
+impl JsonValue {
+    /// Validates the value against a draft-07 JSON Schema and returns every
+    /// failing constraint. Supported keywords are `type`, `enum`,
+    /// `minimum`, `maximum`, `minLength`, `maxLength`, `pattern`, `items`,
+    /// `additionalItems`, `minItems`, `maxItems`, `properties`, `patternProperties`,
+    /// `additionalProperties`, `required`, `allOf`, `anyOf`, `oneOf`, `not`
+    /// and `$ref` to a fragment of the same schema, such as
+    /// `#/definitions/name`. Other keywords are ignored.
+    pub fn validate_schema(&self, schema: &JsonValue) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator { root: schema, active_refs: vec![] };
+        let mut errors = vec![];
+        validator.validate(schema, self, "", &mut errors);
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+#[test]
+fn test_validate_schema_collects_every_error() {
+    let schema = crate::parse_str(
+        r#"{
+            "type": "object",
+            "required": ["name", "age"],
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"type": "string", "pattern": "^[a-z]+$"}, "maxItems": 2}
+            },
+            "additionalProperties": false
+        }"#,
+    )
+    .unwrap();
+    let value = crate::parse_str(r#"{"name": "", "tags": ["ok", "Not OK", 3], "a/b": null}"#).unwrap();
+    let mut errors = value.validate_schema(&schema).unwrap_err();
+    errors.sort_by(|a, b| (&a.path, &a.message).cmp(&(&b.path, &b.message)));
+    let expected = [
+        ("", "additional property \"a/b\" is not allowed"),
+        ("", "missing required property \"age\""),
+        ("/name", "string is shorter than 1 characters"),
+        ("/tags", "array has more than 2 items"),
+        ("/tags/1", "string does not match the pattern \"^[a-z]+$\""),
+        ("/tags/2", "expected string, found a number"),
+    ];
+    let expected: Vec<ValidationError> = expected.iter().map(|(path, message)| error(path, message.to_string())).collect();
+    assert_eq!(errors, expected);
+    assert_eq!(crate::parse_str(r#"{"name": "x", "age": 3}"#).unwrap().validate_schema(&schema), Ok(()));
+}
+
+#[test]
+fn test_validate_schema_refs() {
+    let schema = crate::parse_str(
+        r##"{
+            "definitions": {"node": {"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#/definitions/node"}}}, "required": ["id"]}},
+            "$ref": "#/definitions/node"
+        }"##,
+    )
+    .unwrap();
+    let tree = crate::parse_str(r#"{"id": 1, "children": [{"id": 2}, {"children": []}]}"#).unwrap();
+    assert_eq!(tree.validate_schema(&schema), Err(vec![error("/children/1", "missing required property \"id\"".to_string())]));
+    let circular = crate::parse_str(r##"{"definitions": {"a": {"$ref": "#/definitions/b"}, "b": {"$ref": "#/definitions/a"}}, "$ref": "#/definitions/a"}"##).unwrap();
+    assert_eq!(tree.validate_schema(&circular), Ok(()));
+    let missing = crate::parse_str(r##"{"$ref": "#/definitions/missing"}"##).unwrap();
+    assert_eq!(tree.validate_schema(&missing), Err(vec![error("", "cannot resolve $ref \"#/definitions/missing\"".to_string())]));
+}
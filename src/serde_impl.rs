@@ -0,0 +1,130 @@
+use crate::object::JsonObject;
+use crate::parser::JsonValue;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+impl Serialize for JsonValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(value) => serializer.serialize_bool(*value),
+            JsonValue::Number(value) => serializer.serialize_i64(*value),
+            JsonValue::Number64(value) => serializer.serialize_u64(*value),
+            // serde has no arbitrary precision numbers.
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(value) => serializer.serialize_f64(bigdecimal::ToPrimitive::to_f64(value).unwrap()),
+            JsonValue::Float(value) => serializer.serialize_f64(*value),
+            JsonValue::String(value) => serializer.serialize_str(value),
+            JsonValue::Array(array) => {
+                let mut seq = serializer.serialize_seq(Some(array.len()))?;
+                for element in array {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(map) => {
+                let mut members = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map {
+                    members.serialize_entry(key, value)?;
+                }
+                members.end()
+            }
+        }
+    }
+}
+
+struct JsonValueVisitor;
+
+impl<'de> Visitor<'de> for JsonValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Float(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            array.push(element);
+        }
+        Ok(JsonValue::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut object = JsonObject::new();
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(JsonValue::Object(object))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<JsonValue, D::Error> {
+        deserializer.deserialize_any(JsonValueVisitor)
+    }
+}
+
+#[cfg(test)]
+const SAMPLE: &str = r#"{"null": null, "bool": true, "int": -42, "float": 2.5, "string": "café \"quoted\"", "array": [1, [], {}], "object": {"nested": [false]}}"#;
+
+#[test]
+fn test_serde_json_round_trip() {
+    let value = crate::parse_str(SAMPLE).unwrap();
+    let text = serde_json::to_string(&value).unwrap();
+    assert_eq!(serde_json::from_str::<JsonValue>(&text).unwrap(), value);
+    assert_eq!(crate::parse_str(&text), Ok(value));
+}
+
+#[test]
+fn test_serde_json_value_pipeline() {
+    let value = crate::parse_str(SAMPLE).unwrap();
+    let serde_value = serde_json::to_value(&value).unwrap();
+    assert_eq!(serde_value, serde_json::from_str::<serde_json::Value>(SAMPLE).unwrap());
+    assert_eq!(serde_json::from_value::<JsonValue>(serde_value).unwrap(), value);
+}
+
+#[test]
+fn test_serde_integers_above_i64() {
+    assert_eq!(serde_json::from_str::<JsonValue>("18446744073709551615").unwrap(), JsonValue::Number64(u64::MAX));
+    assert_eq!(serde_json::to_string(&JsonValue::Number64(u64::MAX)).unwrap(), "18446744073709551615");
+    assert_eq!(serde_json::from_str::<JsonValue>("9223372036854775807").unwrap(), JsonValue::Number(i64::MAX));
+}
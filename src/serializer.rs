@@ -0,0 +1,846 @@
+use crate::parser::JsonValue;
+use crate::prelude::*;
+use core::fmt;
+
+pub(crate) fn write_escaped_string(f: &mut impl fmt::Write, value: &str) -> fmt::Result {
+    write_escaped(f, value, false)
+}
+
+// With `html_safe`, also escapes the characters that could end a
+// `<script>` element or start an entity when the JSON is embedded in HTML.
+fn write_escaped(f: &mut impl fmt::Write, value: &str, html_safe: bool) -> fmt::Result {
+    f.write_char('"')?;
+    for ch in value.chars() {
+        match ch {
+            '<' if html_safe => f.write_str("\\u003C")?,
+            '>' if html_safe => f.write_str("\\u003E")?,
+            '&' if html_safe => f.write_str("\\u0026")?,
+            '/' if html_safe => f.write_str("\\u002F")?,
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => f.write_char(ch)?,
+        }
+    }
+    f.write_char('"')
+}
+
+// JSON has no representation for NaN or the infinities, so they are
+// written as the strings "NaN", "Infinity" and "-Infinity", or as the bare
+// literals for JSON5. Finite values use the shortest representation that
+// parses back to the same f64, and always keep a fraction or exponent so
+// that they are read back as a Float rather than a Number.
+pub(crate) fn write_float(f: &mut impl fmt::Write, value: f64, json5: bool) -> fmt::Result {
+    if !value.is_finite() {
+        let literal = if value.is_nan() {
+            "NaN"
+        } else if value > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
+        };
+        return if json5 { f.write_str(literal) } else { write!(f, "\"{}\"", literal) };
+    }
+    write!(f, "{:?}", value)
+}
+
+// Writes the compact form that `Display`, `to_vec` and
+// `serialize_to_writer` share.
+fn serialize_inner(value: &JsonValue, encoder: &mut impl fmt::Write) -> fmt::Result {
+    match value {
+        JsonValue::Null => encoder.write_str("null"),
+        JsonValue::Bool(value) => write!(encoder, "{}", value),
+        JsonValue::Number(value) => write!(encoder, "{}", value),
+        JsonValue::Number64(value) => write!(encoder, "{}", value),
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(value) => crate::bignum::write_bignum(encoder, value),
+        JsonValue::Float(value) => write_float(encoder, *value, false),
+        JsonValue::String(value) => write_escaped_string(encoder, value),
+        JsonValue::Array(array) => {
+            encoder.write_str("[")?;
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    encoder.write_str(",")?;
+                }
+                serialize_inner(value, encoder)?;
+            }
+            encoder.write_str("]")
+        }
+        JsonValue::Object(map) => {
+            encoder.write_str("{")?;
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    encoder.write_str(",")?;
+                }
+                write_escaped_string(encoder, key)?;
+                encoder.write_str(":")?;
+                serialize_inner(value, encoder)?;
+            }
+            encoder.write_str("}")
+        }
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serialize_inner(self, f)
+    }
+}
+
+// Writes UTF-8 straight to bytes or to an `io::Write`, which keeps the
+// first I/O error since `fmt::Error` cannot carry it.
+enum JsonEncoder<'a> {
+    Bytes(&'a mut Vec<u8>),
+    #[cfg(feature = "std")]
+    Writer { writer: &'a mut dyn std::io::Write, error: Option<std::io::Error> },
+}
+
+impl fmt::Write for JsonEncoder<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self {
+            JsonEncoder::Bytes(bytes) => {
+                bytes.extend_from_slice(s.as_bytes());
+                Ok(())
+            }
+            #[cfg(feature = "std")]
+            JsonEncoder::Writer { writer, error } => writer.write_all(s.as_bytes()).map_err(|err| {
+                *error = Some(err);
+                fmt::Error
+            }),
+        }
+    }
+}
+
+// Runs `write` against an encoder for `writer` and returns the I/O error
+// that stopped it, if any.
+#[cfg(feature = "std")]
+fn encode_to(writer: &mut dyn std::io::Write, write: impl FnOnce(&mut JsonEncoder) -> fmt::Result) -> std::io::Result<()> {
+    let mut encoder = JsonEncoder::Writer { writer, error: None };
+    let result = write(&mut encoder);
+    let JsonEncoder::Writer { error, .. } = encoder else { unreachable!() };
+    result.map_err(|_| error.unwrap())
+}
+
+impl JsonValue {
+    /// Same as `to_string`: no whitespace and no trailing newline.
+    pub fn to_string_compact(&self) -> String {
+        self.to_string()
+    }
+
+    /// Pretty prints with `indent` spaces per nesting level and a trailing
+    /// newline. An `indent` of 0 still puts every element and member on a
+    /// line of its own.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        PrettyPrinter::new().with_indent(indent).with_trailing_newline(true).print(self)
+    }
+
+    pub fn serialize_with(&self, options: &SerializeOptions) -> String {
+        let mut builder = String::new();
+        options.write_value(&mut builder, self, 0);
+        if options.trailing_newline {
+            builder.push('\n');
+        }
+        builder
+    }
+
+    /// Same as `to_string`, with `SerializeOptions::html_safe` escaping.
+    pub fn to_html_safe_string(&self) -> String {
+        self.serialize_with(&SerializeOptions::new().html_safe(true))
+    }
+
+    /// Serializes the value compactly, like `to_string`, but as bytes.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        serialize_inner(self, &mut JsonEncoder::Bytes(&mut bytes)).unwrap();
+        bytes
+    }
+
+    /// Writes the compact serialization to `writer` as it is produced.
+    /// The output is written in many small pieces, so unbuffered writers
+    /// such as files and sockets should be wrapped in a `BufWriter`.
+    #[cfg(feature = "std")]
+    pub fn serialize_to_writer(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        encode_to(writer, |encoder| serialize_inner(self, encoder))
+    }
+
+    /// Writes the value through `serializer` one token at a time, so that
+    /// it can be placed inside a document the serializer is streaming.
+    #[cfg(feature = "std")]
+    pub fn serialize_into<W: std::io::Write>(&self, serializer: &mut JsonSerializer<W>) -> std::io::Result<()> {
+        match self {
+            JsonValue::Array(array) => {
+                serializer.begin_array()?;
+                for value in array {
+                    value.serialize_into(serializer)?;
+                }
+                serializer.end_array()
+            }
+            JsonValue::Object(map) => {
+                serializer.begin_object()?;
+                for (key, value) in map.iter() {
+                    serializer.write_key(key)?;
+                    value.serialize_into(serializer)?;
+                }
+                serializer.end_object()
+            }
+            value => serializer.write_scalar(value),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+enum Frame {
+    Array { empty: bool },
+    Object { empty: bool, after_key: bool },
+}
+
+/// Writes compact JSON to `W` token by token, for documents too large to
+/// build as a `JsonValue` first. Commas are inserted automatically.
+///
+/// Every method panics when called where the token is not allowed, such as
+/// `write_key` inside an array or `end_object` to close an array.
+#[cfg(feature = "std")]
+pub struct JsonSerializer<W> {
+    writer: W,
+    stack: Vec<Frame>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> JsonSerializer<W> {
+    /// Like `serialize_to_writer`, the output is written in small pieces,
+    /// so unbuffered writers should be wrapped in a `BufWriter`.
+    pub fn new(writer: W) -> Self {
+        JsonSerializer { writer, stack: vec![] }
+    }
+
+    pub fn begin_object(&mut self) -> std::io::Result<()> {
+        self.before_value("begin_object")?;
+        self.stack.push(Frame::Object { empty: true, after_key: false });
+        self.writer.write_all(b"{")
+    }
+
+    pub fn write_key(&mut self, key: &str) -> std::io::Result<()> {
+        let Some(Frame::Object { empty, after_key: after_key @ false }) = self.stack.last_mut() else {
+            panic!("write_key called {}", self.position());
+        };
+        let comma = !*empty;
+        (*empty, *after_key) = (false, true);
+        encode_to(&mut self.writer, |encoder| {
+            if comma {
+                fmt::Write::write_char(encoder, ',')?;
+            }
+            write_escaped_string(encoder, key)?;
+            fmt::Write::write_char(encoder, ':')
+        })
+    }
+
+    pub fn end_object(&mut self) -> std::io::Result<()> {
+        match self.stack.last() {
+            Some(Frame::Object { after_key: false, .. }) => drop(self.stack.pop()),
+            _ => panic!("end_object called {}", self.position()),
+        }
+        self.writer.write_all(b"}")
+    }
+
+    pub fn begin_array(&mut self) -> std::io::Result<()> {
+        self.before_value("begin_array")?;
+        self.stack.push(Frame::Array { empty: true });
+        self.writer.write_all(b"[")
+    }
+
+    pub fn end_array(&mut self) -> std::io::Result<()> {
+        match self.stack.last() {
+            Some(Frame::Array { .. }) => drop(self.stack.pop()),
+            _ => panic!("end_array called {}", self.position()),
+        }
+        self.writer.write_all(b"]")
+    }
+
+    /// Writes a whole value, which may be an array or an object.
+    pub fn write_value(&mut self, value: &JsonValue) -> std::io::Result<()> {
+        value.serialize_into(self)
+    }
+
+    /// Writes a value that does not need to be wrapped in a `JsonValue`
+    /// first, such as `write_primitive(42)` or `write_primitive("text")`.
+    pub fn write_primitive(&mut self, value: impl Into<JsonValue>) -> std::io::Result<()> {
+        self.write_value(&value.into())
+    }
+
+    /// True once every array and object that was begun has been ended.
+    pub fn is_complete(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_scalar(&mut self, value: &JsonValue) -> std::io::Result<()> {
+        self.before_value("write_value")?;
+        encode_to(&mut self.writer, |encoder| serialize_inner(value, encoder))
+    }
+
+    // Writes the comma before an array element, and checks that an object
+    // member's value follows its key.
+    fn before_value(&mut self, method: &str) -> std::io::Result<()> {
+        match self.stack.last_mut() {
+            None => Ok(()),
+            Some(Frame::Array { empty }) => {
+                let comma = !*empty;
+                *empty = false;
+                if comma {
+                    self.writer.write_all(b",")?;
+                }
+                Ok(())
+            }
+            Some(Frame::Object { after_key: after_key @ true, .. }) => {
+                *after_key = false;
+                Ok(())
+            }
+            Some(Frame::Object { .. }) => panic!("{} called {}", method, self.position()),
+        }
+    }
+
+    fn position(&self) -> &'static str {
+        match self.stack.last() {
+            None => "outside any array or object",
+            Some(Frame::Array { .. }) => "inside an array",
+            Some(Frame::Object { after_key: true, .. }) => "after a key",
+            Some(Frame::Object { after_key: false, .. }) => "where an object key is expected",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrettyPrinter {
+    indent: usize,
+    use_tabs: bool,
+    sorted_keys: bool,
+    trailing_newline: bool,
+    json5: bool,
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        PrettyPrinter {
+            indent: 2,
+            use_tabs: false,
+            sorted_keys: false,
+            trailing_newline: false,
+            json5: false,
+        }
+    }
+}
+
+impl PrettyPrinter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of spaces (or tabs, see `with_tabs`) per nesting level.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn with_tabs(mut self, use_tabs: bool) -> Self {
+        self.use_tabs = use_tabs;
+        self
+    }
+
+    pub fn with_sorted_keys(mut self, sorted_keys: bool) -> Self {
+        self.sorted_keys = sorted_keys;
+        self
+    }
+
+    pub fn with_trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Writes NaN and the infinities as the JSON5 literals `NaN`,
+    /// `Infinity` and `-Infinity` rather than as strings.
+    pub fn with_json5(mut self, json5: bool) -> Self {
+        self.json5 = json5;
+        self
+    }
+
+    pub fn print(&self, value: &JsonValue) -> String {
+        let mut builder = String::new();
+        self.print_value(&mut builder, value, 0);
+        if self.trailing_newline {
+            builder.push('\n');
+        }
+        builder
+    }
+
+    fn push_indent(&self, builder: &mut String, depth: usize) {
+        let ch = if self.use_tabs { '\t' } else { ' ' };
+        builder.extend(core::iter::repeat_n(ch, self.indent * depth));
+    }
+
+    fn print_value(&self, builder: &mut String, value: &JsonValue, depth: usize) {
+        match value {
+            JsonValue::Array(array) if !array.is_empty() => {
+                builder.push('[');
+                for (i, value) in array.iter().enumerate() {
+                    if i > 0 {
+                        builder.push(',');
+                    }
+                    builder.push('\n');
+                    self.push_indent(builder, depth + 1);
+                    self.print_value(builder, value, depth + 1);
+                }
+                builder.push('\n');
+                self.push_indent(builder, depth);
+                builder.push(']');
+            }
+            JsonValue::Object(map) if !map.is_empty() => {
+                let mut entries: Vec<(&String, &JsonValue)> = map.iter().collect();
+                if self.sorted_keys {
+                    entries.sort_by(|a, b| a.0.cmp(b.0));
+                }
+                builder.push('{');
+                for (i, (key, value)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        builder.push(',');
+                    }
+                    builder.push('\n');
+                    self.push_indent(builder, depth + 1);
+                    write_escaped_string(builder, key).unwrap();
+                    builder.push_str(": ");
+                    self.print_value(builder, value, depth + 1);
+                }
+                builder.push('\n');
+                self.push_indent(builder, depth);
+                builder.push('}');
+            }
+            JsonValue::Float(value) => write_float(builder, *value, self.json5).unwrap(),
+            value => builder.push_str(&value.to_string()),
+        }
+    }
+}
+
+/// Settings for `JsonValue::serialize_with`. By default the output is the
+/// same as `to_string`.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    indent: Option<usize>,
+    sorted_keys: bool,
+    omit_nulls: bool,
+    trailing_newline: bool,
+    max_float_precision: Option<u8>,
+    html_safe: bool,
+}
+
+impl SerializeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts every element and member on a line of its own, indented by
+    /// `indent` spaces per nesting level, like `to_string_pretty`.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    pub fn sorted_keys(mut self, sorted_keys: bool) -> Self {
+        self.sorted_keys = sorted_keys;
+        self
+    }
+
+    /// Leaves out object members whose value is null. Null array elements
+    /// are still written, since leaving them out would move the rest.
+    pub fn omit_nulls(mut self, omit_nulls: bool) -> Self {
+        self.omit_nulls = omit_nulls;
+        self
+    }
+
+    pub fn trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Rounds floats to at most `digits` digits after the decimal point.
+    /// The rounded value is still written in its shortest form, so 2.50
+    /// becomes `2.5`.
+    pub fn max_float_precision(mut self, digits: u8) -> Self {
+        self.max_float_precision = Some(digits);
+        self
+    }
+
+    /// Escapes `<`, `>`, `&` and `/` in strings and keys as `\u` escapes,
+    /// so that the output can be embedded in an HTML `<script>` element.
+    pub fn html_safe(mut self, html_safe: bool) -> Self {
+        self.html_safe = html_safe;
+        self
+    }
+
+    fn push_line(&self, builder: &mut String, depth: usize) {
+        if let Some(indent) = self.indent {
+            builder.push('\n');
+            builder.extend(core::iter::repeat_n(' ', indent * depth));
+        }
+    }
+
+    fn write_value(&self, builder: &mut String, value: &JsonValue, depth: usize) {
+        match value {
+            JsonValue::Array(array) if !array.is_empty() => {
+                builder.push('[');
+                for (i, value) in array.iter().enumerate() {
+                    if i > 0 {
+                        builder.push(',');
+                    }
+                    self.push_line(builder, depth + 1);
+                    self.write_value(builder, value, depth + 1);
+                }
+                self.push_line(builder, depth);
+                builder.push(']');
+            }
+            JsonValue::Object(map) => {
+                let mut entries: Vec<(&String, &JsonValue)> =
+                    map.iter().filter(|(_, value)| !(self.omit_nulls && value.is_null())).collect();
+                if self.sorted_keys {
+                    entries.sort_by(|a, b| a.0.cmp(b.0));
+                }
+                builder.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        builder.push(',');
+                    }
+                    self.push_line(builder, depth + 1);
+                    write_escaped(builder, key, self.html_safe).unwrap();
+                    builder.push_str(if self.indent.is_some() { ": " } else { ":" });
+                    self.write_value(builder, value, depth + 1);
+                }
+                if !entries.is_empty() {
+                    self.push_line(builder, depth);
+                }
+                builder.push('}');
+            }
+            JsonValue::Float(value) => {
+                let value = match self.max_float_precision {
+                    Some(digits) if value.is_finite() => format!("{:.*}", digits as usize, value).parse().unwrap(),
+                    _ => *value,
+                };
+                write_float(builder, value, false).unwrap()
+            }
+            JsonValue::String(value) => write_escaped(builder, value, self.html_safe).unwrap(),
+            value => serialize_inner(value, builder).unwrap(),
+        }
+    }
+}
+
+#[test]
+fn test_display_literals() {
+    assert_eq!(JsonValue::Null.to_string(), "null");
+    assert_eq!(JsonValue::Bool(true).to_string(), "true");
+    assert_eq!(JsonValue::Bool(false).to_string(), "false");
+    assert_eq!(JsonValue::Number(-42).to_string(), "-42");
+    assert_eq!(JsonValue::Float(1.5).to_string(), "1.5");
+    assert_eq!(JsonValue::Float(3.0).to_string(), "3.0");
+    assert_eq!(JsonValue::Float(1e300).to_string(), "1e300");
+}
+
+#[test]
+fn test_display_non_finite_floats() {
+    let value = JsonValue::Array(vec![
+        JsonValue::Float(f64::NAN),
+        JsonValue::Float(f64::INFINITY),
+        JsonValue::Float(f64::NEG_INFINITY),
+    ]);
+    assert_eq!(value.to_string(), r#"["NaN","Infinity","-Infinity"]"#);
+    assert_eq!(PrettyPrinter::new().with_indent(0).print(&value), "[\n\"NaN\",\n\"Infinity\",\n\"-Infinity\"\n]");
+    let json5 = PrettyPrinter::new().with_indent(0).with_json5(true).print(&value);
+    assert_eq!(json5, "[\nNaN,\nInfinity,\n-Infinity\n]");
+    let config = crate::JsonParserConfig { mode: crate::ParseMode::Json5, ..Default::default() };
+    let parsed = crate::JsonParser::with_config(json5, config).parse().unwrap();
+    assert_eq!(parsed.as_array().unwrap()[1..], value.as_array().unwrap()[1..]);
+}
+
+#[test]
+fn test_display_escapes_strings() {
+    let value = JsonValue::String("\"\\/\u{8}\u{c}\n\r\t\u{1}é|".into());
+    assert_eq!(value.to_string(), r#""\"\\/\b\f\n\r\t\u0001é|""#);
+}
+
+#[test]
+fn test_display_array() {
+    let value = JsonValue::Array(vec![
+        JsonValue::Number(1),
+        JsonValue::Array(vec![]),
+        JsonValue::Object(crate::JsonObject::new()),
+        JsonValue::String("a".into()),
+    ]);
+    assert_eq!(value.to_string(), r#"[1,[],{},"a"]"#);
+}
+
+#[test]
+fn test_display_round_trip() {
+    let json_input = r#"
+    {
+        "null": null,
+        "bools": [true, false],
+        "numbers": [0, -12, 9223372036854775807, 0.5, -1.25e-8, 6.02e23],
+        "escapes": "quote \" backslash \\ slash / \b \f \n \r \t \u0000 é 😀 | pipe",
+        "nested": { "a": { "b": { "c": [[{ "d": [] }], {}] } } }
+    }
+    "#;
+    let value = crate::parse_str(json_input).unwrap();
+    assert_eq!(crate::parse_str(&value.to_string()), Ok(value));
+}
+
+#[test]
+fn test_pretty_print_nested() {
+    let value = JsonValue::Object(crate::JsonObject::from([
+        ("b".to_string(), JsonValue::Array(vec![JsonValue::Number(1), JsonValue::Null, JsonValue::Array(vec![])])),
+        ("a".to_string(), JsonValue::Object(crate::JsonObject::from([
+            ("y".to_string(), JsonValue::Bool(true)),
+            ("x".to_string(), JsonValue::Object(crate::JsonObject::new())),
+        ]))),
+    ]));
+    let expected = "{
+  \"a\": {
+    \"x\": {},
+    \"y\": true
+  },
+  \"b\": [
+    1,
+    null,
+    []
+  ]
+}";
+    assert_eq!(PrettyPrinter::new().with_sorted_keys(true).print(&value), expected);
+}
+
+#[test]
+fn test_pretty_print_settings() {
+    let value = JsonValue::Array(vec![JsonValue::String("a".into()), JsonValue::Float(0.5)]);
+    assert_eq!(PrettyPrinter::new().with_indent(4).print(&value), "[\n    \"a\",\n    0.5\n]");
+    assert_eq!(PrettyPrinter::new().with_indent(1).with_tabs(true).print(&value), "[\n\t\"a\",\n\t0.5\n]");
+    assert_eq!(PrettyPrinter::new().with_trailing_newline(true).print(&JsonValue::Null), "null\n");
+}
+
+#[test]
+fn test_pretty_print_round_trip() {
+    let json_input = r#"{ "a": [1, 2.5, "x\ny", { "b": null, "c": [] }], "d": {}, "e": false }"#;
+    let value = crate::parse_str(json_input).unwrap();
+    assert_eq!(crate::parse_str(&PrettyPrinter::new().with_trailing_newline(true).print(&value)), Ok(value));
+}
+
+#[cfg(test)]
+use proptest::prelude::*;
+
+#[cfg(test)]
+fn arbitrary_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        Just("\"\\/\u{8}\u{c}\n\r\t\u{0}\u{1f}\u{7f}".to_string()),
+        "[a-z\"\\\\ ,:{}\\[\\]]{0,12}",
+        any::<String>(),
+    ]
+}
+
+// Floats are kept finite, since NaN and the infinities print as strings.
+#[cfg(test)]
+fn arbitrary_value() -> impl Strategy<Value = JsonValue> {
+    let leaf = prop_oneof![
+        Just(JsonValue::Null),
+        any::<bool>().prop_map(JsonValue::Bool),
+        any::<i64>().prop_map(JsonValue::Number),
+        any::<f64>().prop_filter("not representable in JSON", |value| value.is_finite()).prop_map(JsonValue::Float),
+        arbitrary_string().prop_map(JsonValue::from),
+    ];
+    leaf.prop_recursive(6, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(JsonValue::Array),
+            prop::collection::hash_map(arbitrary_string(), inner, 0..8)
+                .prop_map(|map| JsonValue::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+#[cfg(test)]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+    #[test]
+    fn test_display_round_trips_arbitrary_values(value in arbitrary_value()) {
+        prop_assert_eq!(value.to_vec(), value.to_string().into_bytes());
+        prop_assert_eq!(crate::parse_str(&value.to_string()), Ok(value.clone()));
+        prop_assert_eq!(crate::parse_str(&PrettyPrinter::new().print(&value)), Ok(value));
+    }
+}
+
+#[test]
+fn test_to_string_compact_and_pretty() {
+    let input = r#"{"a": [1, {"b": null}, []], "c": "d"}"#;
+    let config = crate::JsonParserConfig { preserve_order: crate::JsonObjectKind::BTreeMap, ..Default::default() };
+    let value = crate::JsonParser::with_config(input, config).parse().unwrap();
+    assert_eq!(value.to_string_compact(), r#"{"a":[1,{"b":null},[]],"c":"d"}"#);
+    let cases = [
+        (2, "{\n  \"a\": [\n    1,\n    {\n      \"b\": null\n    },\n    []\n  ],\n  \"c\": \"d\"\n}\n"),
+        (0, "{\n\"a\": [\n1,\n{\n\"b\": null\n},\n[]\n],\n\"c\": \"d\"\n}\n"),
+    ];
+    for (indent, expected) in cases {
+        assert_eq!(value.to_string_pretty(indent), expected, "indent: {}", indent);
+    }
+    for output in [value.to_string_compact(), value.to_string_pretty(0), value.to_string_pretty(4)] {
+        assert_eq!(crate::parse_str(&output), Ok(value.clone()), "output: {:?}", output);
+    }
+}
+
+#[test]
+fn test_serialize_with_options() {
+    let input = r#"{"b": [1.23456, null, {}], "a": null, "c": {"z": 2.5, "y": null}}"#;
+    let config = crate::JsonParserConfig { preserve_order: crate::JsonObjectKind::BTreeMap, ..Default::default() };
+    let sorted = crate::JsonParser::with_config(input, config).parse().unwrap();
+    let value = crate::parse_str(input).unwrap();
+    assert_eq!(sorted.serialize_with(&SerializeOptions::new()), sorted.to_string());
+    assert_eq!(value.serialize_with(&SerializeOptions::new()), value.to_string());
+
+    let cases = [
+        (SerializeOptions::new().sorted_keys(true), &value, r#"{"a":null,"b":[1.23456,null,{}],"c":{"y":null,"z":2.5}}"#),
+        (SerializeOptions::new().omit_nulls(true), &sorted, r#"{"b":[1.23456,null,{}],"c":{"z":2.5}}"#),
+        (SerializeOptions::new().trailing_newline(true), &sorted, "{\"a\":null,\"b\":[1.23456,null,{}],\"c\":{\"y\":null,\"z\":2.5}}\n"),
+        (SerializeOptions::new().max_float_precision(2), &sorted, r#"{"a":null,"b":[1.23,null,{}],"c":{"y":null,"z":2.5}}"#),
+        (SerializeOptions::new().max_float_precision(0), &sorted, r#"{"a":null,"b":[1.0,null,{}],"c":{"y":null,"z":2.0}}"#),
+        (
+            SerializeOptions::new().indent(2),
+            &sorted,
+            "{\n  \"a\": null,\n  \"b\": [\n    1.23456,\n    null,\n    {}\n  ],\n  \"c\": {\n    \"y\": null,\n    \"z\": 2.5\n  }\n}",
+        ),
+        (
+            SerializeOptions::new().indent(1).sorted_keys(true).omit_nulls(true).max_float_precision(3).trailing_newline(true),
+            &value,
+            "{\n \"b\": [\n  1.235,\n  null,\n  {}\n ],\n \"c\": {\n  \"z\": 2.5\n }\n}\n",
+        ),
+    ];
+    for (options, value, expected) in cases {
+        assert_eq!(value.serialize_with(&options), expected, "options: {:?}", options);
+    }
+    // Objects with only null members become empty.
+    let options = SerializeOptions::new().indent(2).omit_nulls(true);
+    assert_eq!(crate::parse_str(r#"[{"a": null}]"#).unwrap().serialize_with(&options), "[\n  {}\n]");
+    let options = SerializeOptions::new().max_float_precision(1);
+    assert_eq!(JsonValue::Float(f64::NAN).serialize_with(&options), "\"NaN\"");
+    assert_eq!(JsonValue::Float(1e300).serialize_with(&options), "1e300");
+}
+
+#[test]
+fn test_html_safe_serialization() {
+    let value = crate::parse_str(r#"{"</script>": "<script>alert(1)</script>", "q": "a & b > c", "n": [1, "/"]}"#).unwrap();
+    let output = value.to_html_safe_string();
+    assert!(!output.contains(['<', '>', '&']), "output: {:?}", output);
+    assert!(output.contains(r#""\u003Cscript\u003Ealert(1)\u003C\u002Fscript\u003E""#), "output: {:?}", output);
+    assert_eq!(crate::parse_str(&output), Ok(value.clone()));
+    assert!(value.serialize_with(&SerializeOptions::new()).contains("<script>"));
+    let options = SerializeOptions::new().html_safe(true).indent(2);
+    assert_eq!(JsonValue::from("&</").serialize_with(&options), r#""\u0026\u003C\u002F""#);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_serialize_to_writer() {
+    let value = crate::parse_str(r#"{"name": "tinyserde 𝄞", "values": [1, -2.5, true, null, "é\n"], "nested": {"a": [[], {}]}}"#).unwrap();
+    let mut output = vec![];
+    value.serialize_to_writer(&mut output).unwrap();
+    assert_eq!(output, value.to_string().into_bytes());
+    assert_eq!(value.to_vec(), output);
+
+    // Stops at the first error from the writer and returns it.
+    struct FailAfter(usize);
+    impl std::io::Write for FailAfter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.0 < buf.len() {
+                return Err(std::io::Error::other("disk full"));
+            }
+            self.0 -= buf.len();
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let err = value.serialize_to_writer(&mut FailAfter(10)).unwrap_err();
+    assert_eq!(err.to_string(), "disk full");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_json_serializer_matches_in_memory_serialization() {
+    let records: JsonValue = (0..2000i64)
+        .map(|i| {
+            let tags: JsonValue = (0..i % 4).map(|tag| JsonValue::from(format!("tag \"{}\"", tag))).collect();
+            let record = [
+                ("id".to_string(), JsonValue::from(i)),
+                ("score".to_string(), JsonValue::from(i as f64 / 8.0)),
+                ("active".to_string(), JsonValue::from(i % 3 == 0)),
+                ("note".to_string(), if i % 5 == 0 { JsonValue::Null } else { JsonValue::from("é\n") }),
+                ("tags".to_string(), tags),
+                ("empty".to_string(), crate::parse_str("{}").unwrap()),
+            ];
+            record.into_iter().collect::<JsonValue>()
+        })
+        .collect();
+    let mut serializer = JsonSerializer::new(vec![]);
+    records.serialize_into(&mut serializer).unwrap();
+    assert!(serializer.is_complete());
+    assert_eq!(serializer.into_inner(), records.to_vec());
+
+    // Tokens can be written one at a time, as from a database cursor.
+    let mut serializer = JsonSerializer::new(vec![]);
+    serializer.begin_object().unwrap();
+    serializer.write_key("count").unwrap();
+    serializer.write_primitive(3).unwrap();
+    serializer.write_key("rows").unwrap();
+    serializer.begin_array().unwrap();
+    for row in records.as_array().unwrap().iter().take(3) {
+        serializer.write_value(row.as_object().unwrap().get("id").unwrap()).unwrap();
+    }
+    serializer.begin_array().unwrap();
+    serializer.end_array().unwrap();
+    serializer.write_value(&crate::parse_str(r#"{"a": [true]}"#).unwrap()).unwrap();
+    serializer.end_array().unwrap();
+    serializer.write_key("k\"").unwrap();
+    serializer.write_primitive("v").unwrap();
+    assert!(!serializer.is_complete());
+    serializer.end_object().unwrap();
+    let output = String::from_utf8(serializer.into_inner()).unwrap();
+    assert_eq!(output, r#"{"count":3,"rows":[0,1,2,[],{"a":[true]}],"k\"":"v"}"#);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_json_serializer_misuse_panics() {
+    type Misuse = fn(&mut JsonSerializer<Vec<u8>>);
+    let cases: [(Misuse, &str); 5] = [
+        (|s| s.write_key("a").unwrap(), "write_key called outside any array or object"),
+        (|s| s.begin_array().and_then(|_| s.write_key("a")).unwrap(), "write_key called inside an array"),
+        (|s| s.begin_object().and_then(|_| s.write_primitive(1)).unwrap(), "write_value called where an object key is expected"),
+        (|s| s.begin_object().and_then(|_| s.write_key("a")).and_then(|_| s.end_object()).unwrap(), "end_object called after a key"),
+        (|s| s.begin_object().and_then(|_| s.end_array()).unwrap(), "end_array called where an object key is expected"),
+    ];
+    for (misuse, message) in cases {
+        let result = std::panic::catch_unwind(|| misuse(&mut JsonSerializer::new(vec![])));
+        let panic = result.expect_err(message);
+        assert_eq!(panic.downcast_ref::<String>().map(String::as_str), Some(message));
+    }
+}
+
+#[test]
+fn test_display_round_trips_maximum_depth() {
+    let max_depth = crate::JsonParserConfig::default().max_depth;
+    let mut value = JsonValue::String("innermost".into());
+    for _ in 0..max_depth {
+        value = JsonValue::Array(vec![value]);
+    }
+    assert_eq!(crate::parse_str(&value.to_string()), Ok(value));
+}
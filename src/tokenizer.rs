@@ -0,0 +1,475 @@
+use crate::config::ParseMode;
+use crate::whitespace::count_whitespace;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token<'a> {
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Colon,
+    Comma,
+    Null,
+    Bool(bool),
+    /// The literal as written, including any sign, fraction and exponent.
+    Number(&'a str),
+    /// The contents between the quotes, with escape sequences left as is.
+    String(&'a str),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TokenError {
+    UnexpectedCharacter { char: char, at_byte: usize },
+    UnterminatedString { at_byte: usize },
+    InvalidEscapeSequence { char: char, at_byte: usize },
+    InvalidNumberLiteral { at_byte: usize },
+}
+
+pub(crate) fn is_whitespace(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | ' ')
+}
+
+// JSON5 additionally treats the other ECMAScript whitespace characters,
+// such as vertical tab, no-break space and the byte order mark, as
+// insignificant.
+fn is_json5_whitespace(c: char) -> bool {
+    is_whitespace(c)
+        || matches!(
+            c,
+            '\u{b}' | '\u{c}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}'
+                | '\u{2028}' | '\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}' | '\u{feff}'
+        )
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '$' || c == '_'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '$' | '_' | '\u{200c}' | '\u{200d}')
+}
+
+// Returns the length of the JSON number at the start of `input`, or None
+// if it does not start with a valid number.
+pub(crate) fn scan_number(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let digits = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut length = usize::from(bytes.first() == Some(&b'-'));
+    let integer_digits = digits(length);
+    if integer_digits == 0 || (integer_digits > 1 && bytes[length] == b'0') {
+        return None;
+    }
+    length += integer_digits;
+    if bytes.get(length) == Some(&b'.') {
+        let fraction_digits = digits(length + 1);
+        if fraction_digits == 0 {
+            return None;
+        }
+        length += 1 + fraction_digits;
+    }
+    if matches!(bytes.get(length), Some(b'e' | b'E')) {
+        length += 1;
+        if matches!(bytes.get(length), Some(b'+' | b'-')) {
+            length += 1;
+        }
+        let exponent_digits = digits(length);
+        if exponent_digits == 0 {
+            return None;
+        }
+        length += exponent_digits;
+    }
+    Some(length)
+}
+
+// Like `scan_number`, for the numbers that JSON5 allows: a leading `+`,
+// hexadecimal integers, a leading or trailing decimal point, `Infinity`
+// and `NaN`.
+fn scan_json5_number(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let digits = |from: usize, hex: bool| bytes[from..].iter().take_while(|b| if hex { b.is_ascii_hexdigit() } else { b.is_ascii_digit() }).count();
+    let sign = usize::from(matches!(bytes.first(), Some(b'-' | b'+')));
+    let unsigned = &input[sign..];
+    if let Some(literal) = ["Infinity", "NaN"].into_iter().find(|literal| unsigned.starts_with(literal)) {
+        return Some(sign + literal.len());
+    }
+    if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+        let hex_digits = digits(sign + 2, true);
+        return (hex_digits > 0).then_some(sign + 2 + hex_digits);
+    }
+    let integer_digits = digits(sign, false);
+    if integer_digits > 1 && bytes[sign] == b'0' {
+        return None;
+    }
+    let mut length = sign + integer_digits;
+    if bytes.get(length) == Some(&b'.') {
+        let fraction_digits = digits(length + 1, false);
+        if integer_digits == 0 && fraction_digits == 0 {
+            return None;
+        }
+        length += 1 + fraction_digits;
+    } else if integer_digits == 0 {
+        return None;
+    }
+    if matches!(bytes.get(length), Some(b'e' | b'E')) {
+        length += 1;
+        if matches!(bytes.get(length), Some(b'+' | b'-')) {
+            length += 1;
+        }
+        let exponent_digits = digits(length, false);
+        if exponent_digits == 0 {
+            return None;
+        }
+        length += exponent_digits;
+    }
+    Some(length)
+}
+
+/// Splits strict JSON into tokens that borrow from the input. Iteration
+/// stops at the end of the input or after the first error.
+pub struct JsonTokenizer<'a> {
+    input: &'a str,
+    cursor: usize,
+    failed: bool,
+    // `JsonParser` also tokenizes lenient and JSON5 input.
+    mode: ParseMode,
+    // Whether an unescaped quote that is not followed by a structural
+    // character, a comment or the end of input is kept as part of the
+    // string, which `JsonParser::parse` allows for a single Lenient
+    // document.
+    inner_quotes: bool,
+}
+
+impl<'a> JsonTokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        JsonTokenizer::at(input, 0, ParseMode::Strict, false)
+    }
+
+    // Starts at the byte offset `cursor`, reading input in `mode`.
+    #[inline]
+    pub(crate) fn at(input: &'a str, cursor: usize, mode: ParseMode, inner_quotes: bool) -> Self {
+        JsonTokenizer { input, cursor, failed: false, mode, inner_quotes }
+    }
+
+    /// The byte offset just after the last token.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.cursor
+    }
+
+    #[inline]
+    fn remaining(&self) -> &'a str {
+        &self.input[self.cursor..]
+    }
+
+    // Skips whitespace, and the comments that lenient and JSON5 input
+    // allow. An unterminated `/*` comment runs to the end of the input.
+    #[inline]
+    pub(crate) fn skip_insignificant(&mut self) {
+        loop {
+            self.cursor += count_whitespace(self.remaining().as_bytes());
+            if self.mode == ParseMode::Strict {
+                return;
+            }
+            let rest = self.remaining();
+            let json5 = self.mode == ParseMode::Json5;
+            self.cursor += match rest.chars().next() {
+                Some(ch) if json5 && is_json5_whitespace(ch) => ch.len_utf8(),
+                _ if rest.starts_with("//") => rest.find('\n').unwrap_or(rest.len()),
+                _ if json5 && rest.starts_with("/*") => rest[2..].find("*/").map_or(rest.len(), |end| end + 4),
+                _ => return,
+            };
+        }
+    }
+
+    // Reads the token at the cursor if it is punctuation, leaving any other
+    // token unread.
+    #[inline]
+    pub(crate) fn next_punctuation(&mut self) -> Option<Token<'static>> {
+        let token = match self.remaining().as_bytes().first()? {
+            b'{' => Token::LBrace,
+            b'}' => Token::RBrace,
+            b'[' => Token::LBracket,
+            b']' => Token::RBracket,
+            b':' => Token::Colon,
+            b',' => Token::Comma,
+            _ => return None,
+        };
+        self.cursor += 1;
+        Some(token)
+    }
+
+    // Reads the string at the cursor where an object key is expected.
+    // JSON5 keys may also be identifiers, which contain no escape
+    // sequences.
+    pub(crate) fn next_key(&mut self) -> Result<&'a str, TokenError> {
+        let rest = self.remaining();
+        let json5 = self.mode == ParseMode::Json5;
+        match rest.chars().next().unwrap() {
+            ch if json5 && is_identifier_start(ch) => {
+                let length = rest.find(|c: char| !is_identifier_part(c)).unwrap_or(rest.len());
+                self.cursor += length;
+                Ok(&rest[..length])
+            }
+            '"' => self.next_string(b'"'),
+            '\'' if json5 => self.next_string(b'\''),
+            ch => Err(TokenError::UnexpectedCharacter { char: ch, at_byte: self.cursor }),
+        }
+    }
+
+    // Reads the token at the cursor, which must not be at the end of the
+    // input.
+    #[inline]
+    pub(crate) fn next_token(&mut self) -> Result<Token<'a>, TokenError> {
+        let start = self.cursor;
+        if let Some(token) = self.next_punctuation() {
+            return Ok(token);
+        }
+        let literal = match self.remaining().as_bytes()[0] {
+            b'n' => Some(("null", Token::Null)),
+            b't' => Some(("true", Token::Bool(true))),
+            b'f' => Some(("false", Token::Bool(false))),
+            _ => None,
+        };
+        if let Some((literal, token)) = literal.filter(|(literal, _)| self.remaining().starts_with(literal)) {
+            self.cursor += literal.len();
+            return Ok(token);
+        }
+        let ch = self.remaining().chars().next().unwrap();
+        let number = ch == '-'
+            || ch.is_ascii_digit()
+            || (self.mode != ParseMode::Strict && matches!(ch, 'I' | 'N'))
+            || (self.mode == ParseMode::Json5 && matches!(ch, '+' | '.'));
+        match ch {
+            '"' => self.next_string(b'"').map(Token::String),
+            '\'' if self.mode == ParseMode::Json5 => self.next_string(b'\'').map(Token::String),
+            _ if number => match self.scan_number() {
+                Some(length) => {
+                    self.cursor += length;
+                    Ok(Token::Number(&self.input[start..self.cursor]))
+                }
+                None => Err(TokenError::InvalidNumberLiteral { at_byte: start }),
+            },
+            _ => Err(TokenError::UnexpectedCharacter { char: ch, at_byte: start }),
+        }
+    }
+
+    fn scan_number(&self) -> Option<usize> {
+        let rest = self.remaining();
+        match self.mode {
+            ParseMode::Strict => scan_number(rest),
+            ParseMode::Lenient => {
+                let sign = usize::from(rest.starts_with('-'));
+                match ["Infinity", "NaN"].into_iter().find(|literal| rest[sign..].starts_with(literal)) {
+                    Some(literal) => Some(sign + literal.len()),
+                    None if rest[sign..].starts_with(['I', 'N']) => None,
+                    None => scan_number(rest),
+                }
+            }
+            ParseMode::Json5 => scan_json5_number(rest),
+        }
+    }
+
+    // Scans the bytes, since every character that ends a string or needs
+    // checking is ASCII. JSON5 strings may contain control characters and
+    // any escape sequence, which `JsonParser` checks as it decodes them.
+    fn next_string(&mut self, quote: u8) -> Result<&'a str, TokenError> {
+        let start = self.cursor;
+        let bytes = self.input.as_bytes();
+        let json5 = self.mode == ParseMode::Json5;
+        let mut at_byte = start + 1;
+        while let Some(&byte) = bytes.get(at_byte) {
+            match byte {
+                _ if byte == quote => {
+                    if quote == b'"' && self.inner_quotes && !self.quote_terminates(at_byte) {
+                        at_byte += 1;
+                        continue;
+                    }
+                    self.cursor = at_byte + 1;
+                    return Ok(&self.input[start + 1..at_byte]);
+                }
+                b'\\' if json5 => match self.input[at_byte + 1..].chars().next() {
+                    Some(escaped) => at_byte += 1 + escaped.len_utf8(),
+                    None => break,
+                },
+                b'\\' => match bytes.get(at_byte + 1) {
+                    Some(b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => at_byte += 2,
+                    Some(b'u') => {
+                        let hex = bytes[at_byte + 2..].iter().take(4).take_while(|b| b.is_ascii_hexdigit()).count();
+                        match hex {
+                            4 => at_byte += 6,
+                            // An escape cut short by the end of input leaves
+                            // the string unterminated.
+                            _ if at_byte + 2 + hex == bytes.len() => break,
+                            _ => return Err(TokenError::InvalidEscapeSequence { char: 'u', at_byte }),
+                        }
+                    }
+                    Some(_) => {
+                        let escaped = self.input[at_byte + 1..].chars().next().unwrap();
+                        return Err(TokenError::InvalidEscapeSequence { char: escaped, at_byte });
+                    }
+                    None => break,
+                },
+                _ if byte < b' ' && !json5 => return Err(TokenError::UnexpectedCharacter { char: byte as char, at_byte }),
+                _ => at_byte += 1,
+            }
+        }
+        Err(TokenError::UnterminatedString { at_byte: start })
+    }
+
+    // Whether the quote at `at_byte` is followed by a structural character,
+    // a comment or the end of input, after any whitespace.
+    fn quote_terminates(&self, at_byte: usize) -> bool {
+        let after = &self.input.as_bytes()[at_byte + 1..];
+        let whitespace = count_whitespace(after);
+        matches!(after.get(whitespace), None | Some(b'}' | b']' | b':' | b',' | b'/'))
+    }
+}
+
+impl<'a> Iterator for JsonTokenizer<'a> {
+    type Item = Result<Token<'a>, TokenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        self.skip_insignificant();
+        if self.cursor == self.input.len() {
+            return None;
+        }
+        let token = self.next_token();
+        self.failed = token.is_err();
+        Some(token)
+    }
+}
+
+#[test]
+fn test_tokenize_every_construct() {
+    let input = r#"{"a": [1, -2.5e+3, true, false, null], "b\n": {}}"#;
+    let tokens: Result<Vec<_>, _> = JsonTokenizer::new(input).collect();
+    assert_eq!(tokens, Ok(vec![
+        Token::LBrace,
+        Token::String("a"),
+        Token::Colon,
+        Token::LBracket,
+        Token::Number("1"),
+        Token::Comma,
+        Token::Number("-2.5e+3"),
+        Token::Comma,
+        Token::Bool(true),
+        Token::Comma,
+        Token::Bool(false),
+        Token::Comma,
+        Token::Null,
+        Token::RBracket,
+        Token::Comma,
+        Token::String("b\\n"),
+        Token::Colon,
+        Token::LBrace,
+        Token::RBrace,
+        Token::RBrace,
+    ]));
+}
+
+#[test]
+fn test_tokenize_strings() {
+    let cases = [
+        (r#""""#, ""),
+        (r#""plain""#, "plain"),
+        (r#""\"\\\/\b\f\n\r\t""#, r#"\"\\\/\b\f\n\r\t"#),
+        (r#""\u00e9 é""#, r#"\u00e9 é"#),
+        (r#""a \"nested\" string""#, r#"a \"nested\" string"#),
+    ];
+    for (input, expected) in cases {
+        let tokens: Vec<_> = JsonTokenizer::new(input).collect();
+        assert_eq!(tokens, vec![Ok(Token::String(expected))], "input: {:?}", input);
+    }
+    let tokens: Vec<_> = JsonTokenizer::new(r#""a" "b""#).collect();
+    assert_eq!(tokens, vec![Ok(Token::String("a")), Ok(Token::String("b"))]);
+}
+
+#[test]
+fn test_tokenize_numbers() {
+    for input in ["0", "-0", "12", "-12", "0.5", "1e10", "1E-2", "-3.25e+4"] {
+        let tokens: Vec<_> = JsonTokenizer::new(input).collect();
+        assert_eq!(tokens, vec![Ok(Token::Number(input))], "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_tokenize_errors() {
+    let cases = [
+        ("@", TokenError::UnexpectedCharacter { char: '@', at_byte: 0 }),
+        ("[nul]", TokenError::UnexpectedCharacter { char: 'n', at_byte: 1 }),
+        ("\"abc", TokenError::UnterminatedString { at_byte: 0 }),
+        ("\"abc\\", TokenError::UnterminatedString { at_byte: 0 }),
+        ("\"a\\x\"", TokenError::InvalidEscapeSequence { char: 'x', at_byte: 2 }),
+        ("[\"a\tb\"]", TokenError::UnexpectedCharacter { char: '\t', at_byte: 3 }),
+        ("\"\\u12\"", TokenError::InvalidEscapeSequence { char: 'u', at_byte: 1 }),
+        ("[-]", TokenError::InvalidNumberLiteral { at_byte: 1 }),
+        ("012", TokenError::InvalidNumberLiteral { at_byte: 0 }),
+        ("1.", TokenError::InvalidNumberLiteral { at_byte: 0 }),
+        ("1.e5", TokenError::InvalidNumberLiteral { at_byte: 0 }),
+        ("2e+", TokenError::InvalidNumberLiteral { at_byte: 0 }),
+    ];
+    for (input, expected) in cases {
+        let result: Result<Vec<_>, _> = JsonTokenizer::new(input).collect();
+        assert_eq!(result, Err(expected), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_tokenize_in_each_mode() {
+    let tokenize = |input, mode| JsonTokenizer::at(input, 0, mode, false).collect::<Result<Vec<_>, _>>();
+    let input = "[NaN, -Infinity] // comment";
+    assert_eq!(tokenize(input, ParseMode::Strict), Err(TokenError::UnexpectedCharacter { char: 'N', at_byte: 1 }));
+    assert_eq!(
+        tokenize(input, ParseMode::Lenient),
+        Ok(vec![Token::LBracket, Token::Number("NaN"), Token::Comma, Token::Number("-Infinity"), Token::RBracket])
+    );
+    let input = "/* a */ ['it\\'s', +.5, 5., 0x1F, '\\x41']";
+    assert_eq!(
+        tokenize(input, ParseMode::Json5),
+        Ok(vec![
+            Token::LBracket,
+            Token::String("it\\'s"),
+            Token::Comma,
+            Token::Number("+.5"),
+            Token::Comma,
+            Token::Number("5."),
+            Token::Comma,
+            Token::Number("0x1F"),
+            Token::Comma,
+            Token::String("\\x41"),
+            Token::RBracket,
+        ])
+    );
+    let mut tokenizer = JsonTokenizer::at("\"say \"hi\" now\" ]", 0, ParseMode::Lenient, true);
+    assert_eq!(tokenizer.next(), Some(Ok(Token::String("say \"hi\" now"))));
+}
+
+#[test]
+fn test_tokenizer_stops_after_error() {
+    let mut tokenizer = JsonTokenizer::new("[@]");
+    assert_eq!(tokenizer.next(), Some(Ok(Token::LBracket)));
+    assert!(matches!(tokenizer.next(), Some(Err(_))));
+    assert_eq!(tokenizer.next(), None);
+    assert_eq!(tokenizer.offset(), 1);
+}
+
+#[test]
+fn test_tokens_borrow_from_input() {
+    // The returned slices outlive the tokenizer that produced them.
+    fn strings(input: &str) -> Vec<&str> {
+        JsonTokenizer::new(input)
+            .filter_map(|token| match token {
+                Ok(Token::String(value)) | Ok(Token::Number(value)) => Some(value),
+                _ => None,
+            })
+            .collect()
+    }
+    let input = String::from(r#"{"key": 42}"#);
+    let slices = strings(&input);
+    assert_eq!(slices, vec!["key", "42"]);
+    let range = input.as_bytes().as_ptr_range();
+    assert!(slices.iter().all(|slice| range.contains(&slice.as_ptr())));
+}
@@ -0,0 +1,908 @@
+use crate::array::JsonArray;
+use crate::iter::{children, Iter, JsonItem};
+use crate::parser::JsonValue;
+use crate::object::{Entry, JsonObject};
+use crate::prelude::*;
+use core::fmt;
+
+/// Returned by the `as_*_or_err` accessors when the value has another type.
+/// Both fields are JSON Schema type names, such as "string" or "boolean".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonTypeError {
+    pub expected: &'static str,
+    pub actual: &'static str,
+}
+
+impl fmt::Display for JsonTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.actual)
+    }
+}
+
+impl core::error::Error for JsonTypeError {}
+
+/// Whether `JsonValue::filter_keys` keeps or removes the listed keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFilterMode {
+    /// Keep only the listed keys.
+    Allow,
+    /// Remove the listed keys.
+    Deny,
+}
+
+impl JsonValue {
+    /// The usual placeholder for `redact`.
+    pub const REDACTED: JsonValue = JsonValue::String(Cow::Borrowed("[REDACTED]"));
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    #[inline]
+    pub fn is_bool(&self) -> bool {
+        matches!(self, JsonValue::Bool(_))
+    }
+
+    /// True for both integers and floats.
+    #[inline]
+    pub fn is_number(&self) -> bool {
+        match self {
+            JsonValue::Number(_) | JsonValue::Number64(_) | JsonValue::Float(_) => true,
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(_) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn is_string(&self) -> bool {
+        matches!(self, JsonValue::String(_))
+    }
+
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        matches!(self, JsonValue::Array(_))
+    }
+
+    #[inline]
+    pub fn is_object(&self) -> bool {
+        matches!(self, JsonValue::Object(_))
+    }
+
+    /// The JSON Schema name of the value's type: "null", "boolean",
+    /// "number", "string", "array" or "object". Integers are numbers too,
+    /// since "integer" is not a type of its own in JSON.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(_) | JsonValue::Number64(_) | JsonValue::Float(_) => "number",
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(value) => Some(*value),
+            JsonValue::Number64(value) => i64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Integers are converted to `f64`, which may lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(value) => Some(*value as f64),
+            JsonValue::Number64(value) => Some(*value as f64),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(value) => bigdecimal::ToPrimitive::to_f64(value),
+            JsonValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Like `as_array`, but as a `JsonArray`.
+    pub fn as_json_array(&self) -> Option<&JsonArray> {
+        self.as_array().map(JsonArray::from_vec_ref)
+    }
+
+    pub fn as_json_array_mut(&mut self) -> Option<&mut JsonArray> {
+        self.as_array_mut().map(JsonArray::from_vec_mut)
+    }
+
+    /// Same as `as_object`.
+    pub fn as_json_object(&self) -> Option<&JsonObject> {
+        self.as_object()
+    }
+
+    pub fn as_object(&self) -> Option<&JsonObject> {
+        match self {
+            JsonValue::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut JsonObject> {
+        match self {
+            JsonValue::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_str_or_err(&self) -> Result<&str, JsonTypeError> {
+        self.as_str().ok_or_else(|| self.type_error("string"))
+    }
+
+    /// Fails for floats and for integers that do not fit in an `i64`.
+    pub fn as_i64_or_err(&self) -> Result<i64, JsonTypeError> {
+        self.as_i64().ok_or_else(|| self.type_error("integer"))
+    }
+
+    pub fn as_f64_or_err(&self) -> Result<f64, JsonTypeError> {
+        self.as_f64().ok_or_else(|| self.type_error("number"))
+    }
+
+    pub fn as_bool_or_err(&self) -> Result<bool, JsonTypeError> {
+        self.as_bool().ok_or_else(|| self.type_error("boolean"))
+    }
+
+    pub fn as_array_or_err(&self) -> Result<&[JsonValue], JsonTypeError> {
+        self.as_array().map(Vec::as_slice).ok_or_else(|| self.type_error("array"))
+    }
+
+    pub fn as_object_or_err(&self) -> Result<&JsonObject, JsonTypeError> {
+        self.as_object().ok_or_else(|| self.type_error("object"))
+    }
+
+    fn type_error(&self, expected: &'static str) -> JsonTypeError {
+        JsonTypeError { expected, actual: self.type_name() }
+    }
+
+    /// The number of elements of an array or members of an object, or None
+    /// for other values.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            JsonValue::Array(array) => Some(array.len()),
+            JsonValue::Object(map) => Some(map.len()),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Whether the value is an object with a member named `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.as_object().is_some_and(|map| map.contains_key(key))
+    }
+
+    /// Whether the value is an array with an element equal to `value`.
+    pub fn contains(&self, value: &JsonValue) -> bool {
+        self.as_array().is_some_and(|array| array.contains(value))
+    }
+
+    /// The member stored under `key`, or `default` when the value is not an
+    /// object or has no such member.
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a JsonValue) -> &'a JsonValue {
+        self.get_or_else(key, || default)
+    }
+
+    /// Like `get_or`, but only calls `f` when the member is missing.
+    pub fn get_or_else<'a>(&'a self, key: &str, f: impl FnOnce() -> &'a JsonValue) -> &'a JsonValue {
+        self.as_object().and_then(|map| map.get(key)).unwrap_or_else(f)
+    }
+
+    /// The element at `index`, or `default` when the value is not an array
+    /// or is too short.
+    pub fn index_or<'a>(&'a self, index: usize, default: &'a JsonValue) -> &'a JsonValue {
+        self.index_or_else(index, || default)
+    }
+
+    /// Like `index_or`, but only calls `f` when the element is missing.
+    pub fn index_or_else<'a>(&'a self, index: usize, f: impl FnOnce() -> &'a JsonValue) -> &'a JsonValue {
+        self.as_array().and_then(|array| array.get(index)).unwrap_or_else(f)
+    }
+
+    /// Returns a copy of an array in which arrays nested up to `depth`
+    /// levels deep are replaced by their elements, like JavaScript's
+    /// `Array.prototype.flat`. Other values are returned unchanged.
+    pub fn flatten(&self, depth: usize) -> JsonValue {
+        match self {
+            JsonValue::Array(array) => {
+                let mut flat = vec![];
+                flatten_into(&mut flat, array, depth);
+                JsonValue::Array(flat)
+            }
+            value => value.clone(),
+        }
+    }
+
+    /// Flattens nested arrays at every depth.
+    pub fn flatten_all(&self) -> JsonValue {
+        self.flatten(usize::MAX)
+    }
+
+    /// Replaces every value that is not an array or an object with the
+    /// result of `f`, keeping the arrays and objects around them.
+    pub fn map_values(self, f: impl Fn(JsonValue) -> JsonValue) -> JsonValue {
+        map_leaves(self, &f)
+    }
+
+    /// Copies the value like `clone`, but with an explicit stack instead of
+    /// recursion, so deeply nested values cannot overflow the call stack.
+    pub fn deep_clone(&self) -> JsonValue {
+        // Each frame holds the children left to copy, the copy built so far
+        // and the key of the member being copied.
+        let mut stack: Vec<(Iter<'_>, JsonValue, Option<String>)> = vec![];
+        let mut value = self;
+        loop {
+            // `clone` only recurses one level into a container without
+            // nested containers, and is faster than rebuilding it here.
+            let nested = children(value).is_some_and(|mut iter| iter.any(|item| children(item.value()).is_some()));
+            let mut copy = match value {
+                JsonValue::Array(array) if nested => {
+                    stack.push((children(value).unwrap(), JsonValue::Array(Vec::with_capacity(array.len())), None));
+                    None
+                }
+                JsonValue::Object(map) if nested => {
+                    stack.push((children(value).unwrap(), JsonValue::Object(JsonObject::with_kind(map.kind())), None));
+                    None
+                }
+                value => Some(value.clone()),
+            };
+            loop {
+                let Some((iter, container, key)) = stack.last_mut() else {
+                    return copy.unwrap();
+                };
+                if let Some(copy) = copy.take() {
+                    match (container, key.take()) {
+                        (JsonValue::Object(map), Some(key)) => {
+                            map.insert(key, copy);
+                        }
+                        (JsonValue::Array(array), _) => array.push(copy),
+                        _ => unreachable!(),
+                    }
+                }
+                match iter.next() {
+                    Some(JsonItem::Element(child)) => value = child,
+                    Some(JsonItem::Member(name, child)) => {
+                        *key = Some(name.to_string());
+                        value = child;
+                    }
+                    None => {
+                        copy = stack.pop().map(|(_, container, _)| container);
+                        continue;
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    /// Returns a copy in which every object, at any depth, keeps only the
+    /// listed keys with `KeyFilterMode::Allow` or loses them with
+    /// `KeyFilterMode::Deny`. With `Allow`, the keys of nested objects
+    /// have to be listed too for their members to be kept.
+    pub fn filter_keys(&self, mode: KeyFilterMode, keys: &[&str]) -> JsonValue {
+        let mut filtered = self.deep_clone();
+        let mut stack = vec![&mut filtered];
+        while let Some(value) = stack.pop() {
+            match value {
+                JsonValue::Array(array) => stack.extend(array.iter_mut()),
+                JsonValue::Object(map) => {
+                    map.retain(|key, _| keys.contains(&key) == (mode == KeyFilterMode::Allow));
+                    stack.extend(map.values_mut());
+                }
+                _ => {}
+            }
+        }
+        filtered
+    }
+
+    /// Returns a copy in which every object member whose key satisfies
+    /// `predicate`, at any depth, has its value replaced with
+    /// `placeholder`, usually `JsonValue::REDACTED`. Replaced values are
+    /// not searched further.
+    pub fn redact(&self, predicate: impl Fn(&str) -> bool, placeholder: JsonValue) -> JsonValue {
+        let mut redacted = self.deep_clone();
+        let mut stack = vec![&mut redacted];
+        while let Some(value) = stack.pop() {
+            match value {
+                JsonValue::Array(array) => stack.extend(array.iter_mut()),
+                JsonValue::Object(map) => {
+                    for (key, value) in map.iter_mut() {
+                        if predicate(key) {
+                            *value = placeholder.clone();
+                        } else {
+                            stack.push(value);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        redacted
+    }
+
+    /// Keeps only the array elements for which `predicate` returns true.
+    /// Does nothing to other values.
+    pub fn retain_array(&mut self, predicate: impl FnMut(&JsonValue) -> bool) {
+        if let JsonValue::Array(array) = self {
+            array.retain(predicate);
+        }
+    }
+
+    /// Keeps only the object members for which `predicate` returns true.
+    /// Does nothing to other values.
+    pub fn retain_object(&mut self, mut predicate: impl FnMut(&str, &JsonValue) -> bool) {
+        if let JsonValue::Object(map) = self {
+            map.retain(|key, value| predicate(key, value));
+        }
+    }
+
+    /// Returns the member of an object stored under `key`, for in-place
+    /// insertion or modification. Null is replaced with an empty object
+    /// first.
+    ///
+    /// # Panics
+    ///
+    /// When the value is neither an object nor null, as do the other
+    /// mutation methods below.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        self.object_for("entry").entry(key)
+    }
+
+    /// Returns the previous value stored under `key`, if any. Null is
+    /// replaced with an empty object first.
+    pub fn insert(&mut self, key: &str, value: JsonValue) -> Option<JsonValue> {
+        self.object_for("insert").insert(key.to_string(), value)
+    }
+
+    /// Returns None for null as well as for a missing key.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        match self {
+            JsonValue::Null => None,
+            JsonValue::Object(map) => map.remove(key),
+            value => panic!("remove called on {}", article_and_type(value)),
+        }
+    }
+
+    /// Appends to an array. Null is replaced with an empty array first.
+    pub fn push(&mut self, value: JsonValue) {
+        self.array_for("push").push(value)
+    }
+
+    /// Returns None for null as well as for an empty array.
+    pub fn pop(&mut self) -> Option<JsonValue> {
+        match self {
+            JsonValue::Null => None,
+            JsonValue::Array(array) => array.pop(),
+            value => panic!("pop called on {}", article_and_type(value)),
+        }
+    }
+
+    /// Inserts into an array at `index`, shifting the elements after it.
+    /// Null is replaced with an empty array first. Panics if `index` is
+    /// greater than the length of the array.
+    pub fn insert_at(&mut self, index: usize, value: JsonValue) {
+        self.array_for("insert_at").insert(index, value)
+    }
+
+    /// Appends every element of `iter` to an array. Null is replaced with
+    /// an empty array first.
+    pub fn extend_array(&mut self, iter: impl IntoIterator<Item = JsonValue>) {
+        self.array_for("extend_array").extend(iter)
+    }
+
+    /// Inserts every member of `iter` into an object, so that a later
+    /// duplicate key overwrites an earlier one. Null is replaced with an
+    /// empty object first.
+    pub fn extend_object(&mut self, iter: impl IntoIterator<Item = (String, JsonValue)>) {
+        self.object_for("extend_object").extend(iter)
+    }
+
+    fn object_for(&mut self, method: &str) -> &mut JsonObject {
+        if self.is_null() {
+            *self = JsonValue::Object(JsonObject::new());
+        }
+        match self {
+            JsonValue::Object(map) => map,
+            value => panic!("{} called on {}", method, article_and_type(value)),
+        }
+    }
+
+    fn array_for(&mut self, method: &str) -> &mut Vec<JsonValue> {
+        if self.is_null() {
+            *self = JsonValue::Array(vec![]);
+        }
+        match self {
+            JsonValue::Array(array) => array,
+            value => panic!("{} called on {}", method, article_and_type(value)),
+        }
+    }
+}
+
+fn flatten_into(flat: &mut Vec<JsonValue>, array: &[JsonValue], depth: usize) {
+    for value in array {
+        match value {
+            JsonValue::Array(nested) if depth > 0 => flatten_into(flat, nested, depth - 1),
+            value => flat.push(value.clone()),
+        }
+    }
+}
+
+fn map_leaves(mut value: JsonValue, f: &impl Fn(JsonValue) -> JsonValue) -> JsonValue {
+    match &mut value {
+        JsonValue::Array(array) => {
+            for value in array.iter_mut() {
+                *value = map_leaves(core::mem::take(value), f);
+            }
+            value
+        }
+        JsonValue::Object(map) => {
+            for value in map.values_mut() {
+                *value = map_leaves(core::mem::take(value), f);
+            }
+            value
+        }
+        _ => f(value),
+    }
+}
+
+pub(crate) fn article_and_type(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a bool",
+        JsonValue::Number(_) | JsonValue::Number64(_) | JsonValue::Float(_) => "a number",
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn sample_values() -> Vec<JsonValue> {
+    vec![
+        JsonValue::Null,
+        JsonValue::Bool(true),
+        JsonValue::Number(7),
+        JsonValue::Float(2.5),
+        JsonValue::String("abc".into()),
+        JsonValue::Array(vec![JsonValue::Null]),
+        JsonValue::Object(JsonObject::from([("a".to_string(), JsonValue::Null)])),
+    ]
+}
+
+#[test]
+fn test_predicates() {
+    let expected = [
+        // null, bool, number, string, array, object
+        [true, false, false, false, false, false],
+        [false, true, false, false, false, false],
+        [false, false, true, false, false, false],
+        [false, false, true, false, false, false],
+        [false, false, false, true, false, false],
+        [false, false, false, false, true, false],
+        [false, false, false, false, false, true],
+    ];
+    for (value, expected) in sample_values().iter().zip(expected) {
+        let actual = [
+            value.is_null(),
+            value.is_bool(),
+            value.is_number(),
+            value.is_string(),
+            value.is_array(),
+            value.is_object(),
+        ];
+        assert_eq!(actual, expected, "value: {:?}", value);
+    }
+}
+
+#[test]
+fn test_predicates_in_iterator_chain() {
+    let values = sample_values();
+    assert_eq!(values.iter().filter(|v| v.is_object()).count(), 1);
+    assert_eq!(values.iter().filter(|v| v.is_number()).count(), 2);
+}
+
+#[test]
+fn test_accessors() {
+    for value in sample_values() {
+        match &value {
+            JsonValue::String(_) => assert_eq!(value.as_str(), Some("abc")),
+            _ => assert_eq!(value.as_str(), None),
+        }
+        match &value {
+            JsonValue::Number(_) => assert_eq!(value.as_i64(), Some(7)),
+            _ => assert_eq!(value.as_i64(), None),
+        }
+        match &value {
+            JsonValue::Number(_) => assert_eq!(value.as_f64(), Some(7.0)),
+            JsonValue::Float(_) => assert_eq!(value.as_f64(), Some(2.5)),
+            _ => assert_eq!(value.as_f64(), None),
+        }
+        match &value {
+            JsonValue::Bool(_) => assert_eq!(value.as_bool(), Some(true)),
+            _ => assert_eq!(value.as_bool(), None),
+        }
+        match &value {
+            JsonValue::Array(array) => assert_eq!(value.as_array(), Some(array)),
+            _ => assert_eq!(value.as_array(), None),
+        }
+        match &value {
+            JsonValue::Object(map) => assert_eq!(value.as_object(), Some(map)),
+            _ => assert_eq!(value.as_object(), None),
+        }
+    }
+}
+
+#[test]
+fn test_type_name() {
+    let names = ["null", "boolean", "number", "number", "string", "array", "object"];
+    for (value, name) in sample_values().iter().zip(names) {
+        assert_eq!(value.type_name(), name, "value: {}", value);
+        // Each name is a JSON Schema type that accepts the value.
+        let schema = crate::parse_str(&format!(r#"{{"type": "{}"}}"#, name)).unwrap();
+        assert_eq!(value.validate_schema(&schema), Ok(()), "value: {}", value);
+    }
+    assert_eq!(JsonValue::Number64(u64::MAX).type_name(), "number");
+    assert_eq!(JsonValue::Float(f64::NAN).type_name(), "number");
+    assert_eq!(JsonValue::Bool(false).as_str_or_err().unwrap_err().actual, JsonValue::Bool(false).type_name());
+}
+
+#[test]
+fn test_accessors_with_type_errors() {
+    let error = |expected, actual| JsonTypeError { expected, actual };
+    assert_eq!(JsonValue::Number(1).as_str_or_err(), Err(error("string", "number")));
+    assert_eq!(JsonValue::from("s").as_str_or_err(), Ok("s"));
+    assert_eq!(JsonValue::Number(-3).as_i64_or_err(), Ok(-3));
+    assert_eq!(JsonValue::Float(1.5).as_i64_or_err(), Err(error("integer", "number")));
+    assert_eq!(JsonValue::Number64(u64::MAX).as_i64_or_err(), Err(error("integer", "number")));
+    assert_eq!(JsonValue::Number(2).as_f64_or_err(), Ok(2.0));
+    assert_eq!(JsonValue::Null.as_f64_or_err(), Err(error("number", "null")));
+    assert_eq!(JsonValue::Bool(true).as_bool_or_err(), Ok(true));
+    assert_eq!(JsonValue::from("true").as_bool_or_err(), Err(error("boolean", "string")));
+    let array = crate::parse_str("[1]").unwrap();
+    assert_eq!(array.as_array_or_err(), Ok(&[JsonValue::Number(1)][..]));
+    assert_eq!(array.as_object_or_err(), Err(error("object", "array")));
+    let object = crate::parse_str("{}").unwrap();
+    assert_eq!(object.as_object_or_err(), Ok(&JsonObject::new()));
+    assert_eq!(object.as_array_or_err(), Err(error("array", "object")));
+    assert_eq!(JsonValue::Bool(false).as_str_or_err().unwrap_err().to_string(), "expected string, found boolean");
+}
+
+#[test]
+fn test_len_and_is_empty() {
+    let cases = [
+        ("{}", Some(0), Some(true)),
+        ("[]", Some(0), Some(true)),
+        ("{\"a\":1}", Some(1), Some(false)),
+        ("[1, [2, 3]]", Some(2), Some(false)),
+        ("\"\"", None, None),
+        ("0", None, None),
+        ("null", None, None),
+    ];
+    for (input, len, is_empty) in cases {
+        let value = crate::parse_str(input).unwrap();
+        assert_eq!(value.len(), len, "input: {}", input);
+        assert_eq!(value.is_empty(), is_empty, "input: {}", input);
+    }
+    let value = crate::parse_str("[{\"a\": 1, \"b\": 2}]").unwrap();
+    assert_eq!(value.as_json_array().unwrap().len(), 1);
+    assert_eq!(value.as_array().unwrap()[0].as_json_object().unwrap().len(), 2);
+    assert!(!value.as_json_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_contains_key_and_contains() {
+    let object = crate::parse_str(r#"{"a": 1, "b": null}"#).unwrap();
+    assert!(object.contains_key("a"));
+    assert!(object.contains_key("b"));
+    assert!(!object.contains_key("c"));
+    assert!(!object.contains(&JsonValue::Number(1)));
+    let array = crate::parse_str(r#"[1, "a", {"b": [2]}]"#).unwrap();
+    assert!(array.contains(&JsonValue::Number(1)));
+    assert!(array.contains(&JsonValue::from("a")));
+    assert!(array.contains(&crate::parse_str(r#"{"b": [2]}"#).unwrap()));
+    assert!(array.contains(&JsonValue::Float(1.0)));
+    assert!(!array.contains(&JsonValue::Float(1.5)));
+    assert!(!array.contains_key("a"));
+    for value in sample_values().into_iter().filter(|value| !value.is_array() && !value.is_object()) {
+        assert!(!value.contains_key("a"), "value: {}", value);
+        assert!(!value.contains(&value), "value: {}", value);
+    }
+}
+
+#[test]
+fn test_get_or_and_index_or() {
+    let config = crate::parse_str(r#"{"port": 8080, "hosts": ["a", "b"]}"#).unwrap();
+    let default = JsonValue::Number(80);
+    assert_eq!(config.get_or("port", &default), &JsonValue::Number(8080));
+    assert_eq!(config.get_or("timeout", &default), &default);
+    let null = JsonValue::Null;
+    assert_eq!(null.get_or("port", &default), &default);
+    let hosts = config.get_or("hosts", &null);
+    assert_eq!(hosts.index_or(1, &default), &JsonValue::from("b"));
+    assert_eq!(hosts.index_or(2, &default), &default);
+    assert_eq!(config.index_or(0, &default), &default);
+
+    let mut calls = 0;
+    let fallback = JsonValue::Bool(false);
+    assert_eq!(config.get_or_else("port", || { calls += 1; &fallback }), &JsonValue::Number(8080));
+    assert_eq!(hosts.index_or_else(0, || { calls += 1; &fallback }), &JsonValue::from("a"));
+    assert_eq!(calls, 0);
+    assert_eq!(config.get_or_else("missing", || { calls += 1; &fallback }), &fallback);
+    assert_eq!(hosts.index_or_else(9, || { calls += 1; &fallback }), &fallback);
+    assert_eq!(calls, 2);
+}
+
+#[test]
+fn test_flatten() {
+    let value = crate::parse_str("[[1, [2]], [[3, [4]]], 5]").unwrap();
+    let cases = [
+        (0, "[[1, [2]], [[3, [4]]], 5]"),
+        (1, "[1, [2], [3, [4]], 5]"),
+        (2, "[1, 2, 3, [4], 5]"),
+        (3, "[1, 2, 3, 4, 5]"),
+    ];
+    for (depth, expected) in cases {
+        assert_eq!(value.flatten(depth), crate::parse_str(expected).unwrap(), "depth: {}", depth);
+    }
+    assert_eq!(value.flatten_all(), crate::parse_str("[1, 2, 3, 4, 5]").unwrap());
+
+    let value = crate::parse_str(r#"[[], [{"a": [1, [2]]}], [[[]]], "s"]"#).unwrap();
+    assert_eq!(value.flatten_all(), crate::parse_str(r#"[{"a": [1, [2]]}, "s"]"#).unwrap());
+    for value in [crate::parse_str(r#"{"a": [[1]]}"#).unwrap(), JsonValue::Number(1), JsonValue::Null] {
+        assert_eq!(value.flatten_all(), value);
+    }
+}
+
+#[test]
+fn test_map_values() {
+    let value = crate::parse_str(
+        r#"{"name": "ada", "tags": ["x", ["y", {"z": "deep"}]], "age": 36, "ok": true, "none": null, "empty": {}}"#,
+    )
+    .unwrap();
+    let upper = value.map_values(|value| match &value {
+        JsonValue::String(s) => JsonValue::String(s.to_uppercase().into()),
+        _ => value,
+    });
+    let expected =
+        r#"{"name": "ADA", "tags": ["X", ["Y", {"z": "DEEP"}]], "age": 36, "ok": true, "none": null, "empty": {}}"#;
+    assert_eq!(upper, crate::parse_str(expected).unwrap());
+
+    let nulls = crate::parse_str(r#"[1, [2.5, "s"], {"a": false}, []]"#).unwrap().map_values(|_| JsonValue::Null);
+    assert_eq!(nulls, crate::parse_str(r#"[null, [null, null], {"a": null}, []]"#).unwrap());
+    assert_eq!(JsonValue::Number(1).map_values(|_| JsonValue::Bool(true)), JsonValue::Bool(true));
+}
+
+#[test]
+fn test_deep_clone() {
+    for value in sample_values() {
+        assert_eq!(value.deep_clone(), value);
+    }
+    let value = crate::parse_str(r#"{"a": [1, {"b": null}], "c": {}, "d": []}"#).unwrap();
+    let mut copy = value.deep_clone();
+    assert_eq!(copy, value);
+    copy.pointer_mut("/a/1").unwrap().insert("b", JsonValue::Bool(true));
+    copy.pointer_mut("/d").unwrap().push(JsonValue::Null);
+    assert_eq!(value, crate::parse_str(r#"{"a": [1, {"b": null}], "c": {}, "d": []}"#).unwrap());
+    assert_eq!(copy, crate::parse_str(r#"{"a": [1, {"b": true}], "c": {}, "d": [null]}"#).unwrap());
+}
+
+#[test]
+fn test_deep_clone_deep_nesting() {
+    let depth = 10_000;
+    let mut value = JsonValue::Array(vec![]);
+    for _ in 1..depth {
+        value = JsonValue::Array(vec![value]);
+    }
+    let mut copy = value.deep_clone();
+    // Compare one level at a time, since comparing the values as a whole
+    // would recurse once per level.
+    let mut levels = 0;
+    let (mut original, mut copied) = (&value, &mut copy);
+    while let (JsonValue::Array(original_array), JsonValue::Array(copied_array)) = (original, copied) {
+        levels += 1;
+        assert_eq!(original_array.len(), copied_array.len());
+        if original_array.is_empty() {
+            copied_array.push(JsonValue::Null);
+            assert!(original_array.is_empty());
+            break;
+        }
+        original = &original_array[0];
+        copied = &mut copied_array[0];
+    }
+    assert_eq!(levels, depth);
+}
+
+#[test]
+fn test_filter_keys() {
+    let value = crate::parse_str(
+        r#"{"id": 1, "password": "x", "profile": {"name": "ada", "password": "y", "tags": [{"id": 2, "secret": true}]}, "items": [{"id": 3, "password": "z"}, 4]}"#,
+    )
+    .unwrap();
+    let denied = value.filter_keys(KeyFilterMode::Deny, &["password", "secret"]);
+    let expected = r#"{"id": 1, "profile": {"name": "ada", "tags": [{"id": 2}]}, "items": [{"id": 3}, 4]}"#;
+    assert_eq!(denied, crate::parse_str(expected).unwrap());
+    assert!(denied.walk().all(|(pointer, _)| !pointer.ends_with("password") && !pointer.ends_with("secret")));
+
+    let allowed = value.filter_keys(KeyFilterMode::Allow, &["id", "profile", "tags"]);
+    let expected = r#"{"id": 1, "profile": {"tags": [{"id": 2}]}}"#;
+    assert_eq!(allowed, crate::parse_str(expected).unwrap());
+    assert_eq!(value.filter_keys(KeyFilterMode::Allow, &[]), crate::parse_str("{}").unwrap());
+    assert_eq!(value.filter_keys(KeyFilterMode::Deny, &[]), value);
+
+    for value in sample_values().into_iter().filter(|value| !value.is_object()) {
+        assert_eq!(value.filter_keys(KeyFilterMode::Allow, &[]), value, "value: {}", value);
+    }
+}
+
+#[test]
+fn test_redact() {
+    let value = crate::parse_str(
+        r#"{"user": "ada", "password": "hunter2", "session": {"token": "abc", "expires": 60}, "keys": [{"api_key": {"id": 1}, "name": "ci"}, "token"]}"#,
+    )
+    .unwrap();
+    let sensitive = |key: &str| ["password", "token", "api_key"].contains(&key);
+    let redacted = value.redact(sensitive, JsonValue::REDACTED);
+    let expected = r#"{"user": "ada", "password": "[REDACTED]", "session": {"token": "[REDACTED]", "expires": 60}, "keys": [{"api_key": "[REDACTED]", "name": "ci"}, "token"]}"#;
+    assert_eq!(redacted, crate::parse_str(expected).unwrap());
+    assert_eq!(value.pointer("/password"), Some(&JsonValue::from("hunter2")));
+
+    let nulled = value.redact(|key| key.ends_with("key"), JsonValue::Null);
+    assert_eq!(nulled.pointer("/keys/0/api_key"), Some(&JsonValue::Null));
+    assert_eq!(nulled.pointer("/password"), value.pointer("/password"));
+    assert_eq!(value.redact(|_| false, JsonValue::Null), value);
+}
+
+#[test]
+fn test_retain() {
+    let mut array = crate::parse_str("[1, 2, 3, 4, 5, 6]").unwrap();
+    array.retain_array(|value| value.as_i64().is_some_and(|n| n % 2 == 0));
+    assert_eq!(array, crate::parse_str("[2, 4, 6]").unwrap());
+    array.retain_object(|_, _| false);
+    assert_eq!(array.len(), Some(3));
+
+    for kind in [crate::JsonObjectKind::HashMap, crate::JsonObjectKind::BTreeMap] {
+        let config = crate::JsonParserConfig { preserve_order: kind, ..Default::default() };
+        let mut object = crate::JsonParser::with_config(r#"{"a": 1, "b": null, "c": [], "d": null}"#, config).parse().unwrap();
+        let mut seen = vec![];
+        object.retain_object(|key, value| {
+            seen.push(key.to_string());
+            !value.is_null()
+        });
+        seen.sort();
+        assert_eq!(seen, ["a", "b", "c", "d"]);
+        assert_eq!(object, crate::parse_str(r#"{"a": 1, "c": []}"#).unwrap());
+        object.retain_array(|_| false);
+        assert_eq!(object.len(), Some(2));
+    }
+
+    let mut value = JsonValue::from("text");
+    value.retain_array(|_| false);
+    value.retain_object(|_, _| false);
+    assert_eq!(value, JsonValue::from("text"));
+}
+
+#[test]
+fn test_mutable_accessors() {
+    let mut array = JsonValue::Array(vec![]);
+    array.as_array_mut().unwrap().push(JsonValue::Number(1));
+    assert_eq!(array, JsonValue::Array(vec![JsonValue::Number(1)]));
+    assert!(array.as_object_mut().is_none());
+
+    let mut object = JsonValue::Object(JsonObject::new());
+    object.as_object_mut().unwrap().insert("k".to_string(), JsonValue::Bool(false));
+    assert_eq!(object, JsonValue::Object(JsonObject::from([("k".to_string(), JsonValue::Bool(false))])));
+    assert!(object.as_array_mut().is_none());
+}
+
+#[test]
+fn test_object_mutation() {
+    let mut value = JsonValue::Null;
+    assert_eq!(value.remove("a"), None);
+    assert_eq!(value.insert("a", JsonValue::Number(1)), None);
+    assert_eq!(value.insert("b", JsonValue::Bool(true)), None);
+    assert_eq!(value.insert("a", JsonValue::Number(2)), Some(JsonValue::Number(1)));
+    assert_eq!(Ok(value.clone()), crate::parse_str(r#"{"a": 2, "b": true}"#));
+    assert_eq!(value.remove("b"), Some(JsonValue::Bool(true)));
+    assert_eq!(value.remove("b"), None);
+    assert_eq!(Ok(value), crate::parse_str(r#"{"a": 2}"#));
+}
+
+#[test]
+fn test_array_mutation() {
+    let mut value = JsonValue::Null;
+    assert_eq!(value.pop(), None);
+    value.push(JsonValue::Number(1));
+    value.push(JsonValue::Number(3));
+    value.insert_at(1, JsonValue::Number(2));
+    value.insert_at(0, JsonValue::Number(0));
+    value.insert_at(4, JsonValue::Number(4));
+    assert_eq!(Ok(value.clone()), crate::parse_str("[0, 1, 2, 3, 4]"));
+    assert_eq!(value.pop(), Some(JsonValue::Number(4)));
+    assert_eq!(Ok(value), crate::parse_str("[0, 1, 2, 3]"));
+}
+
+#[test]
+fn test_extend() {
+    let mut value = crate::parse_str(r#"[1, "two"]"#).unwrap();
+    let more = Vec::try_from(crate::parse_str(r#"[[3], {"four": 4}]"#).unwrap()).unwrap();
+    value.extend_array(more);
+    assert_eq!(value, crate::parse_str(r#"[1, "two", [3], {"four": 4}]"#).unwrap());
+    value.extend_array([]);
+    assert_eq!(value.len(), Some(4));
+
+    let mut value = crate::parse_str(r#"{"a": 1, "b": 2}"#).unwrap();
+    value.extend_object([
+        ("b".to_string(), JsonValue::Number(20)),
+        ("c".to_string(), JsonValue::Number(3)),
+        ("c".to_string(), JsonValue::Number(30)),
+    ]);
+    assert_eq!(value, crate::parse_str(r#"{"a": 1, "b": 20, "c": 30}"#).unwrap());
+
+    let mut value = JsonValue::Null;
+    value.extend_array([JsonValue::Bool(true)]);
+    assert_eq!(value, crate::parse_str("[true]").unwrap());
+    let mut value = JsonValue::Null;
+    value.extend_object([("a".to_string(), JsonValue::Null)]);
+    assert_eq!(value, crate::parse_str(r#"{"a": null}"#).unwrap());
+}
+
+#[cfg(test)]
+type Mutation = fn(&mut JsonValue);
+
+#[test]
+fn test_mutation_on_wrong_type_panics() {
+    let cases: [(JsonValue, Mutation, &str); 7] = [
+        (JsonValue::Array(vec![]), |value| drop(value.insert("a", JsonValue::Null)), "insert called on an array"),
+        (JsonValue::Array(vec![]), |value| drop(value.remove("a")), "remove called on an array"),
+        (JsonValue::Object(JsonObject::new()), |value| value.push(JsonValue::Null), "push called on an object"),
+        (JsonValue::String("s".into()), |value| drop(value.pop()), "pop called on a string"),
+        (JsonValue::Number(1), |value| value.insert_at(0, JsonValue::Null), "insert_at called on a number"),
+        (JsonValue::Object(JsonObject::new()), |value| value.extend_array([]), "extend_array called on an object"),
+        (JsonValue::Bool(false), |value| value.extend_object([]), "extend_object called on a bool"),
+    ];
+    for (mut value, mutate, message) in cases {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| mutate(&mut value)));
+        let panic = result.expect_err(message);
+        assert_eq!(panic.downcast_ref::<String>().map(String::as_str), Some(message));
+    }
+}
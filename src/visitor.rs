@@ -0,0 +1,196 @@
+use crate::object;
+use crate::parser::JsonValue;
+use crate::prelude::*;
+
+/// Receives the nodes of a `JsonValue` in depth-first order from
+/// `JsonValue::accept`. Every method does nothing by default.
+pub trait Visitor {
+    fn visit_null(&mut self) {}
+    fn visit_bool(&mut self, _value: bool) {}
+    fn visit_number(&mut self, _value: i64) {}
+    /// Called for `JsonValue::Number64`.
+    fn visit_number64(&mut self, _value: u64) {}
+    /// Called for `JsonValue::BigNum`.
+    #[cfg(feature = "bignum")]
+    fn visit_bignum(&mut self, _value: &bigdecimal::BigDecimal) {}
+    /// Called for `JsonValue::Float`.
+    fn visit_float(&mut self, _value: f64) {}
+    fn visit_string(&mut self, _value: &str) {}
+    fn visit_array_start(&mut self, _len: usize) {}
+    fn visit_array_end(&mut self) {}
+    fn visit_object_start(&mut self, _len: usize) {}
+    /// Called before the value of each member.
+    fn visit_key(&mut self, _key: &str) {}
+    fn visit_object_end(&mut self) {}
+}
+
+// A container whose elements are still being visited.
+enum Frame<'a> {
+    Array(core::slice::Iter<'a, JsonValue>),
+    Object(object::Iter<'a>),
+}
+
+impl JsonValue {
+    /// Walks the value depth first, calling `visitor` for every node.
+    /// Uses an explicit stack, so deeply nested values cannot overflow
+    /// the call stack.
+    pub fn accept(&self, visitor: &mut impl Visitor) {
+        let mut stack: Vec<Frame> = vec![];
+        let mut next = Some(self);
+        loop {
+            if let Some(value) = next.take() {
+                match value {
+                    JsonValue::Null => visitor.visit_null(),
+                    JsonValue::Bool(value) => visitor.visit_bool(*value),
+                    JsonValue::Number(value) => visitor.visit_number(*value),
+                    JsonValue::Number64(value) => visitor.visit_number64(*value),
+                    #[cfg(feature = "bignum")]
+                    JsonValue::BigNum(value) => visitor.visit_bignum(value),
+                    JsonValue::Float(value) => visitor.visit_float(*value),
+                    JsonValue::String(value) => visitor.visit_string(value),
+                    JsonValue::Array(array) => {
+                        visitor.visit_array_start(array.len());
+                        stack.push(Frame::Array(array.iter()));
+                    }
+                    JsonValue::Object(map) => {
+                        visitor.visit_object_start(map.len());
+                        stack.push(Frame::Object(map.iter()));
+                    }
+                }
+            }
+            match stack.last_mut() {
+                None => return,
+                Some(Frame::Array(iter)) => match iter.next() {
+                    Some(element) => next = Some(element),
+                    None => {
+                        stack.pop();
+                        visitor.visit_array_end();
+                    }
+                },
+                Some(Frame::Object(iter)) => match iter.next() {
+                    Some((key, member)) => {
+                        visitor.visit_key(key);
+                        next = Some(member);
+                    }
+                    None => {
+                        stack.pop();
+                        visitor.visit_object_end();
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct NodeCountVisitor {
+    nodes: usize,
+    keys: usize,
+    open: usize,
+}
+
+#[cfg(test)]
+impl Visitor for NodeCountVisitor {
+    fn visit_null(&mut self) {
+        self.nodes += 1;
+    }
+    fn visit_bool(&mut self, _value: bool) {
+        self.nodes += 1;
+    }
+    fn visit_number(&mut self, _value: i64) {
+        self.nodes += 1;
+    }
+    fn visit_float(&mut self, _value: f64) {
+        self.nodes += 1;
+    }
+    fn visit_string(&mut self, _value: &str) {
+        self.nodes += 1;
+    }
+    fn visit_array_start(&mut self, _len: usize) {
+        self.nodes += 1;
+        self.open += 1;
+    }
+    fn visit_array_end(&mut self) {
+        self.open -= 1;
+    }
+    fn visit_object_start(&mut self, _len: usize) {
+        self.nodes += 1;
+        self.open += 1;
+    }
+    fn visit_key(&mut self, _key: &str) {
+        self.keys += 1;
+    }
+    fn visit_object_end(&mut self) {
+        self.open -= 1;
+    }
+}
+
+// Collects string values, and keys prefixed with a colon, in the order
+// they are visited.
+#[cfg(test)]
+#[derive(Default)]
+struct StringCollectorVisitor {
+    strings: Vec<String>,
+}
+
+#[cfg(test)]
+impl Visitor for StringCollectorVisitor {
+    fn visit_string(&mut self, value: &str) {
+        self.strings.push(value.to_string());
+    }
+    fn visit_key(&mut self, key: &str) {
+        self.strings.push(format!(":{}", key));
+    }
+}
+
+#[test]
+fn test_node_count_visitor() {
+    let cases = [
+        ("null", 1, 0),
+        ("[]", 1, 0),
+        ("[1, 2.5, [true, \"x\"]]", 6, 0),
+        (r#"{"a": {"b": [null, {}]}, "c": "d"}"#, 6, 3),
+    ];
+    for (input, nodes, keys) in cases {
+        let mut visitor = NodeCountVisitor::default();
+        crate::parse_str(input).unwrap().accept(&mut visitor);
+        assert_eq!((visitor.nodes, visitor.keys, visitor.open), (nodes, keys, 0), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_string_collector_visitor() {
+    let config = crate::JsonParserConfig { preserve_order: crate::JsonObjectKind::BTreeMap, ..Default::default() };
+    let input = r#"{"z": ["one", {"y": "two"}, 3], "a": "three", "m": {}}"#;
+    let value = crate::JsonParser::with_config(input, config).parse().unwrap();
+    let mut visitor = StringCollectorVisitor::default();
+    value.accept(&mut visitor);
+    assert_eq!(visitor.strings, [":a", "three", ":m", ":z", "one", ":y", "two"]);
+}
+
+#[test]
+fn test_accept_reports_lengths_and_order() {
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+    impl Visitor for Recorder {
+        fn visit_number(&mut self, value: i64) {
+            self.0.push(value.to_string());
+        }
+        fn visit_array_start(&mut self, len: usize) {
+            self.0.push(format!("[{}", len));
+        }
+        fn visit_array_end(&mut self) {
+            self.0.push("]".to_string());
+        }
+        fn visit_object_start(&mut self, len: usize) {
+            self.0.push(format!("{{{}", len));
+        }
+        fn visit_object_end(&mut self) {
+            self.0.push("}".to_string());
+        }
+    }
+    let mut recorder = Recorder::default();
+    crate::parse_str(r#"[1, [2, 3], {"a": 4}, []]"#).unwrap().accept(&mut recorder);
+    assert_eq!(recorder.0, ["[4", "1", "[2", "2", "3", "]", "{1", "4", "}", "[0", "]", "]"]);
+}
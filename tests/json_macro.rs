@@ -0,0 +1,14 @@
+// Calls `json!` from outside the crate, where paths in the expansion
+// resolve against this crate rather than tinyserde.
+
+use tinyserde::{json, JsonValue};
+
+#[test]
+fn test_json_macro_from_another_crate() {
+    let count = 3;
+    let value = json!({"a": [1, -2, null], "b": {"count": count, "tags": ["x", true]}, "c": []});
+    let expected = tinyserde::parse_str(r#"{"a": [1, -2, null], "b": {"count": 3, "tags": ["x", true]}, "c": []}"#).unwrap();
+    assert_eq!(value, expected);
+    assert_eq!(json!([]), JsonValue::Array(Vec::new()));
+    assert_eq!(json!({("k".to_string() + "ey"): "v"}), tinyserde::parse_str(r#"{"key": "v"}"#).unwrap());
+}
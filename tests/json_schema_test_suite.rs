@@ -0,0 +1,54 @@
+// Runs the draft-07 tests from the JSON Schema Test Suite
+// (https://github.com/json-schema-org/JSON-Schema-Test-Suite) that are
+// copied into tests/testdata/json_schema.
+
+use std::fs;
+use std::path::PathBuf;
+use tinyserde::JsonValue;
+
+// Groups that need remote schemas or `$id` based references, which
+// `validate_schema` does not resolve.
+const UNSUPPORTED: &[(&str, &str)] = &[
+    ("ref.json", "remote ref, containing refs itself"),
+    ("ref.json", "Recursive references between schemas"),
+    ("ref.json", "Location-independent identifier"),
+    ("ref.json", "Location-independent identifier with absolute URI"),
+    ("ref.json", "Location-independent identifier with base URI change in subschema"),
+];
+
+fn member<'a>(value: &'a JsonValue, key: &str) -> &'a JsonValue {
+    value.as_object().unwrap().get(key).unwrap()
+}
+
+#[test]
+fn test_json_schema_suite() {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/testdata/json_schema");
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory).unwrap().map(|entry| entry.unwrap().path()).collect();
+    paths.sort();
+    let mut checked = 0;
+    for path in paths {
+        let file = path.file_name().unwrap().to_string_lossy().into_owned();
+        let groups = tinyserde::parse_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        for group in groups.as_array().unwrap() {
+            let description = member(group, "description").as_str().unwrap();
+            if UNSUPPORTED.contains(&(file.as_str(), description)) {
+                continue;
+            }
+            for test in member(group, "tests").as_array().unwrap() {
+                let result = member(test, "data").validate_schema(member(group, "schema"));
+                let valid = member(test, "valid");
+                assert_eq!(
+                    JsonValue::Bool(result.is_ok()),
+                    *valid,
+                    "{}: {}: {}: {:?}",
+                    file,
+                    description,
+                    member(test, "description"),
+                    result
+                );
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 300, "only {} tests ran", checked);
+}
@@ -0,0 +1,71 @@
+// Runs the parsing tests from JSONTestSuite
+// (https://github.com/nst/JSONTestSuite), which are copied into
+// tests/testdata. Files starting with y_ must parse, files starting with
+// n_ must not, and files starting with i_ may go either way.
+
+use std::fs;
+use std::path::PathBuf;
+
+// The i_ files that parse. Every other i_ file is rejected: numbers that do
+// not fit in an i64 or an f64, unpaired UTF-16 surrogates, input that is
+// not valid UTF-8, and a leading byte order mark.
+const ACCEPTED_IMPLEMENTATION_DEFINED: &[&str] = &[
+    // Both round to 0.0.
+    "i_number_double_huge_neg_exp.json",
+    "i_number_real_underflow.json",
+    // Within the default depth limit of 512.
+    "i_structure_500_nested_arrays.json",
+    // The byte order mark is skipped unless `reject_bom` is set.
+    "i_structure_UTF-8_BOM_empty_object.json",
+];
+
+fn test_files(prefix: &str) -> Vec<(String, Vec<u8>)> {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/testdata");
+    let mut files: Vec<(String, Vec<u8>)> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .map(|path| (path.file_name().unwrap().to_string_lossy().into_owned(), fs::read(&path).unwrap()))
+        .filter(|(name, _)| name.starts_with(prefix))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "no test files starting with {}", prefix);
+    files
+}
+
+#[test]
+fn test_must_accept() {
+    for (name, input) in test_files("y_") {
+        let value = tinyserde::parse_bytes(&input);
+        assert!(value.is_ok(), "{}: {:?}", name, value);
+        if let Ok(input) = std::str::from_utf8(&input) {
+            assert_eq!(tinyserde::JsonParser::validate(input), Ok(()), "{}", name);
+        }
+        let value = value.unwrap();
+        assert_eq!(tinyserde::parse_str(&value.to_string()), Ok(value), "{} does not round-trip", name);
+    }
+}
+
+#[test]
+fn test_must_reject() {
+    for (name, input) in test_files("n_") {
+        let value = tinyserde::parse_bytes(&input);
+        assert!(value.is_err(), "{}: {:?}", name, value);
+        if let Ok(input) = std::str::from_utf8(&input) {
+            assert!(tinyserde::JsonParser::validate(input).is_err(), "{}", name);
+        }
+    }
+}
+
+#[test]
+fn test_implementation_defined() {
+    let files = test_files("i_");
+    for name in ACCEPTED_IMPLEMENTATION_DEFINED {
+        assert!(files.iter().any(|(file, _)| file == name), "{} is missing", name);
+    }
+    for (name, input) in files {
+        let value = tinyserde::parse_bytes(&input);
+        let accepted = ACCEPTED_IMPLEMENTATION_DEFINED.contains(&name.as_str());
+        assert_eq!(value.is_ok(), accepted, "{}: {:?}", name, value);
+    }
+}
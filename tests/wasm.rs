@@ -0,0 +1,29 @@
+// Runs under Node.js with `wasm-pack test --node`, with and without the
+// default features, to check the crate on wasm32-unknown-unknown.
+#![cfg(target_arch = "wasm32")]
+
+use tinyserde::{parse_str, JsonParser, JsonParserConfig, JsonValue, ParseMode, ParserError};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn parse_and_print() {
+    let value = parse_str(r#"{ "foo": [1, 2.5, "bar"] }"#).unwrap();
+    assert_eq!(value.to_string(), r#"{"foo":[1,2.5,"bar"]}"#);
+    assert_eq!(parse_str(&value.to_string()).unwrap(), value);
+}
+
+#[wasm_bindgen_test]
+fn numbers_keep_their_precision() {
+    assert_eq!(parse_str("18446744073709551615").unwrap(), JsonValue::Number64(u64::MAX));
+    assert_eq!(parse_str("-9223372036854775808").unwrap(), JsonValue::Number(i64::MIN));
+    assert_eq!(parse_str("1e400"), Err(ParserError::NumberOverflow("1e400".to_string())));
+}
+
+#[wasm_bindgen_test]
+fn parse_with_config() {
+    let lenient = JsonParserConfig { mode: ParseMode::Lenient, ..Default::default() };
+    let value = JsonParser::with_config("[1, 2, // two\n]", lenient).parse().unwrap();
+    assert_eq!(value, parse_str("[1, 2]").unwrap());
+    let shallow = JsonParserConfig { max_depth: 1, ..Default::default() };
+    assert_eq!(JsonParser::with_config("[[]]", shallow).parse(), Err(ParserError::DepthLimitExceeded { limit: 1 }));
+}